@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS rides (
+    id TEXT PRIMARY KEY,
+    rider_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    driver_id TEXT REFERENCES transport_company_drivers(id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'requested',
+    pickup_lat DOUBLE PRECISION NOT NULL,
+    pickup_lng DOUBLE PRECISION NOT NULL,
+    pickup_address TEXT,
+    dropoff_lat DOUBLE PRECISION NOT NULL,
+    dropoff_lng DOUBLE PRECISION NOT NULL,
+    dropoff_address TEXT,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    accepted_at TIMESTAMPTZ,
+    arriving_at TIMESTAMPTZ,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    cancelled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT rides_status_check CHECK (
+        status IN ('requested', 'accepted', 'arriving', 'in_progress', 'completed', 'cancelled')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_rides_rider_id ON rides(rider_id);
+CREATE INDEX IF NOT EXISTS idx_rides_driver_id ON rides(driver_id);
+CREATE INDEX IF NOT EXISTS idx_rides_status ON rides(status);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS driver_locations (
+    driver_id TEXT PRIMARY KEY REFERENCES transport_company_drivers(id) ON DELETE CASCADE,
+    lat DOUBLE PRECISION NOT NULL,
+    lng DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_driver_locations_lat_lng ON driver_locations(lat, lng);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS ride_offers (
+    id TEXT PRIMARY KEY,
+    ride_id TEXT NOT NULL REFERENCES rides(id) ON DELETE CASCADE,
+    driver_id TEXT NOT NULL REFERENCES transport_company_drivers(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    rank INTEGER NOT NULL,
+    distance_km DOUBLE PRECISION NOT NULL,
+    offered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    responded_at TIMESTAMPTZ,
+    UNIQUE (ride_id, driver_id),
+    CONSTRAINT ride_offers_status_check CHECK (
+        status IN ('pending', 'accepted', 'declined', 'expired')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_ride_offers_ride_id ON ride_offers(ride_id);
+CREATE INDEX IF NOT EXISTS idx_ride_offers_driver_status ON ride_offers(driver_id, status);
@@ -0,0 +1,5 @@
+ALTER TABLE driver_locations
+    ADD COLUMN IF NOT EXISTS heading DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS speed DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS accuracy DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS ride_location_history (
+    id BIGSERIAL PRIMARY KEY,
+    ride_id TEXT NOT NULL REFERENCES rides(id) ON DELETE CASCADE,
+    driver_id TEXT NOT NULL REFERENCES transport_company_drivers(id) ON DELETE CASCADE,
+    lat DOUBLE PRECISION NOT NULL,
+    lng DOUBLE PRECISION NOT NULL,
+    heading DOUBLE PRECISION,
+    speed DOUBLE PRECISION,
+    accuracy DOUBLE PRECISION,
+    recorded_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ride_location_history_ride_recorded
+    ON ride_location_history(ride_id, recorded_at);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS pricing_rules (
+    id TEXT PRIMARY KEY,
+    transport_company_id TEXT REFERENCES transport_companies(id) ON DELETE CASCADE,
+    vehicle_type TEXT,
+    currency TEXT NOT NULL DEFAULT 'NGN',
+    base_fare BIGINT NOT NULL CHECK (base_fare >= 0),
+    per_km BIGINT NOT NULL CHECK (per_km >= 0),
+    per_minute BIGINT NOT NULL CHECK (per_minute >= 0),
+    minimum_fare BIGINT NOT NULL CHECK (minimum_fare >= 0),
+    booking_fee BIGINT NOT NULL CHECK (booking_fee >= 0),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One rule per (company, vehicle type) scope; NULL means "any".
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pricing_rules_scope
+    ON pricing_rules (COALESCE(transport_company_id, ''), COALESCE(vehicle_type, ''));
@@ -0,0 +1,11 @@
+ALTER TABLE rides
+    ADD COLUMN IF NOT EXISTS vehicle_type TEXT,
+    ADD COLUMN IF NOT EXISTS currency TEXT,
+    ADD COLUMN IF NOT EXISTS estimated_fare BIGINT,
+    ADD COLUMN IF NOT EXISTS final_fare BIGINT,
+    ADD COLUMN IF NOT EXISTS fare_breakdown JSONB;
+
+ALTER TABLE user_payments
+    ADD COLUMN IF NOT EXISTS ride_id TEXT REFERENCES rides(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_user_payments_ride_id ON user_payments(ride_id);
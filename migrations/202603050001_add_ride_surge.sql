@@ -0,0 +1,8 @@
+ALTER TABLE rides
+    ADD COLUMN IF NOT EXISTS surge_multiplier DOUBLE PRECISION NOT NULL DEFAULT 1.0,
+    ADD COLUMN IF NOT EXISTS surge_zone TEXT,
+    ADD COLUMN IF NOT EXISTS surge_demand INTEGER,
+    ADD COLUMN IF NOT EXISTS surge_supply INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_rides_pickup_requested
+    ON rides(pickup_lat, pickup_lng) WHERE status = 'requested';
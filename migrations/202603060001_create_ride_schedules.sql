@@ -0,0 +1,27 @@
+ALTER TABLE rides DROP CONSTRAINT IF EXISTS rides_status_check;
+ALTER TABLE rides ADD CONSTRAINT rides_status_check CHECK (
+    status IN ('scheduled', 'requested', 'accepted', 'arriving', 'in_progress', 'completed', 'cancelled')
+);
+
+ALTER TABLE rides ADD COLUMN IF NOT EXISTS scheduled_for TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS ride_schedules (
+    id TEXT PRIMARY KEY,
+    ride_id TEXT NOT NULL UNIQUE REFERENCES rides(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    pickup_at TIMESTAMPTZ NOT NULL,
+    dispatch_at TIMESTAMPTZ NOT NULL,
+    remind_at TIMESTAMPTZ NOT NULL,
+    reminder_sent_at TIMESTAMPTZ,
+    dispatched_at TIMESTAMPTZ,
+    last_dispatch_at TIMESTAMPTZ,
+    dispatch_attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ride_schedules_status_check CHECK (
+        status IN ('pending', 'dispatched', 'matched', 'cancelled', 'expired')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_ride_schedules_dispatch ON ride_schedules(status, dispatch_at);
+CREATE INDEX IF NOT EXISTS idx_ride_schedules_remind ON ride_schedules(remind_at) WHERE reminder_sent_at IS NULL;
@@ -0,0 +1,17 @@
+ALTER TABLE rides
+    ADD COLUMN IF NOT EXISTS cancelled_by TEXT,
+    ADD COLUMN IF NOT EXISTS cancellation_reason TEXT,
+    ADD COLUMN IF NOT EXISTS cancellation_note TEXT,
+    ADD COLUMN IF NOT EXISTS cancellation_fee BIGINT;
+
+ALTER TABLE rides DROP CONSTRAINT IF EXISTS rides_cancelled_by_check;
+ALTER TABLE rides ADD CONSTRAINT rides_cancelled_by_check CHECK (
+    cancelled_by IS NULL OR cancelled_by IN ('rider', 'driver', 'system')
+);
+
+-- Driver-side cancellations are reviewed per driver.
+CREATE INDEX IF NOT EXISTS idx_rides_driver_cancellations
+    ON rides(driver_id, cancelled_at) WHERE cancelled_by = 'driver';
+
+ALTER TABLE transport_company_drivers
+    ADD COLUMN IF NOT EXISTS cancellation_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS ride_ratings (
+    id TEXT PRIMARY KEY,
+    ride_id TEXT NOT NULL REFERENCES rides(id) ON DELETE CASCADE,
+    rater_role TEXT NOT NULL,
+    rater_id TEXT NOT NULL,
+    ratee_id TEXT NOT NULL,
+    score SMALLINT NOT NULL,
+    comment TEXT,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (ride_id, rater_role),
+    CONSTRAINT ride_ratings_rater_role_check CHECK (rater_role IN ('rider', 'driver')),
+    CONSTRAINT ride_ratings_score_check CHECK (score BETWEEN 1 AND 5)
+);
+
+-- Aggregates are recomputed from the latest ratings a person received.
+CREATE INDEX IF NOT EXISTS idx_ride_ratings_ratee ON ride_ratings(ratee_id, created_at DESC);
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS rating REAL,
+    ADD COLUMN IF NOT EXISTS rating_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE transport_company_drivers
+    ADD COLUMN IF NOT EXISTS rating_count INTEGER NOT NULL DEFAULT 0;
+
+-- Driver ratings used to be set directly through the API and cannot be
+-- trusted; they are rebuilt from trip ratings from now on.
+UPDATE transport_company_drivers SET rating = NULL;
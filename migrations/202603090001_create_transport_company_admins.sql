@@ -0,0 +1,20 @@
+-- Riders have always been created with a role, but the column was missing.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'User';
+
+CREATE TABLE IF NOT EXISTS transport_company_admins (
+    id TEXT PRIMARY KEY,
+    transport_company_id TEXT NOT NULL REFERENCES transport_companies(id) ON DELETE CASCADE,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'TCAdmin',
+    phone TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transport_company_admins_company
+    ON transport_company_admins(transport_company_id);
@@ -0,0 +1,30 @@
+-- A session is one sign-in on one device. Its refresh tokens form a single
+-- rotation family: replaying a used token revokes the whole session.
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    account_type TEXT NOT NULL,
+    role TEXT NOT NULL,
+    transport_company_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revoked_reason TEXT,
+    CONSTRAINT auth_sessions_account_type_check CHECK (
+        account_type IN ('rider', 'driver', 'company_admin')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_account ON auth_sessions(account_id) WHERE revoked_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL REFERENCES auth_sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_session ON refresh_tokens(session_id);
@@ -0,0 +1,16 @@
+-- Single-use password reset tokens, stored as SHA-256 digests. Requesting a
+-- new token invalidates the account's earlier ones.
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    account_type TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    CONSTRAINT password_reset_tokens_account_type_check CHECK (
+        account_type IN ('rider', 'driver')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_account ON password_reset_tokens(account_id) WHERE used_at IS NULL;
@@ -0,0 +1,40 @@
+-- Email and phone verification of riders and drivers. Changing an email or
+-- phone number clears its verification.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone_verified_at TIMESTAMPTZ;
+
+ALTER TABLE transport_company_drivers ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+ALTER TABLE transport_company_drivers ADD COLUMN IF NOT EXISTS phone_verified_at TIMESTAMPTZ;
+
+-- Accounts that exist before verification ships are trusted as they are, so
+-- riders can keep booking and drivers keep getting offers. Only accounts
+-- created from now on, and addresses changed from now on, must be verified.
+UPDATE users SET email_verified_at = NOW() WHERE email_verified_at IS NULL;
+UPDATE users SET phone_verified_at = NOW()
+    WHERE phone_verified_at IS NULL AND phone IS NOT NULL;
+UPDATE transport_company_drivers SET email_verified_at = NOW() WHERE email_verified_at IS NULL;
+UPDATE transport_company_drivers SET phone_verified_at = NOW() WHERE phone_verified_at IS NULL;
+
+-- Email links and SMS codes, stored as SHA-256 digests. A code is bound to the
+-- address it was sent to, so it stops working if the address changes.
+CREATE TABLE IF NOT EXISTS verification_codes (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    account_type TEXT NOT NULL,
+    channel TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    CONSTRAINT verification_codes_account_type_check CHECK (
+        account_type IN ('rider', 'driver')
+    ),
+    CONSTRAINT verification_codes_channel_check CHECK (
+        channel IN ('email', 'phone')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_codes_account ON verification_codes(account_id, channel, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_verification_codes_hash ON verification_codes(code_hash);
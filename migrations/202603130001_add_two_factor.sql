@@ -0,0 +1,44 @@
+-- TOTP second factor for platform and company admins.
+CREATE TABLE IF NOT EXISTS two_factor_credentials (
+    account_id TEXT PRIMARY KEY,
+    account_type TEXT NOT NULL,
+    -- Base32 shared secret; the authenticator app holds the other copy.
+    secret TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL while enrollment has not been confirmed with a first code.
+    enabled_at TIMESTAMPTZ,
+    -- Last accepted time step; codes of that step or earlier are refused.
+    last_used_step BIGINT,
+    CONSTRAINT two_factor_credentials_account_type_check CHECK (
+        account_type IN ('rider', 'company_admin')
+    )
+);
+
+CREATE TABLE IF NOT EXISTS two_factor_recovery_codes (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES two_factor_credentials(account_id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_two_factor_recovery_codes_account ON two_factor_recovery_codes(account_id) WHERE used_at IS NULL;
+
+-- Password-verified logins waiting for their second factor.
+CREATE TABLE IF NOT EXISTS login_challenges (
+    id TEXT PRIMARY KEY,
+    token_hash TEXT NOT NULL UNIQUE,
+    account_id TEXT NOT NULL,
+    account_type TEXT NOT NULL,
+    role TEXT NOT NULL,
+    transport_company_id TEXT,
+    username TEXT NOT NULL,
+    email TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ
+);
+
+-- Whether a session passed the second factor at login.
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS two_factor BOOLEAN NOT NULL DEFAULT FALSE;
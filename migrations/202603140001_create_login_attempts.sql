@@ -0,0 +1,26 @@
+-- Audit trail of login attempts, successful or not.
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id TEXT PRIMARY KEY,
+    account_type TEXT NOT NULL,
+    username TEXT NOT NULL,
+    account_id TEXT,
+    ip_address TEXT,
+    succeeded BOOLEAN NOT NULL,
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_attempts_username ON login_attempts(LOWER(username), created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_ip ON login_attempts(ip_address, created_at DESC);
+
+-- Consecutive failures per account (`account_type:username`) and per client
+-- IP. Past the threshold each failure doubles the lockout.
+CREATE TABLE IF NOT EXISTS login_throttles (
+    scope TEXT NOT NULL,
+    key TEXT NOT NULL,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMPTZ NOT NULL,
+    locked_until TIMESTAMPTZ,
+    PRIMARY KEY (scope, key),
+    CONSTRAINT login_throttles_scope_check CHECK (scope IN ('account', 'ip'))
+);
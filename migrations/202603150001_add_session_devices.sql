@@ -0,0 +1,16 @@
+-- Which device a session was opened on, for the session list, plus the
+-- push-notification token the device registered for the session.
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS device_name TEXT;
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS platform TEXT;
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS ip_address TEXT;
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS user_agent TEXT;
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS push_token TEXT;
+ALTER TABLE auth_sessions ADD COLUMN IF NOT EXISTS push_token_updated_at TIMESTAMPTZ;
+
+ALTER TABLE auth_sessions ADD CONSTRAINT auth_sessions_platform_check CHECK (
+    platform IS NULL OR platform IN ('ios', 'android', 'web', 'other')
+);
+
+-- A device token belongs to one open session at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_auth_sessions_push_token
+    ON auth_sessions(push_token) WHERE push_token IS NOT NULL AND revoked_at IS NULL;
@@ -0,0 +1,12 @@
+-- The gateway each payment went through; verification and webhooks must go
+-- back to the same one. Existing payments were all made through Paystack.
+ALTER TABLE user_payments ADD COLUMN IF NOT EXISTS gateway TEXT NOT NULL DEFAULT 'paystack';
+ALTER TABLE driver_payments ADD COLUMN IF NOT EXISTS gateway TEXT NOT NULL DEFAULT 'paystack';
+
+-- A company can send its payments through a gateway of its own, overriding
+-- the currency routing and the default.
+ALTER TABLE transport_companies ADD COLUMN IF NOT EXISTS payment_gateway TEXT;
+
+ALTER TABLE transport_companies ADD CONSTRAINT transport_companies_payment_gateway_check CHECK (
+    payment_gateway IS NULL OR payment_gateway IN ('paystack', 'flutterwave', 'mock')
+);
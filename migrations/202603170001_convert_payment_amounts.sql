@@ -0,0 +1,29 @@
+-- Payment amounts become integer minor units. The TEXT amounts were already
+-- minor units; a row that does not hold a whole number fails the migration
+-- and has to be corrected by hand first.
+ALTER TABLE user_payments ALTER COLUMN amount TYPE BIGINT USING trim(amount)::BIGINT;
+ALTER TABLE driver_payments ALTER COLUMN amount TYPE BIGINT USING trim(amount)::BIGINT;
+
+UPDATE user_payments SET currency = upper(currency) WHERE currency <> upper(currency);
+UPDATE driver_payments SET currency = upper(currency) WHERE currency <> upper(currency);
+
+ALTER TABLE user_payments ADD CONSTRAINT user_payments_amount_check CHECK (amount > 0);
+ALTER TABLE driver_payments ADD CONSTRAINT driver_payments_amount_check CHECK (amount > 0);
+ALTER TABLE user_payments ADD CONSTRAINT user_payments_currency_check CHECK (currency ~ '^[A-Z]{3}$');
+ALTER TABLE driver_payments ADD CONSTRAINT driver_payments_currency_check CHECK (currency ~ '^[A-Z]{3}$');
+
+-- What the gateway confirmed for a successful charge. A payment whose
+-- confirmed amount or currency differs from what was initialized is flagged
+-- for review and does not settle a ride.
+ALTER TABLE user_payments ADD COLUMN IF NOT EXISTS confirmed_amount BIGINT;
+ALTER TABLE user_payments ADD COLUMN IF NOT EXISTS confirmed_currency TEXT;
+ALTER TABLE user_payments ADD COLUMN IF NOT EXISTS amount_mismatch BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE driver_payments ADD COLUMN IF NOT EXISTS confirmed_amount BIGINT;
+ALTER TABLE driver_payments ADD COLUMN IF NOT EXISTS confirmed_currency TEXT;
+ALTER TABLE driver_payments ADD COLUMN IF NOT EXISTS amount_mismatch BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_user_payments_amount_mismatch
+    ON user_payments(updated_at) WHERE amount_mismatch;
+CREATE INDEX IF NOT EXISTS idx_driver_payments_amount_mismatch
+    ON driver_payments(updated_at) WHERE amount_mismatch;
@@ -0,0 +1,27 @@
+-- Refunds of user and driver payments. A refund points at its payment by
+-- reference and gateway, since payments live in two tables. Its status is
+-- moved forward by the gateway's response and its `refund.*` webhooks.
+CREATE TABLE IF NOT EXISTS refunds (
+    id TEXT PRIMARY KEY,
+    payment_reference TEXT NOT NULL,
+    gateway TEXT NOT NULL,
+    gateway_refund_id TEXT,
+    amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    reason TEXT,
+    failure_reason TEXT,
+    requested_by TEXT NOT NULL,
+    raw_payload JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+    CONSTRAINT refunds_amount_check CHECK (amount > 0),
+    CONSTRAINT refunds_status_check CHECK (
+        status IN ('pending', 'processing', 'processed', 'failed')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_refunds_payment ON refunds(payment_reference, gateway);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_refunds_gateway_refund_id
+    ON refunds(gateway, gateway_refund_id) WHERE gateway_refund_id IS NOT NULL;
@@ -0,0 +1,25 @@
+-- Every webhook delivery, authentic or not. Authentic events are
+-- deduplicated on `(gateway, event_id)` so a redelivery is applied at most
+-- once, unless applying it failed. Admins can replay stored events.
+CREATE TABLE IF NOT EXISTS webhook_events (
+    id TEXT PRIMARY KEY,
+    gateway TEXT NOT NULL,
+    event_id TEXT,
+    event TEXT,
+    reference TEXT,
+    signature_valid BOOLEAN NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    payload JSONB,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+    CONSTRAINT webhook_events_status_check CHECK (
+        status IN ('received', 'processed', 'ignored', 'failed', 'rejected')
+    )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webhook_events_event_id
+    ON webhook_events(gateway, event_id) WHERE event_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_webhook_events_received ON webhook_events(received_at DESC);
+CREATE INDEX IF NOT EXISTS idx_webhook_events_reference ON webhook_events(reference, received_at DESC);
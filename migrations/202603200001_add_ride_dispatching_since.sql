@@ -0,0 +1,4 @@
+-- Set while a dispatch loop is offering the ride to drivers, so a second
+-- loop cannot start for the same ride. A claim older than a full loop is
+-- taken to belong to a loop that died and may be taken over.
+ALTER TABLE rides ADD COLUMN IF NOT EXISTS dispatching_since TIMESTAMPTZ;
@@ -0,0 +1,6 @@
+-- Distance driven since pickup, added up as GPS history is flushed so it
+-- survives the history being trimmed on long trips. `trail_counted_until`
+-- is the newest point already counted; the next flush continues from it.
+ALTER TABLE rides
+    ADD COLUMN IF NOT EXISTS trail_distance_km DOUBLE PRECISION NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS trail_counted_until TIMESTAMPTZ;
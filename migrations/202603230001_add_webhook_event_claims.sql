@@ -0,0 +1,4 @@
+-- When the event was last taken up for processing. An event left `received`
+-- for too long belongs to a delivery that died mid-way and is taken up again
+-- by the next redelivery.
+ALTER TABLE webhook_events ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMPTZ;
@@ -0,0 +1,6 @@
+-- Drivers sign in from `transport_company_drivers`; the legacy `drivers`
+-- table is no longer written. Point driver payments at the accounts that
+-- actually pay. Rows from the legacy table are left as they are.
+ALTER TABLE driver_payments DROP CONSTRAINT IF EXISTS driver_payments_driver_id_fkey;
+ALTER TABLE driver_payments ADD CONSTRAINT driver_payments_driver_id_fkey
+    FOREIGN KEY (driver_id) REFERENCES transport_company_drivers(id) ON DELETE CASCADE NOT VALID;
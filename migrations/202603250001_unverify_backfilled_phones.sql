@@ -0,0 +1,18 @@
+-- Phone numbers were marked verified when verification shipped without ever
+-- receiving a code. Clear every phone verification that no consumed SMS code
+-- backs, so those numbers go through verification. Emails stay grandfathered.
+UPDATE users u SET phone_verified_at = NULL
+    WHERE phone_verified_at IS NOT NULL
+    AND NOT EXISTS (
+        SELECT 1 FROM verification_codes v
+        WHERE v.account_id = u.id AND v.account_type = 'rider' AND v.channel = 'phone'
+        AND v.destination = u.phone AND v.consumed_at IS NOT NULL
+    );
+
+UPDATE transport_company_drivers d SET phone_verified_at = NULL, is_available = FALSE
+    WHERE phone_verified_at IS NOT NULL
+    AND NOT EXISTS (
+        SELECT 1 FROM verification_codes v
+        WHERE v.account_id = d.id AND v.account_type = 'driver' AND v.channel = 'phone'
+        AND v.destination = d.phone AND v.consumed_at IS NOT NULL
+    );
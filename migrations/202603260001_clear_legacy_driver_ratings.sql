@@ -0,0 +1,4 @@
+-- Driver ratings used to be set directly through the API and cannot be
+-- trusted; they are rebuilt from trip ratings. Ratings already rebuilt from
+-- trips (`rating_count > 0`) are kept.
+UPDATE transport_company_drivers SET rating = NULL WHERE rating_count = 0;
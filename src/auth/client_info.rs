@@ -0,0 +1,116 @@
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{HeaderMap, header, request::Parts};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+/// Where a request came from, for login lockouts and the session list.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| *peer);
+
+        Ok(ClientInfo {
+            ip_address: client_ip(&parts.headers, peer),
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.chars().take(512).collect()),
+        })
+    }
+}
+
+/// `X-Forwarded-For` is only trusted when `TRUST_PROXY_HEADERS=true`,
+/// otherwise clients could pick their own IP.
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<String> {
+    let trust_proxy = std::env::var("TRUST_PROXY_HEADERS")
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    let hops: usize = std::env::var("TRUSTED_PROXY_HOPS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|hops| *hops > 0)
+        .unwrap_or(1);
+
+    trust_proxy
+        .then(|| forwarded_client_ip(headers, hops))
+        .flatten()
+        .or_else(|| peer.map(|peer| peer.ip().to_string()))
+}
+
+/// Each of the `hops` proxies in front of us appends the address it saw, so
+/// the client is that many entries from the right; anything further left was
+/// sent by the client and is ignored.
+fn forwarded_client_ip(headers: &HeaderMap, hops: usize) -> Option<String> {
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    forwarded
+        .len()
+        .checked_sub(hops)
+        .and_then(|index| forwarded[index].parse::<IpAddr>().ok())
+        .map(|client| client.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn forwarded(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn takes_the_address_seen_by_the_nearest_proxy() {
+        let headers = forwarded(&["10.0.0.1, 203.0.113.7"]);
+        assert_eq!(
+            forwarded_client_ip(&headers, 1).as_deref(),
+            Some("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn ignores_entries_the_client_prepended() {
+        let headers = forwarded(&["1.2.3.4, 203.0.113.7, 198.51.100.2"]);
+        assert_eq!(
+            forwarded_client_ip(&headers, 2).as_deref(),
+            Some("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn reads_repeated_headers_in_order() {
+        let headers = forwarded(&["203.0.113.7", "198.51.100.2"]);
+        assert_eq!(
+            forwarded_client_ip(&headers, 2).as_deref(),
+            Some("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn rejects_short_or_malformed_chains() {
+        assert_eq!(forwarded_client_ip(&forwarded(&["203.0.113.7"]), 2), None);
+        assert_eq!(forwarded_client_ip(&forwarded(&["not-an-ip"]), 1), None);
+        assert_eq!(forwarded_client_ip(&HeaderMap::new(), 1), None);
+    }
+}
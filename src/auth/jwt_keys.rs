@@ -0,0 +1,268 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use ring::rand::SystemRandom;
+use ring::rsa::PublicKeyComponents;
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+/// The signing keys from the `JWT_KEYS_FILE` manifest. Each key has its
+/// own `kid`; rotation follows the `active_from` and `retire_at` schedule.
+pub struct KeySet {
+    keys: Vec<SigningKey>,
+}
+
+/// One entry of the `JWT_KEYS_FILE` manifest.
+#[derive(Debug, Deserialize)]
+struct KeyManifestEntry {
+    kid: String,
+    /// `RS256` or `EdDSA`.
+    algorithm: String,
+    /// PEM private key; relative paths are resolved against the manifest.
+    private_key_file: String,
+    /// The key signs new tokens from this time on, until a newer key takes
+    /// over. Before it, the key is only published.
+    #[serde(default)]
+    active_from: Option<DateTime<Utc>>,
+    /// The key is dropped from verification and the JWKS at this time.
+    #[serde(default)]
+    retire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyManifest {
+    keys: Vec<KeyManifestEntry>,
+}
+
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub active_from: Option<DateTime<Utc>>,
+    pub retire_at: Option<DateTime<Utc>>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl SigningKey {
+    fn is_published(&self, now: DateTime<Utc>) -> bool {
+        self.retire_at.is_none_or(|retire_at| retire_at > now)
+    }
+
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.is_published(now)
+            && self
+                .active_from
+                .is_none_or(|active_from| active_from <= now)
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+}
+
+impl KeySet {
+    /// Without a key file a throwaway Ed25519 key is generated, which is
+    /// only fit for development: tokens stop verifying on restart.
+    pub fn from_env() -> Result<Self, String> {
+        let keys = match std::env::var("JWT_KEYS_FILE") {
+            Ok(path) => load_manifest(&path)?,
+            Err(_) => {
+                warn!("JWT_KEYS_FILE is not set, signing tokens with an ephemeral key");
+                vec![ephemeral_key()?]
+            }
+        };
+
+        for key in &keys {
+            info!(
+                kid = %key.kid,
+                algorithm = ?key.algorithm,
+                active_from = ?key.active_from,
+                retire_at = ?key.retire_at,
+                "JWT signing key loaded"
+            );
+        }
+
+        Ok(KeySet { keys })
+    }
+
+    /// The key new tokens are signed with: the most recently activated one.
+    pub fn signing_key(&self) -> Result<&SigningKey, String> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .filter(|key| key.is_active(now))
+            .max_by_key(|key| key.active_from)
+            .ok_or_else(|| "No active JWT signing key".to_string())
+    }
+
+    /// A key that may still verify tokens.
+    pub fn verification_key(&self, kid: &str) -> Option<&SigningKey> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .find(|key| key.kid == kid && key.is_published(now))
+    }
+
+    /// Public keys for `/.well-known/jwks.json`, including keys scheduled
+    /// to activate so verifiers can cache them ahead of the rotation.
+    pub fn jwks(&self) -> JwkSet {
+        let now = Utc::now();
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| key.is_published(now))
+                .map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn load_manifest(path: &str) -> Result<Vec<SigningKey>, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let manifest: KeyManifest =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid {}: {}", path, e))?;
+    let base_dir = std::path::Path::new(path)
+        .parent()
+        .unwrap_or(std::path::Path::new("."));
+
+    let mut keys: Vec<SigningKey> = Vec::new();
+    for entry in manifest.keys {
+        if keys.iter().any(|key| key.kid == entry.kid) {
+            return Err(format!("Duplicate key id {}", entry.kid));
+        }
+        if let (Some(active_from), Some(retire_at)) = (entry.active_from, entry.retire_at)
+            && retire_at <= active_from
+        {
+            return Err(format!("Key {} retires before it activates", entry.kid));
+        }
+
+        let pem_bytes = std::fs::read(base_dir.join(&entry.private_key_file))
+            .map_err(|e| format!("Cannot read key {}: {}", entry.kid, e))?;
+        keys.push(load_key(entry, &pem_bytes)?);
+    }
+
+    if keys.is_empty() {
+        return Err("The key manifest lists no keys".to_string());
+    }
+
+    Ok(keys)
+}
+
+fn load_key(entry: KeyManifestEntry, pem_bytes: &[u8]) -> Result<SigningKey, String> {
+    let invalid = |e: String| format!("Invalid key {}: {}", entry.kid, e);
+    let pem = pem::parse(pem_bytes).map_err(|e| invalid(e.to_string()))?;
+
+    let (algorithm, encoding_key, public) = match entry.algorithm.as_str() {
+        "RS256" => {
+            let key_pair = match pem.tag() {
+                "RSA PRIVATE KEY" => RsaKeyPair::from_der(pem.contents()),
+                _ => RsaKeyPair::from_pkcs8(pem.contents()),
+            }
+            .map_err(|e| invalid(e.to_string()))?;
+            let encoding_key =
+                EncodingKey::from_rsa_pem(pem_bytes).map_err(|e| invalid(e.to_string()))?;
+            let components = PublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+            (
+                Algorithm::RS256,
+                encoding_key,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(components.n),
+                    e: URL_SAFE_NO_PAD.encode(components.e),
+                }),
+            )
+        }
+        "EdDSA" => {
+            let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pem.contents())
+                .map_err(|e| invalid(e.to_string()))?;
+            (
+                Algorithm::EdDSA,
+                EncodingKey::from_ed_der(pem.contents()),
+                ed25519_parameters(&key_pair),
+            )
+        }
+        other => return Err(invalid(format!("unsupported algorithm {}", other))),
+    };
+
+    build_key(
+        entry.kid,
+        algorithm,
+        entry.active_from,
+        entry.retire_at,
+        encoding_key,
+        public,
+    )
+}
+
+fn ephemeral_key() -> Result<SigningKey, String> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| "Failed to generate a signing key".to_string())?;
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|e| e.to_string())?;
+
+    build_key(
+        format!("ephemeral-{}", Utc::now().timestamp()),
+        Algorithm::EdDSA,
+        None,
+        None,
+        EncodingKey::from_ed_der(pkcs8.as_ref()),
+        ed25519_parameters(&key_pair),
+    )
+}
+
+fn ed25519_parameters(key_pair: &Ed25519KeyPair) -> AlgorithmParameters {
+    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+        key_type: OctetKeyPairType::OctetKeyPair,
+        curve: EllipticCurve::Ed25519,
+        x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+    })
+}
+
+fn build_key(
+    kid: String,
+    algorithm: Algorithm,
+    active_from: Option<DateTime<Utc>>,
+    retire_at: Option<DateTime<Utc>>,
+    encoding_key: EncodingKey,
+    public: AlgorithmParameters,
+) -> Result<SigningKey, String> {
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                Algorithm::RS256 => KeyAlgorithm::RS256,
+                _ => KeyAlgorithm::EdDSA,
+            }),
+            key_id: Some(kid.clone()),
+            ..Default::default()
+        },
+        algorithm: public,
+    };
+    let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| e.to_string())?;
+
+    Ok(SigningKey {
+        kid,
+        algorithm,
+        active_from,
+        retire_at,
+        encoding_key,
+        decoding_key,
+        jwk,
+    })
+}
@@ -1,12 +1,15 @@
 use axum::{
     extract::Request,
-    http::{StatusCode, header},
+    http::{HeaderValue, header},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use sqlx::PgPool;
 use tracing::{debug, error};
 
 use super::password_utils::verify_token;
+use crate::error::AppError;
+use crate::services::auth_service::is_session_active;
 
 /// Middleware to verify JWT token from Authorization header
 pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, Response> {
@@ -22,7 +25,9 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, R
         Some(header) => header,
         None => {
             error!("Missing Authorization header");
-            return Err((StatusCode::UNAUTHORIZED, "Missing Authorization header").into_response());
+            return Err(
+                AppError::Unauthorized("Missing Authorization header".to_string()).into_response(),
+            );
         }
     };
 
@@ -31,11 +36,10 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, R
         Some(token) => token,
         None => {
             error!("Invalid Authorization header format");
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                "Invalid Authorization header format. Expected: Bearer <token>",
+            return Err(AppError::Unauthorized(
+                "Invalid Authorization header format. Expected: Bearer <token>".to_string(),
             )
-                .into_response());
+            .into_response());
         }
     };
 
@@ -47,32 +51,53 @@ pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, R
         }
         Err(e) => {
             error!(error = %e, "Token verification failed");
-            return Err((StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response());
+            return Err(
+                AppError::Unauthorized("Invalid or expired token".to_string()).into_response(),
+            );
         }
     };
 
+    // Tokens stay valid only while their session is open
+    let Some(pool) = req.extensions().get::<PgPool>().cloned() else {
+        error!("Database pool missing from request extensions");
+        return Err(AppError::Internal(
+            "Database pool missing from request extensions".to_string(),
+        )
+        .into_response());
+    };
+    match is_session_active(&pool, &claims.sid).await {
+        Ok(true) => {}
+        Ok(false) => {
+            error!(user_id = %claims.sub, session_id = %claims.sid, "Token belongs to a revoked session");
+            return Err(
+                AppError::Unauthorized("Session has been revoked".to_string()).into_response(),
+            );
+        }
+        Err(e) => {
+            error!(error = %e, "Session lookup failed");
+            return Err(e.into_response());
+        }
+    }
+
     // Insert claims into request extensions so handlers can access it
     req.extensions_mut().insert(claims);
 
     Ok(next.run(req).await)
 }
 
-/// Optional middleware that allows requests without auth but adds claims if token is present
-pub async fn optional_auth_middleware(mut req: Request, next: Next) -> Result<Response, Response> {
-    debug!("Running optional auth middleware");
-
-    if let Some(auth_header) = req
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
+/// Auth for WebSocket upgrades. Browsers cannot set headers on a WebSocket
+/// handshake, so the token may also be passed as `?access_token=<token>`.
+pub async fn ws_auth_middleware(mut req: Request, next: Next) -> Result<Response, Response> {
+    if !req.headers().contains_key(header::AUTHORIZATION)
+        && let Some(token) = req.uri().query().and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("access_token="))
+        })
+        && let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", token))
     {
-        if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            if let Ok(claims) = verify_token(token) {
-                debug!(user_id = %claims.sub, role = %claims.role, "Token verified successfully");
-                req.extensions_mut().insert(claims);
-            }
-        }
+        req.headers_mut().insert(header::AUTHORIZATION, value);
     }
 
-    Ok(next.run(req).await)
+    auth_middleware(req, next).await
 }
@@ -1,2 +1,6 @@
+pub mod client_info;
+pub mod jwt_keys;
 pub mod middleware;
 pub mod password_utils;
+pub mod permissions;
+pub mod totp;
@@ -2,15 +2,45 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
 };
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
+use std::sync::OnceLock;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
-struct AuthConfig {
-    jwt_secret: String,
-    jwt_expiry_hours: i64,
+use super::jwt_keys::KeySet;
+
+static AUTH_CONFIG: OnceLock<AuthConfig> = OnceLock::new();
+
+/// Token settings, loaded once at startup.
+pub struct AuthConfig {
+    /// Lifetime of access tokens, from `JWT_EXPIRATION_SECONDS`. Access
+    /// tokens are short-lived; sessions are kept alive with refresh tokens.
+    pub access_token_ttl: Duration,
+    pub keys: KeySet,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let seconds = std::env::var("JWT_EXPIRATION_SECONDS")
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(900);
+
+        Ok(AuthConfig {
+            access_token_ttl: Duration::seconds(seconds),
+            keys: KeySet::from_env()?,
+        })
+    }
+
+    /// The process-wide config. `main` loads it before serving so a bad key
+    /// setup stops the server at startup.
+    pub fn global() -> &'static AuthConfig {
+        AUTH_CONFIG.get_or_init(|| {
+            AuthConfig::from_env().unwrap_or_else(|e| panic!("Invalid JWT configuration: {}", e))
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,55 +48,82 @@ pub struct Claims {
     pub sub: String, // user id
     pub exp: usize,  // expiry timestamp
     pub role: String,
+    /// Transport company of a driver or company admin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<String>,
+    /// Session the token was issued for; revoking the session revokes it.
+    pub sid: String,
+    /// Whether the session passed a second factor at login.
+    #[serde(default)]
+    pub two_factor: bool,
 }
 
 pub fn hash_password(password: &str) -> Result<String, String> {
     let salt = SaltString::generate(&mut OsRng);
     let result = Argon2::default()
         .hash_password(password.as_bytes(), &salt)
-        .unwrap()
+        .map_err(|e| e.to_string())?
         .to_string();
     Ok(result)
 }
 
 pub fn verify_password(password: &str, hash: &str) -> bool {
     use argon2::{Argon2, PasswordHash, PasswordVerifier};
-    let parsed_hash = PasswordHash::new(hash).unwrap();
+    // A corrupt stored hash must fail the login, not take the server down
+    let parsed_hash = match PasswordHash::new(hash) {
+        Ok(parsed_hash) => parsed_hash,
+        Err(e) => {
+            tracing::error!(error = %e, "Stored password hash is malformed");
+            return false;
+        }
+    };
     Argon2::default()
         .verify_password(password.as_bytes(), &parsed_hash)
         .is_ok()
 }
 
-pub fn create_token(user_id: &str, role: &str) -> Result<String, String> {
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .map_err(|_| "Failed to read JWT_SECRET environment variable".to_string())?;
-    let jwt_expiry_hours = std::env::var("JWT_EXPIRATION_SECONDS")
-        .map_err(|_| "Failed to read JWT_EXPIRATION_SECONDS environment variable".to_string())?;
-    let jwt_expiry_hours = jwt_expiry_hours
-        .parse::<i64>()
-        .map_err(|_| "Failed to parse JWT_EXPIRATION_SECONDS as integer".to_string())?;
-    let expiry = Utc::now() + Duration::hours(jwt_expiry_hours);
+pub fn access_token_ttl() -> Duration {
+    AuthConfig::global().access_token_ttl
+}
+
+pub fn create_token(
+    user_id: &str,
+    role: &str,
+    company_id: Option<&str>,
+    session_id: &str,
+    two_factor: bool,
+    expires_at: DateTime<Utc>,
+) -> Result<String, String> {
+    let key = AuthConfig::global().keys.signing_key()?;
     let claims = Claims {
         sub: user_id.to_string(),
-        exp: expiry.timestamp() as usize,
+        exp: expires_at.timestamp() as usize,
         role: role.to_string(),
+        company_id: company_id.map(str::to_string),
+        sid: session_id.to_string(),
+        two_factor,
     };
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(jwt_secret.as_bytes()),
-    )
-    .map_err(|_| "Failed to create JWT token".into())
+    let mut header = Header::new(key.algorithm);
+    header.kid = Some(key.kid.clone());
+    encode(&header, &claims, key.encoding_key()).map_err(|_| "Failed to create JWT token".into())
 }
 
+/// Verifies a token against the key named by its `kid` header. Tokens
+/// signed with a retired or unknown key are refused.
 pub fn verify_token(token: &str) -> Result<Claims, String> {
-    let jwt_secret = std::env::var("JWT_SECRET")
-        .map_err(|_| "Failed to read JWT_SECRET environment variable".to_string())?;
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(jwt_secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    .map_err(|_| "Failed to verify JWT token".into())
+    let header = decode_header(token).map_err(|_| "Failed to verify JWT token".to_string())?;
+    let key = header
+        .kid
+        .as_deref()
+        .and_then(|kid| AuthConfig::global().keys.verification_key(kid))
+        .ok_or_else(|| "Failed to verify JWT token".to_string())?;
+
+    decode::<Claims>(token, key.decoding_key(), &Validation::new(key.algorithm))
+        .map(|data| data.claims)
+        .map_err(|_| "Failed to verify JWT token".into())
+}
+
+/// Public signing keys, for services that verify RideNow tokens.
+pub fn jwks() -> JwkSet {
+    AuthConfig::global().keys.jwks()
 }
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use axum::{
+    Extension,
+    extract::{Path, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error};
+
+use super::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
+use crate::services::two_factor_service::TwoFactorConfig;
+
+/// Kinds of account a token can belong to, derived from `Claims.role`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Rider,
+    Driver,
+    CompanyAdmin,
+    PlatformAdmin,
+}
+
+impl Role {
+    /// Maps the role stored on accounts (and copied into tokens) to a role.
+    pub fn from_claim(value: &str) -> Option<Role> {
+        match value {
+            "User" => Some(Role::Rider),
+            "TCDriver" => Some(Role::Driver),
+            "TCAdmin" => Some(Role::CompanyAdmin),
+            "PlatformAdmin" => Some(Role::PlatformAdmin),
+            _ => None,
+        }
+    }
+
+    /// Table family the account lives in. Platform admins are promoted riders.
+    pub fn account_type(&self) -> AccountType {
+        match self {
+            Role::Rider | Role::PlatformAdmin => AccountType::Rider,
+            Role::Driver => AccountType::Driver,
+            Role::CompanyAdmin => AccountType::CompanyAdmin,
+        }
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Rider => &[Permission::RequestRides],
+            Role::Driver => &[Permission::DriveRides],
+            Role::CompanyAdmin => &[Permission::ManageFleet, Permission::ManagePricing],
+            Role::PlatformAdmin => &[
+                Permission::ManageUsers,
+                Permission::ManageCompanies,
+                Permission::ManageFleet,
+                Permission::ManagePricing,
+                Permission::ManagePayments,
+                Permission::ViewAdminDashboard,
+            ],
+        }
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/// Actions guarded by role. Ownership (own account, own company) is checked
+/// separately on top of these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Book rides and pay for them.
+    RequestRides,
+    /// Report locations, receive offers and run trips.
+    DriveRides,
+    /// Read and change any user account.
+    ManageUsers,
+    /// Create, change and remove transport companies.
+    ManageCompanies,
+    /// Vehicles, drivers and admins of a company.
+    ManageFleet,
+    ManagePricing,
+    /// Every payment across the platform.
+    ManagePayments,
+    ViewAdminDashboard,
+}
+
+/// The caller as seen by authorization checks.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub id: String,
+    pub role: Role,
+    pub company_id: Option<String>,
+}
+
+impl Principal {
+    pub fn from_claims(claims: &Claims) -> Result<Principal, String> {
+        let role = Role::from_claim(&claims.role)
+            .ok_or_else(|| format!("Unknown role: {}", claims.role))?;
+        Ok(Principal {
+            id: claims.sub.clone(),
+            role,
+            company_id: claims.company_id.clone(),
+        })
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.role.has(permission)
+    }
+
+    pub fn is_platform_admin(&self) -> bool {
+        self.role == Role::PlatformAdmin
+    }
+
+    /// Platform admins reach every company; company-scoped accounts only
+    /// their own. `None` stands for platform-wide resources.
+    pub fn can_access_company(&self, company_id: Option<&str>) -> bool {
+        self.is_platform_admin()
+            || (company_id.is_some() && self.company_id.as_deref() == company_id)
+    }
+}
+
+/// Company-owned resource addressed by a route's path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompanyResource {
+    /// `{id}` or `{transport_company_id}` is the company itself.
+    Company,
+    /// `{id}` is a vehicle.
+    Vehicle,
+    /// `{id}` is a driver, who may also read their own record.
+    Driver,
+}
+
+// ============================================================================
+// Route Guards
+// ============================================================================
+
+/// Route layer requiring a permission:
+/// `.route_layer(middleware::from_fn_with_state(Permission::ManageUsers, require_permission))`.
+/// Must run after `auth_middleware`.
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    Extension(claims): Extension<Claims>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let Some(principal) = principal(&claims) else {
+        return Err(forbidden());
+    };
+    if missing_two_factor(&claims) {
+        return Err(two_factor_required());
+    }
+    if !principal.can(permission) {
+        error!(user_id = %claims.sub, role = %claims.role, permission = ?permission, "Permission denied");
+        return Err(forbidden());
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Route layer for per-account resources (`{id}`, `{user_id}` or
+/// `{driver_id}`): the account itself passes, anyone else needs `permission`.
+pub async fn require_self_or_permission(
+    State(permission): State<Permission>,
+    Extension(claims): Extension<Claims>,
+    Path(params): Path<HashMap<String, String>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let Some(principal) = principal(&claims) else {
+        return Err(forbidden());
+    };
+    if missing_two_factor(&claims) {
+        return Err(two_factor_required());
+    }
+    let owner = ["id", "user_id", "driver_id"]
+        .iter()
+        .find_map(|key| params.get(*key));
+
+    if owner != Some(&principal.id) && !principal.can(permission) {
+        error!(user_id = %claims.sub, owner = ?owner, permission = ?permission, "Access to another account denied");
+        return Err(forbidden());
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Route layer for company-owned resources. Requires `ManageFleet` and that
+/// the resource belongs to the caller's company, unless the caller is a
+/// platform admin. A driver may read their own record.
+pub async fn require_company_access(
+    State(resource): State<CompanyResource>,
+    Extension(claims): Extension<Claims>,
+    Extension(pool): Extension<PgPool>,
+    Path(params): Path<HashMap<String, String>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let Some(principal) = principal(&claims) else {
+        return Err(forbidden());
+    };
+    if missing_two_factor(&claims) {
+        return Err(two_factor_required());
+    }
+    let Some(id) = params
+        .get("transport_company_id")
+        .or_else(|| params.get("id"))
+    else {
+        return Err(forbidden());
+    };
+
+    if resource == CompanyResource::Driver && *req.method() == Method::GET && *id == principal.id {
+        return Ok(next.run(req).await);
+    }
+    if !principal.can(Permission::ManageFleet) {
+        error!(user_id = %claims.sub, role = %claims.role, "Fleet access denied");
+        return Err(forbidden());
+    }
+    if principal.is_platform_admin() {
+        return Ok(next.run(req).await);
+    }
+
+    let company_id = match resource {
+        CompanyResource::Company => Some(id.clone()),
+        CompanyResource::Vehicle => {
+            owning_company(
+                &pool,
+                "SELECT transport_company_id FROM vehicles WHERE id = $1",
+                id,
+            )
+            .await?
+        }
+        CompanyResource::Driver => {
+            owning_company(
+                &pool,
+                "SELECT transport_company_id FROM transport_company_drivers WHERE id = $1",
+                id,
+            )
+            .await?
+        }
+    };
+
+    if !principal.can_access_company(company_id.as_deref()) {
+        error!(user_id = %claims.sub, company_id = ?company_id, "Access to another company denied");
+        return Err(forbidden());
+    }
+
+    debug!(user_id = %claims.sub, company_id = ?company_id, "Company access granted");
+    Ok(next.run(req).await)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+async fn owning_company(pool: &PgPool, query: &str, id: &str) -> Result<Option<String>, Response> {
+    sqlx::query(query)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map(|row| row.map(|row| row.get("transport_company_id")))
+        .map_err(|e| {
+            error!(error = %e, "Failed to resolve owning company");
+            AppError::from(e).into_response()
+        })
+}
+
+fn principal(claims: &Claims) -> Option<Principal> {
+    Principal::from_claims(claims)
+        .map_err(|e| error!(user_id = %claims.sub, error = %e, "Token carries an unknown role"))
+        .ok()
+}
+
+/// Roles listed in `TWO_FACTOR_REQUIRED_ROLES` reach guarded routes only
+/// with a session that passed the second factor.
+fn missing_two_factor(claims: &Claims) -> bool {
+    let missing = !claims.two_factor && TwoFactorConfig::from_env().is_required_for(&claims.role);
+    if missing {
+        error!(user_id = %claims.sub, role = %claims.role, "Two-factor authentication required");
+    }
+    missing
+}
+
+fn two_factor_required() -> Response {
+    AppError::Forbidden(
+        "Two-factor authentication required: enroll at /auth/2fa/enroll and sign in again"
+            .to_string(),
+    )
+    .into_response()
+}
+
+fn forbidden() -> Response {
+    AppError::Forbidden("Access denied".to_string()).into_response()
+}
@@ -0,0 +1,8 @@
+/// Reads a setting from the environment, falling back to `default` when it
+/// is unset or does not parse.
+pub fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
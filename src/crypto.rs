@@ -0,0 +1,5 @@
+/// Compares two secrets without leaking through timing how much of them
+/// matched. Used for TOTP codes and webhook secrets.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
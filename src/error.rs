@@ -0,0 +1,190 @@
+use axum::Json;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+use std::fmt;
+use tracing::error;
+
+/// Error returned by services and handlers. Each variant maps to an HTTP
+/// status and a stable `code` that clients can branch on:
+///
+/// ```json
+/// { "status": "error", "code": "CONFLICT", "message": "Email is already in use", "details": { "field": "email" } }
+/// ```
+#[derive(Debug)]
+pub enum AppError {
+    /// The request is malformed or breaks a business rule.
+    Validation(String),
+    NotFound(String),
+    /// The request clashes with existing data, e.g. a taken email.
+    Conflict {
+        message: String,
+        field: Option<String>,
+    },
+    Unauthorized(String),
+    Forbidden(String),
+    /// Too many attempts; the client may retry after the given delay.
+    RateLimited {
+        message: String,
+        retry_after_seconds: i64,
+    },
+    /// The payment provider failed or refused the request.
+    PaymentGateway(String),
+    /// A bug or an infrastructure failure. The message is logged, never
+    /// sent to the client.
+    Internal(String),
+}
+
+impl AppError {
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+            field: None,
+        }
+    }
+
+    pub fn rate_limited(message: impl Into<String>, retry_after_seconds: i64) -> Self {
+        AppError::RateLimited {
+            message: message.into(),
+            retry_after_seconds,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) => "VALIDATION_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict { .. } => "CONFLICT",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::RateLimited { .. } => "RATE_LIMITED",
+            AppError::PaymentGateway(_) => "PAYMENT_GATEWAY_ERROR",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::PaymentGateway(_) => StatusCode::BAD_GATEWAY,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message shown to the client.
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Validation(message)
+            | AppError::NotFound(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::PaymentGateway(message)
+            | AppError::Conflict { message, .. }
+            | AppError::RateLimited { message, .. } => message,
+            AppError::Internal(_) => "Internal server error",
+        }
+    }
+
+    fn details(&self) -> Value {
+        match self {
+            AppError::Conflict {
+                field: Some(field), ..
+            } => json!({ "field": field }),
+            AppError::RateLimited {
+                retry_after_seconds,
+                ..
+            } => json!({ "retry_after_seconds": retry_after_seconds }),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Internal(message) => write!(f, "{}", message),
+            other => write!(f, "{}", other.message()),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        if let AppError::Internal(message) = &self {
+            error!(error = %message, "Internal error");
+        }
+
+        let body = json!({
+            "status": "error",
+            "code": self.code(),
+            "message": self.message(),
+            "details": self.details(),
+        });
+        let mut response = (self.status(), Json(body)).into_response();
+
+        if let AppError::RateLimited {
+            retry_after_seconds,
+            ..
+        } = self
+            && let Ok(value) = HeaderValue::from_str(&retry_after_seconds.to_string())
+        {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+
+        response
+    }
+}
+
+/// Missing rows become not found, unique violations become conflicts naming
+/// the column, broken foreign keys and check constraints become validation
+/// errors, and anything else is an internal error.
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if let sqlx::Error::RowNotFound = err {
+            return AppError::NotFound("Record not found".to_string());
+        }
+        if let sqlx::Error::Database(db_err) = &err {
+            match db_err.code().as_deref() {
+                Some("23505") => {
+                    let field = conflict_field(db_err.table(), db_err.constraint());
+                    let message = match &field {
+                        Some(field) => format!("{} is already in use", capitalize(field)),
+                        None => "Record already exists".to_string(),
+                    };
+                    return AppError::Conflict { message, field };
+                }
+                Some("23503") => {
+                    return AppError::Validation("Referenced record does not exist".to_string());
+                }
+                Some("23514") | Some("22P02") => {
+                    return AppError::Validation("Invalid value".to_string());
+                }
+                _ => {}
+            }
+        }
+        AppError::Internal(err.to_string())
+    }
+}
+
+/// Column behind a default `<table>_<column>_key` unique constraint.
+fn conflict_field(table: Option<&str>, constraint: Option<&str>) -> Option<String> {
+    let field = constraint?
+        .strip_prefix(table?)?
+        .strip_prefix('_')?
+        .strip_suffix("_key")?;
+    Some(field.to_string())
+}
+
+fn capitalize(field: &str) -> String {
+    let field = field.replace('_', " ");
+    let mut chars = field.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => field,
+    }
+}
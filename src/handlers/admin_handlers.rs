@@ -0,0 +1,112 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::login_model::{LoginAttemptQuery, UnlockAccountRequest};
+use crate::services::lockout_service::{list_login_attempts_service, unlock_account_service};
+use crate::services::session_service::{
+    list_sessions_service, revoke_account_session_service, revoke_all_account_sessions_service,
+};
+
+// ============================================================================
+// Login Security Handlers
+// ============================================================================
+
+pub async fn unlock_account(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<UnlockAccountRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(admin_id = %claims.sub, username = %payload.username, "Unlocking account");
+
+    match unlock_account_service(&pool, payload).await {
+        Ok(was_locked) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": { "was_locked": was_locked } })),
+        )),
+        Err(e) => {
+            error!(admin_id = %claims.sub, error = %e, "Failed to unlock account");
+            Err(e)
+        }
+    }
+}
+
+pub async fn list_login_attempts(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<LoginAttemptQuery>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match list_login_attempts_service(&pool, query).await {
+        Ok(attempts) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": attempts })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to list login attempts");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Support Session Handlers
+// ============================================================================
+
+pub async fn list_account_sessions(
+    Extension(pool): Extension<PgPool>,
+    Path(account_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match list_sessions_service(&pool, &account_id, None).await {
+        Ok(sessions) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": sessions })),
+        )),
+        Err(e) => {
+            error!(account_id = %account_id, error = %e, "Failed to list account sessions");
+            Err(e)
+        }
+    }
+}
+
+pub async fn revoke_account_session(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path((account_id, session_id)): Path<(String, String)>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(admin_id = %claims.sub, account_id = %account_id, session_id = %session_id, "Revoking account session");
+
+    match revoke_account_session_service(&pool, &account_id, &session_id, "support").await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Session revoked" })),
+        )),
+        Err(e) => {
+            error!(account_id = %account_id, session_id = %session_id, error = %e, "Failed to revoke account session");
+            Err(e)
+        }
+    }
+}
+
+pub async fn revoke_all_account_sessions(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(account_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(admin_id = %claims.sub, account_id = %account_id, "Revoking all account sessions");
+
+    match revoke_all_account_sessions_service(&pool, &account_id).await {
+        Ok(revoked) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": { "sessions_revoked": revoked } })),
+        )),
+        Err(e) => {
+            error!(account_id = %account_id, error = %e, "Failed to revoke account sessions");
+            Err(e)
+        }
+    }
+}
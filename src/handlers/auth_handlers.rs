@@ -0,0 +1,118 @@
+use axum::Extension;
+use axum::Json;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::client_info::ClientInfo;
+use crate::auth::password_utils::{Claims, jwks};
+use crate::error::AppError;
+use crate::models::auth_model::{
+    ForgotPasswordRequest, LogoutRequest, RefreshRequest, ResetPasswordRequest,
+};
+use crate::services::auth_service::{logout_service, refresh_session_service};
+use crate::services::notification_service::Notifier;
+use crate::services::password_reset_service::{forgot_password_service, reset_password_service};
+
+// ============================================================================
+// Session Handlers
+// ============================================================================
+
+pub async fn refresh_token(
+    Extension(pool): Extension<PgPool>,
+    client: ClientInfo,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match refresh_session_service(&pool, payload.refresh_token, &client).await {
+        Ok(tokens) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": tokens })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to refresh session");
+            Err(e)
+        }
+    }
+}
+
+pub async fn logout(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    payload: Option<Json<LogoutRequest>>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+    info!(user_id = %claims.sub, all_devices = payload.all_devices, "Logging out");
+
+    match logout_service(&pool, &claims, payload.all_devices).await {
+        Ok(revoked) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": { "sessions_revoked": revoked } })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to log out");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Password Reset Handlers
+// ============================================================================
+
+pub async fn forgot_password(
+    Extension(pool): Extension<PgPool>,
+    Extension(notifier): Extension<Notifier>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(
+        account_type = payload.account_type.as_str(),
+        "Password reset requested"
+    );
+
+    match forgot_password_service(&pool, &notifier, payload).await {
+        Ok(()) => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "status": "success",
+                "message": "If an account with that email exists, a reset link has been sent"
+            })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to start password reset");
+            Err(e)
+        }
+    }
+}
+
+pub async fn reset_password(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match reset_password_service(&pool, payload).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Password has been reset" })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to reset password");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Key Discovery Handlers
+// ============================================================================
+
+/// Public keys other services use to verify RideNow tokens. Served as a
+/// bare JWK set, as verifiers expect.
+pub async fn jwks_document() -> Response {
+    let mut response = Json(jwks()).into_response();
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=300"),
+    );
+    response
+}
@@ -0,0 +1,123 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::location_model::LocationUpdateRequest;
+use crate::models::realtime_model::RealtimeEvent;
+use crate::services::location_service::{
+    LocationBuffer, get_driver_location_service, get_ride_route_service, is_active_rider_of_driver,
+};
+use crate::services::realtime_service::{RealtimeHub, Topic};
+use crate::services::ride_service::get_ride_service;
+
+// ============================================================================
+// Location Ingestion Handlers
+// ============================================================================
+
+pub async fn push_location(
+    Extension(buffer): Extension<LocationBuffer>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<LocationUpdateRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match buffer.record_pings(&claims.sub, payload.pings) {
+        Ok((result, newest)) => {
+            if let Some(ping) = newest {
+                hub.publish(
+                    Topic::DriverLocation(claims.sub.clone()),
+                    RealtimeEvent::DriverLocation {
+                        driver_id: claims.sub.clone(),
+                        lat: ping.lat,
+                        lng: ping.lng,
+                        heading: ping.heading,
+                        speed: ping.speed,
+                        recorded_at: ping.recorded_at,
+                    },
+                );
+            }
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(json!({ "status": "success", "data": result })),
+            ))
+        }
+        Err(e) => {
+            error!(driver_id = %claims.sub, error = %e, "Rejected location update");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Location Query Handlers
+// ============================================================================
+
+pub async fn get_driver_location(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(driver_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(driver_id = %driver_id, "Fetching driver location");
+
+    if claims.sub != driver_id {
+        match is_active_rider_of_driver(&pool, &claims.sub, &driver_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                error!(user_id = %claims.sub, driver_id = %driver_id, "Not allowed to track driver");
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to check ride participation");
+                return Err(e);
+            }
+        }
+    }
+
+    match get_driver_location_service(&pool, driver_id.clone()).await {
+        Ok(location) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": location })),
+        )),
+        Err(e) => {
+            error!(driver_id = %driver_id, error = %e, "Driver location not found");
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_ride_route(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(ride_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %ride_id, "Fetching ride route");
+
+    let ride = match get_ride_service(&pool, ride_id.clone()).await {
+        Ok(ride) => ride,
+        Err(e) => {
+            error!(ride_id = %ride_id, error = %e, "Ride not found");
+            return Err(e);
+        }
+    };
+
+    if ride.rider_id != claims.sub && ride.driver_id.as_deref() != Some(claims.sub.as_str()) {
+        error!(ride_id = %ride_id, user_id = %claims.sub, "User is not a participant of the ride");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    match get_ride_route_service(&pool, ride_id.clone()).await {
+        Ok(points) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": points })),
+        )),
+        Err(e) => {
+            error!(ride_id = %ride_id, error = %e, "Failed to fetch ride route");
+            Err(e)
+        }
+    }
+}
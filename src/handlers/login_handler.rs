@@ -1,14 +1,31 @@
-use crate::services::login_service::login_service;
-use axum::{extract::Extension, http::StatusCode, Json};
+use crate::auth::client_info::ClientInfo;
+use crate::error::AppError;
+use crate::models::login_model::LoginRequest;
+use crate::models::two_factor_model::TwoFactorLoginRequest;
+use crate::services::login_service::{login_service, login_two_factor_service};
+use axum::{Json, extract::Extension, http::StatusCode};
 use sqlx::PgPool;
-use crate::models::login_model::LoginRequest;   
 
 pub async fn login_handler(
     Extension(pool): Extension<PgPool>,
+    client: ClientInfo,
     Json(payload): Json<LoginRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    match login_service(&pool, payload).await {
-        Ok(response) => (StatusCode::OK, Json(serde_json::json!({ "status": "success", "data": response }))),
-        Err(e) => (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "status": "error", "message": e }))),
-    }
-}
\ No newline at end of file
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let response = login_service(&pool, payload, client).await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "success", "data": response })),
+    ))
+}
+
+pub async fn login_two_factor_handler(
+    Extension(pool): Extension<PgPool>,
+    client: ClientInfo,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    let response = login_two_factor_service(&pool, payload, client).await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "success", "data": response })),
+    ))
+}
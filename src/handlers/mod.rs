@@ -1,4 +1,13 @@
+pub mod admin_handlers;
+pub mod auth_handlers;
+pub mod location_handlers;
+pub mod login_handler;
 pub mod payment_handlers;
+pub mod pricing_handlers;
+pub mod realtime_handlers;
+pub mod ride_handlers;
+pub mod session_handlers;
 pub mod transport_company_handlers;
+pub mod two_factor_handlers;
 pub mod user_handlers;
-pub mod login_handler;
\ No newline at end of file
+pub mod verification_handlers;
@@ -1,19 +1,30 @@
 use axum::{
-    Json,
+    Extension, Json,
     body::Bytes,
-    extract::Path,
+    extract::{Path, Query},
     http::{HeaderMap, StatusCode},
     response::Redirect,
-    Extension,
 };
 use sqlx::PgPool;
 use tracing::{error, info};
 
-use crate::models::payment_model::{InitializeRequest, InitializeResponse, PaymentResponse, VerifyResponse};
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::{Permission, Principal, Role};
+use crate::error::AppError;
+use crate::models::payment_model::{
+    InitializeRequest, InitializeResponse, PaymentResponse, VerifyResponse,
+};
+use crate::models::refund_model::{CreateRefundRequest, RefundResponse};
+use crate::models::webhook_event_model::{WebhookEventQuery, WebhookEventResponse};
+use crate::services::payment_gateway::{PaymentGateway, PaymentProvider};
 use crate::services::payment_service::{
     get_payment_redirect_url, initialize_payment_service, list_all_payments_service,
-    list_driver_payments_service, list_user_payments_service, process_webhook_event,
-    verify_payment_service, verify_webhook_signature,
+    list_driver_payments_service, list_user_payments_service, payment_payer_service,
+    verify_payment_service,
+};
+use crate::services::refund_service::{create_refund_service, list_refunds_service};
+use crate::services::webhook_service::{
+    list_webhook_events_service, receive_webhook_service, replay_webhook_event_service,
 };
 
 // ============================================================================
@@ -30,36 +41,44 @@ pub async fn health_check() -> (StatusCode, &'static str) {
 
 pub async fn initialize_payment(
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<InitializeRequest>,
-) -> Result<Json<InitializeResponse>, StatusCode> {
-    info!(email = %payload.email, amount = %payload.amount, "Initializing payment");
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
+    Json(mut payload): Json<InitializeRequest>,
+) -> Result<Json<InitializeResponse>, AppError> {
+    info!(email = %payload.email, amount = ?payload.amount, ride_id = ?payload.ride_id, "Initializing payment");
+
+    authorize_payer(&claims, &mut payload)?;
 
-    match initialize_payment_service(&pool, payload).await {
+    match initialize_payment_service(&pool, &provider, payload).await {
         Ok(response) => {
             info!(reference = %response.data.reference, "Payment initialization successful");
             Ok(Json(response))
         }
         Err(e) => {
             error!(error = %e, "Failed to initialize payment");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e)
         }
     }
 }
 
 pub async fn initialize_payment_redirect(
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<InitializeRequest>,
-) -> Result<Redirect, StatusCode> {
-    info!(email = %payload.email, amount = %payload.amount, "Getting payment redirect");
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
+    Json(mut payload): Json<InitializeRequest>,
+) -> Result<Redirect, AppError> {
+    info!(email = %payload.email, amount = ?payload.amount, ride_id = ?payload.ride_id, "Getting payment redirect");
 
-    match get_payment_redirect_url(&pool, payload).await {
+    authorize_payer(&claims, &mut payload)?;
+
+    match get_payment_redirect_url(&pool, &provider, payload).await {
         Ok(url) => {
             info!(url = %url, "Redirecting to payment page");
             Ok(Redirect::to(&url))
         }
         Err(e) => {
             error!(error = %e, "Failed to get redirect URL");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e)
         }
     }
 }
@@ -70,31 +89,25 @@ pub async fn initialize_payment_redirect(
 
 pub async fn verify_payment(
     Extension(pool): Extension<PgPool>,
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
     Path(reference): Path<String>,
-) -> (StatusCode, Json<VerifyResponse>) {
+) -> Result<(StatusCode, Json<VerifyResponse>), AppError> {
     info!(reference = %reference, "Verifying payment");
+    authorize_reference(&pool, &claims, &reference).await?;
 
-    match verify_payment_service(&pool, reference).await {
+    match verify_payment_service(&pool, &provider, reference).await {
         Ok(response) => {
             info!(
                 reference = %response.data.reference,
                 status = %response.data.status,
                 "Payment verification successful"
             );
-            (StatusCode::OK, Json(response))
+            Ok((StatusCode::OK, Json(response)))
         }
         Err(e) => {
             error!(error = %e, "Failed to verify payment");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(VerifyResponse {
-                status: false,
-                message: "Payment verification failed".to_string(),
-                data: crate::models::payment_model::VerifyResponseData {
-                    status: "failed".to_string(),
-                    amount: 0,
-                    reference: "".to_string(),
-                    gateway_response: "".to_string(),
-                },
-            }))
+            Err(e)
         }
     }
 }
@@ -103,55 +116,28 @@ pub async fn verify_payment(
 // Webhook Handler
 // ============================================================================
 
-pub async fn paystack_webhook(
+/// Webhook of the default gateway.
+pub async fn payment_webhook(
     Extension(pool): Extension<PgPool>,
+    Extension(provider): Extension<PaymentProvider>,
     headers: HeaderMap,
     body: Bytes,
 ) -> StatusCode {
-    info!("Received webhook event");
-
-    // 1. Get the signature from headers
-    let signature = match headers.get("x-paystack-signature") {
-        Some(sig) => match sig.to_str() {
-            Ok(s) => s,
-            Err(_) => {
-                error!("Invalid signature header format");
-                return StatusCode::BAD_REQUEST;
-            }
-        },
-        None => {
-            error!("Missing x-paystack-signature header");
-            return StatusCode::BAD_REQUEST;
-        }
-    };
-
-    // 2. Verify signature
-    match verify_webhook_signature(signature, &body) {
-        Ok(is_valid) => {
-            if !is_valid {
-                error!("Invalid webhook signature");
-                return StatusCode::UNAUTHORIZED;
-            }
-        }
-        Err(e) => {
-            error!(error = %e, "Failed to verify webhook signature");
-            return StatusCode::INTERNAL_SERVER_ERROR;
-        }
-    }
+    handle_webhook(&pool, provider.default_gateway(), &headers, &body).await
+}
 
-    // 3. Parse and process the webhook payload
-    match serde_json::from_slice::<serde_json::Value>(&body) {
-        Ok(payload) => {
-            if let Err(e) = process_webhook_event(&pool, payload).await {
-                error!(error = %e, "Failed to process webhook event");
-                return StatusCode::INTERNAL_SERVER_ERROR;
-            }
-            info!("Webhook processed successfully");
-            StatusCode::OK
-        }
+pub async fn gateway_webhook(
+    Extension(pool): Extension<PgPool>,
+    Extension(provider): Extension<PaymentProvider>,
+    Path(gateway): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    match provider.gateway(&gateway) {
+        Ok(gateway) => handle_webhook(&pool, gateway, &headers, &body).await,
         Err(e) => {
-            error!(error = %e, "Failed to parse webhook payload");
-            StatusCode::BAD_REQUEST
+            error!(gateway = %gateway, error = %e, "Webhook for a gateway that is not enabled");
+            StatusCode::NOT_FOUND
         }
     }
 }
@@ -162,17 +148,17 @@ pub async fn paystack_webhook(
 
 pub async fn list_all_payments(
     Extension(pool): Extension<PgPool>,
-) -> (StatusCode, Json<Vec<PaymentResponse>>) {
+) -> Result<(StatusCode, Json<Vec<PaymentResponse>>), AppError> {
     info!("Fetching all payments");
 
     match list_all_payments_service(&pool).await {
         Ok(payments) => {
             info!(count = payments.len(), "All payments fetched successfully");
-            (StatusCode::OK, Json(payments))
+            Ok((StatusCode::OK, Json(payments)))
         }
         Err(e) => {
             error!(error = %e, "Failed to fetch payments");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -180,17 +166,17 @@ pub async fn list_all_payments(
 pub async fn list_user_payments(
     Extension(pool): Extension<PgPool>,
     Path(user_id): Path<String>,
-) -> (StatusCode, Json<Vec<PaymentResponse>>) {
+) -> Result<(StatusCode, Json<Vec<PaymentResponse>>), AppError> {
     info!(user_id = %user_id, "Fetching user payments");
 
     match list_user_payments_service(&pool, user_id.clone()).await {
         Ok(payments) => {
             info!(count = payments.len(), user_id = %user_id, "User payments fetched successfully");
-            (StatusCode::OK, Json(payments))
+            Ok((StatusCode::OK, Json(payments)))
         }
         Err(e) => {
             error!(error = %e, user_id = %user_id, "Failed to fetch user payments");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -198,17 +184,164 @@ pub async fn list_user_payments(
 pub async fn list_driver_payments(
     Extension(pool): Extension<PgPool>,
     Path(driver_id): Path<String>,
-) -> (StatusCode, Json<Vec<PaymentResponse>>) {
+) -> Result<(StatusCode, Json<Vec<PaymentResponse>>), AppError> {
     info!(driver_id = %driver_id, "Fetching driver payments");
 
     match list_driver_payments_service(&pool, driver_id.clone()).await {
         Ok(payments) => {
             info!(count = payments.len(), driver_id = %driver_id, "Driver payments fetched successfully");
-            (StatusCode::OK, Json(payments))
+            Ok((StatusCode::OK, Json(payments)))
         }
         Err(e) => {
             error!(error = %e, driver_id = %driver_id, "Failed to fetch driver payments");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Refund Handlers
+// ============================================================================
+
+/// Refunds a payment in full, or in part when an amount is given.
+pub async fn create_refund(
+    Extension(pool): Extension<PgPool>,
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
+    Path(reference): Path<String>,
+    Json(payload): Json<CreateRefundRequest>,
+) -> Result<(StatusCode, Json<RefundResponse>), AppError> {
+    info!(reference = %reference, admin_id = %claims.sub, "Refunding payment");
+
+    match create_refund_service(&pool, &provider, &reference, &claims.sub, payload).await {
+        Ok(refund) => {
+            info!(reference = %reference, refund_id = %refund.id, status = refund.status.as_str(), "Refund created");
+            Ok((StatusCode::CREATED, Json(refund)))
+        }
+        Err(e) => {
+            error!(error = %e, reference = %reference, "Failed to refund payment");
+            Err(e)
+        }
+    }
+}
+
+pub async fn list_refunds(
+    Extension(pool): Extension<PgPool>,
+    Path(reference): Path<String>,
+) -> Result<(StatusCode, Json<Vec<RefundResponse>>), AppError> {
+    match list_refunds_service(&pool, &reference).await {
+        Ok(refunds) => Ok((StatusCode::OK, Json(refunds))),
+        Err(e) => {
+            error!(error = %e, reference = %reference, "Failed to fetch refunds");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Webhook Event Handlers
+// ============================================================================
+
+pub async fn list_webhook_events(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<WebhookEventQuery>,
+) -> Result<(StatusCode, Json<Vec<WebhookEventResponse>>), AppError> {
+    match list_webhook_events_service(&pool, query).await {
+        Ok(events) => Ok((StatusCode::OK, Json(events))),
+        Err(e) => {
+            error!(error = %e, "Failed to fetch webhook events");
+            Err(e)
+        }
+    }
+}
+
+pub async fn replay_webhook_event(
+    Extension(pool): Extension<PgPool>,
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<WebhookEventResponse>), AppError> {
+    info!(id = %id, admin_id = %claims.sub, "Replaying webhook event");
+
+    match replay_webhook_event_service(&pool, &provider, &id).await {
+        Ok(event) => {
+            info!(id = %id, status = event.status.as_str(), "Webhook event replayed");
+            Ok((StatusCode::OK, Json(event)))
+        }
+        Err(e) => {
+            error!(error = %e, id = %id, "Failed to replay webhook event");
+            Err(e)
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+async fn handle_webhook(
+    pool: &PgPool,
+    gateway: &dyn PaymentGateway,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> StatusCode {
+    info!(gateway = gateway.name(), "Received webhook event");
+
+    // Anything but a 2xx makes the gateway deliver the event again
+    match receive_webhook_service(pool, gateway, headers, body).await {
+        Ok(status) => {
+            info!(
+                gateway = gateway.name(),
+                status = status.as_str(),
+                "Webhook processed successfully"
+            );
+            StatusCode::OK
+        }
+        Err(e) => {
+            error!(gateway = gateway.name(), error = %e, "Failed to process webhook event");
+            e.status()
+        }
+    }
+}
+
+/// Only the payer and platform admins may look at a payment.
+async fn authorize_reference(
+    pool: &PgPool,
+    claims: &Claims,
+    reference: &str,
+) -> Result<(), AppError> {
+    let principal = Principal::from_claims(claims).map_err(AppError::Forbidden)?;
+    if principal.can(Permission::ManagePayments) {
+        return Ok(());
+    }
+    let payer = payment_payer_service(pool, reference).await?;
+    if payer != principal.id {
+        error!(user_id = %claims.sub, reference = %reference, "Access to another account's payment denied");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+    Ok(())
+}
+
+/// Payments are made on the caller's own behalf; only platform admins may
+/// initialize one for another rider or driver. Without a payer the caller
+/// pays, as a driver if they are one.
+fn authorize_payer(claims: &Claims, payload: &mut InitializeRequest) -> Result<(), AppError> {
+    let principal = Principal::from_claims(claims).map_err(AppError::Forbidden)?;
+    if payload.user_id.is_none() && payload.driver_id.is_none() {
+        if principal.role == Role::Driver {
+            payload.driver_id = Some(principal.id);
+        } else {
+            payload.user_id = Some(principal.id);
+        }
+        return Ok(());
+    }
+
+    let payers = [payload.user_id.as_deref(), payload.driver_id.as_deref()];
+    for payer in payers.into_iter().flatten() {
+        if payer != principal.id && !principal.can(Permission::ManagePayments) {
+            error!(user_id = %claims.sub, payer = %payer, "Payment initialized for another account");
+            return Err(AppError::Forbidden("Access denied".to_string()));
         }
     }
+    Ok(())
 }
@@ -0,0 +1,153 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::Principal;
+use crate::error::AppError;
+use crate::models::pricing_model::{
+    CreatePricingRuleRequest, PricingRule, UpdatePricingRuleRequest,
+};
+use crate::services::pricing_service::{
+    create_pricing_rule_service, delete_pricing_rule_service, get_pricing_rule_service,
+    list_pricing_rules_service, update_pricing_rule_service,
+};
+
+// ============================================================================
+// Pricing Rule Handlers
+// ============================================================================
+
+pub async fn create_pricing_rule(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreatePricingRuleRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let principal = principal(&claims)?;
+    if !principal.can_access_company(payload.transport_company_id.as_deref()) {
+        error!(user_id = %claims.sub, transport_company_id = ?payload.transport_company_id, "Pricing rule outside the admin's company");
+        return Err(forbidden());
+    }
+    info!(admin_id = %claims.sub, "Creating pricing rule");
+
+    match create_pricing_rule_service(&pool, payload).await {
+        Ok(rule) => Ok((
+            StatusCode::CREATED,
+            Json(json!({ "status": "success", "data": rule })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to create pricing rule");
+            Err(e)
+        }
+    }
+}
+
+pub async fn list_pricing_rules(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let principal = principal(&claims)?;
+    info!("Fetching pricing rules");
+
+    match list_pricing_rules_service(&pool).await {
+        Ok(mut rules) => {
+            rules.retain(|rule| principal.can_access_company(rule.transport_company_id.as_deref()));
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": rules })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch pricing rules");
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_pricing_rule(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(pricing_rule_id = %id, "Fetching pricing rule by ID");
+
+    let rule = authorize_rule(&pool, &claims, &id).await?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "status": "success", "data": rule })),
+    ))
+}
+
+pub async fn update_pricing_rule(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePricingRuleRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    authorize_rule(&pool, &claims, &id).await?;
+    info!(pricing_rule_id = %id, "Updating pricing rule");
+
+    match update_pricing_rule_service(&pool, id.clone(), payload).await {
+        Ok(rule) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": rule })),
+        )),
+        Err(e) => {
+            error!(pricing_rule_id = %id, error = %e, "Failed to update pricing rule");
+            Err(e)
+        }
+    }
+}
+
+pub async fn delete_pricing_rule(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    authorize_rule(&pool, &claims, &id).await?;
+    info!(pricing_rule_id = %id, "Deleting pricing rule");
+
+    match delete_pricing_rule_service(&pool, id.clone()).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Pricing rule deleted" })),
+        )),
+        Err(e) => {
+            error!(pricing_rule_id = %id, error = %e, "Failed to delete pricing rule");
+            Err(e)
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn principal(claims: &Claims) -> Result<Principal, AppError> {
+    Principal::from_claims(claims).map_err(AppError::Forbidden)
+}
+
+/// Loads a rule the caller may manage: company admins only reach their own
+/// company's rules, platform-wide rules belong to platform admins.
+async fn authorize_rule(pool: &PgPool, claims: &Claims, id: &str) -> Result<PricingRule, AppError> {
+    let principal = principal(claims)?;
+    let rule = get_pricing_rule_service(pool, id.to_string())
+        .await
+        .map_err(|e| {
+            error!(pricing_rule_id = %id, error = %e, "Pricing rule not found");
+            e
+        })?;
+
+    if !principal.can_access_company(rule.transport_company_id.as_deref()) {
+        error!(user_id = %claims.sub, pricing_rule_id = %id, "Pricing rule outside the admin's company");
+        return Err(forbidden());
+    }
+    Ok(rule)
+}
+
+fn forbidden() -> AppError {
+    AppError::Forbidden("You cannot manage this pricing rule".to_string())
+}
@@ -0,0 +1,224 @@
+use axum::Extension;
+use axum::extract::Path;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use sqlx::PgPool;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tracing::{debug, error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::realtime_model::RealtimeEvent;
+use crate::models::ride_model::{RideResponse, RideStatus};
+use crate::services::matching_service::{
+    estimate_travel_seconds, haversine_km, list_driver_offers_service,
+};
+use crate::services::realtime_service::{RealtimeHub, Topic};
+use crate::services::ride_service::get_ride_service;
+
+// ============================================================================
+// Ride Tracking Socket
+// ============================================================================
+
+/// Live status, driver position and ETA for one ride. Participants only.
+pub async fn ride_socket(
+    ws: WebSocketUpgrade,
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Path(ride_id): Path<String>,
+) -> Result<Response, AppError> {
+    info!(ride_id = %ride_id, user_id = %claims.sub, "Ride tracking socket requested");
+
+    let ride = match get_ride_service(&pool, ride_id.clone()).await {
+        Ok(ride) => ride,
+        Err(e) => {
+            error!(ride_id = %ride_id, error = %e, "Ride not found");
+            return Err(e);
+        }
+    };
+
+    if ride.rider_id != claims.sub && ride.driver_id.as_deref() != Some(claims.sub.as_str()) {
+        error!(ride_id = %ride_id, user_id = %claims.sub, "User is not a participant of the ride");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    if ride.status.is_terminal() {
+        return Err(AppError::Validation("Ride has already ended".to_string()));
+    }
+
+    Ok(ws.on_upgrade(move |socket| ride_session(socket, pool, hub, ride_id)))
+}
+
+async fn ride_session(mut socket: WebSocket, pool: PgPool, hub: RealtimeHub, ride_id: String) {
+    // Subscribe before loading the snapshot so no transition slips in between.
+    let mut ride_rx = hub.subscribe(Topic::Ride(ride_id.clone()));
+    let mut ride = match get_ride_service(&pool, ride_id.clone()).await {
+        Ok(ride) => ride,
+        Err(e) => {
+            error!(ride_id = %ride_id, error = %e, "Failed to load ride for socket");
+            return;
+        }
+    };
+    let mut location_rx = subscribe_driver_location(&hub, &ride);
+    let mut last_eta_minutes: Option<i64> = None;
+
+    if send_event(
+        &mut socket,
+        &RealtimeEvent::RideStatus {
+            ride: Box::new(ride.clone()),
+        },
+    )
+    .await
+    .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = ride_rx.recv() => match event {
+                Ok(RealtimeEvent::RideStatus { ride: updated }) => {
+                    if updated.driver_id != ride.driver_id {
+                        location_rx = subscribe_driver_location(&hub, &updated);
+                    }
+                    ride = *updated;
+                    if send_event(&mut socket, &RealtimeEvent::RideStatus { ride: Box::new(ride.clone()) }).await.is_err()
+                        || ride.status.is_terminal()
+                    {
+                        break;
+                    }
+                }
+                Ok(other) => {
+                    if send_event(&mut socket, &other).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => debug!(ride_id = %ride.id, skipped, "Ride socket lagging"),
+                Err(RecvError::Closed) => break,
+            },
+            event = recv_optional(&mut location_rx) => match event {
+                Ok(event) => {
+                    if send_event(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                    if let RealtimeEvent::DriverLocation { lat, lng, .. } = event
+                        && let Some(eta_seconds) = eta_seconds(&ride, lat, lng)
+                    {
+                        let eta_minutes = (eta_seconds + 59) / 60;
+                        if last_eta_minutes != Some(eta_minutes) {
+                            last_eta_minutes = Some(eta_minutes);
+                            let changed = RealtimeEvent::EtaChanged { ride_id: ride.id.clone(), eta_seconds };
+                            if send_event(&mut socket, &changed).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => debug!(ride_id = %ride.id, skipped, "Location feed lagging"),
+                Err(RecvError::Closed) => location_rx = None,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    info!(ride_id = %ride.id, "Ride tracking socket closed");
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+// ============================================================================
+// Driver Offer Socket
+// ============================================================================
+
+/// Pushes new ride offers to the authenticated driver as they are made.
+pub async fn offers_socket(
+    ws: WebSocketUpgrade,
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+) -> Response {
+    info!(driver_id = %claims.sub, "Driver offer socket requested");
+    ws.on_upgrade(move |socket| offers_session(socket, pool, hub, claims.sub))
+}
+
+async fn offers_session(mut socket: WebSocket, pool: PgPool, hub: RealtimeHub, driver_id: String) {
+    let mut offers_rx = hub.subscribe(Topic::DriverOffers(driver_id.clone()));
+
+    // Replay offers that are still open so a reconnecting driver misses nothing.
+    match list_driver_offers_service(&pool, driver_id.clone()).await {
+        Ok(offers) => {
+            for offer in offers {
+                if send_event(&mut socket, &RealtimeEvent::RideOffered { offer })
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+        Err(e) => error!(driver_id = %driver_id, error = %e, "Failed to load pending offers"),
+    }
+
+    loop {
+        tokio::select! {
+            event = offers_rx.recv() => match event {
+                Ok(event) => {
+                    if send_event(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => debug!(driver_id = %driver_id, skipped, "Offer socket lagging"),
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    info!(driver_id = %driver_id, "Driver offer socket closed");
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn subscribe_driver_location(
+    hub: &RealtimeHub,
+    ride: &RideResponse,
+) -> Option<Receiver<RealtimeEvent>> {
+    ride.driver_id
+        .clone()
+        .map(|driver_id| hub.subscribe(Topic::DriverLocation(driver_id)))
+}
+
+async fn recv_optional(
+    receiver: &mut Option<Receiver<RealtimeEvent>>,
+) -> Result<RealtimeEvent, RecvError> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Time for the driver to reach the pickup, or the dropoff once on trip.
+fn eta_seconds(ride: &RideResponse, lat: f64, lng: f64) -> Option<i64> {
+    let (target_lat, target_lng) = match ride.status {
+        RideStatus::Accepted | RideStatus::Arriving => (ride.pickup_lat, ride.pickup_lng),
+        RideStatus::InProgress => (ride.dropoff_lat, ride.dropoff_lng),
+        _ => return None,
+    };
+    Some(estimate_travel_seconds(haversine_km(
+        lat, lng, target_lat, target_lng,
+    )))
+}
+
+async fn send_event(socket: &mut WebSocket, event: &RealtimeEvent) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text.into())).await
+}
@@ -0,0 +1,504 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::{Path, Query};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::Role;
+use crate::error::AppError;
+use crate::models::history_model::{ReceiptFormat, ReceiptQuery, RideHistoryQuery};
+use crate::models::pricing_model::FareEstimateRequest;
+use crate::models::rating_model::CreateRatingRequest;
+use crate::models::realtime_model::RealtimeEvent;
+use crate::models::ride_model::{CancelRideRequest, CreateRideRequest, RideStatus};
+use crate::services::history_service::{
+    render_receipt_html, ride_history_service, ride_receipt_service,
+};
+use crate::services::matching_service::{
+    list_driver_offers_service, respond_to_offer_service, spawn_dispatch, start_dispatch,
+};
+use crate::services::payment_gateway::PaymentProvider;
+use crate::services::payment_service::initialize_ride_payment_service;
+use crate::services::pricing_service::estimate_fare_service;
+use crate::services::rating_service::{get_ride_rating_service, rate_ride_service};
+use crate::services::realtime_service::{RealtimeHub, Topic};
+use crate::services::ride_service::{
+    cancel_ride_service, create_ride_service, get_ride_service, list_rides_service,
+    transition_ride_service,
+};
+use crate::services::schedule_service::list_ride_schedules_service;
+
+// ============================================================================
+// Ride Request Handlers
+// ============================================================================
+
+pub async fn create_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateRideRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(rider_id = %claims.sub, "Creating ride request");
+
+    match create_ride_service(&pool, claims.sub, payload).await {
+        Ok(ride) => {
+            info!(ride_id = %ride.id, status = %ride.status.as_str(), "Ride requested successfully");
+            // Scheduled rides are dispatched by the scheduler when they fall due.
+            if ride.status == RideStatus::Requested {
+                spawn_dispatch(pool, hub, ride.id.clone());
+            }
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({ "status": "success", "data": ride })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create ride");
+            Err(e)
+        }
+    }
+}
+
+/// Upfront fare quote for a trip, before the rider books it.
+pub async fn estimate_fare(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<FareEstimateRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(rider_id = %claims.sub, "Estimating fare");
+
+    match estimate_fare_service(&pool, payload).await {
+        Ok(fare) => {
+            info!(total = fare.total, currency = %fare.currency, "Fare estimated successfully");
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": fare })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to estimate fare");
+            Err(e)
+        }
+    }
+}
+
+pub async fn list_rides(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(user_id = %claims.sub, "Fetching rides");
+
+    match list_rides_service(&pool, claims.sub).await {
+        Ok(rides) => {
+            info!(count = rides.len(), "Rides fetched successfully");
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": rides })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch rides");
+            Err(e)
+        }
+    }
+}
+
+pub async fn list_scheduled_rides(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(rider_id = %claims.sub, "Fetching scheduled rides");
+
+    match list_ride_schedules_service(&pool, claims.sub).await {
+        Ok(schedules) => {
+            info!(
+                count = schedules.len(),
+                "Scheduled rides fetched successfully"
+            );
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": schedules })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch scheduled rides");
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, "Fetching ride by ID");
+
+    match get_ride_service(&pool, id.clone()).await {
+        Ok(ride) => {
+            if ride.rider_id != claims.sub && ride.driver_id.as_deref() != Some(claims.sub.as_str())
+            {
+                error!(ride_id = %id, user_id = %claims.sub, "User is not a participant of the ride");
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": ride })),
+            ))
+        }
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Ride not found");
+            Err(e)
+        }
+    }
+}
+
+/// Restarts driver matching for a ride that is still waiting for a driver.
+pub async fn dispatch_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, "Re-dispatching ride");
+
+    match get_ride_service(&pool, id.clone()).await {
+        Ok(ride) => {
+            if ride.rider_id != claims.sub {
+                error!(ride_id = %id, user_id = %claims.sub, "Only the rider can re-dispatch a ride");
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            if ride.status != RideStatus::Requested {
+                return Err(AppError::conflict("Ride is no longer waiting for a driver"));
+            }
+            if !start_dispatch(pool, hub, ride.id.clone()).await? {
+                return Err(AppError::conflict("Ride is already being dispatched"));
+            }
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(json!({ "status": "success", "data": ride })),
+            ))
+        }
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Ride not found");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Ride Offer Handlers
+// ============================================================================
+
+pub async fn list_offers(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(driver_id = %claims.sub, "Fetching ride offers");
+
+    match list_driver_offers_service(&pool, claims.sub).await {
+        Ok(offers) => {
+            info!(count = offers.len(), "Ride offers fetched successfully");
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": offers })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch ride offers");
+            Err(e)
+        }
+    }
+}
+
+pub async fn accept_offer(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    offer_id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    respond_to_offer(pool, hub, claims, offer_id, true).await
+}
+
+pub async fn decline_offer(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    offer_id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    respond_to_offer(pool, hub, claims, offer_id, false).await
+}
+
+async fn respond_to_offer(
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Path(offer_id): Path<String>,
+    accept: bool,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(offer_id = %offer_id, driver_id = %claims.sub, accept, "Responding to ride offer");
+
+    match respond_to_offer_service(&pool, offer_id.clone(), claims.sub, accept).await {
+        Ok(offer) => {
+            if accept {
+                publish_ride_status(&pool, &hub, offer.ride_id.clone()).await;
+            }
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": offer })),
+            ))
+        }
+        Err(e) => {
+            error!(offer_id = %offer_id, error = %e, "Failed to respond to ride offer");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Trip Lifecycle Handlers
+// ============================================================================
+
+pub async fn accept_ride(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    transition_ride(pool, hub, claims, id, RideStatus::Accepted).await
+}
+
+pub async fn arrive_ride(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    transition_ride(pool, hub, claims, id, RideStatus::Arriving).await
+}
+
+pub async fn start_ride(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    transition_ride(pool, hub, claims, id, RideStatus::InProgress).await
+}
+
+pub async fn complete_ride(
+    pool: Extension<PgPool>,
+    hub: Extension<RealtimeHub>,
+    claims: Extension<Claims>,
+    id: Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    transition_ride(pool, hub, claims, id, RideStatus::Completed).await
+}
+
+pub async fn cancel_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(provider): Extension<PaymentProvider>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<CancelRideRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, user_id = %claims.sub, reason = %payload.reason.as_str(), "Cancelling ride");
+
+    match cancel_ride_service(&pool, id.clone(), claims.sub, payload).await {
+        Ok(ride) => {
+            info!(ride_id = %id, cancelled_by = ?ride.cancelled_by, "Ride cancelled successfully");
+            hub.publish(
+                Topic::Ride(ride.id.clone()),
+                RealtimeEvent::RideStatus {
+                    ride: Box::new(ride.clone()),
+                },
+            );
+
+            // The fee stays owed on the ride if the charge cannot be started
+            // now; the rider can still settle it through /payments/initialize.
+            let payment = match ride.cancellation_fee {
+                Some(fee) if fee > 0 => {
+                    match initialize_ride_payment_service(&pool, &provider, &ride).await {
+                        Ok(payment) => Some(payment.data),
+                        Err(e) => {
+                            error!(ride_id = %id, error = %e, "Failed to initialize cancellation fee payment");
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": ride, "payment": payment })),
+            ))
+        }
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Failed to cancel ride");
+            Err(e)
+        }
+    }
+}
+
+async fn transition_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    next: RideStatus,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, user_id = %claims.sub, next = %next.as_str(), "Updating ride status");
+
+    match transition_ride_service(&pool, id.clone(), claims.sub, next).await {
+        Ok(ride) => {
+            info!(ride_id = %id, status = %ride.status.as_str(), "Ride status updated successfully");
+            hub.publish(
+                Topic::Ride(ride.id.clone()),
+                RealtimeEvent::RideStatus {
+                    ride: Box::new(ride.clone()),
+                },
+            );
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": ride })),
+            ))
+        }
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Failed to update ride status");
+            Err(e)
+        }
+    }
+}
+
+pub async fn rate_ride(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateRatingRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, user_id = %claims.sub, "Rating ride");
+
+    match rate_ride_service(&pool, id.clone(), claims.sub, payload).await {
+        Ok(rating) => {
+            info!(ride_id = %id, rating_id = %rating.id, "Ride rated successfully");
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({ "status": "success", "data": rating })),
+            ))
+        }
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Failed to rate ride");
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_ride_rating(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(ride_id = %id, user_id = %claims.sub, "Fetching ride rating");
+
+    match get_ride_rating_service(&pool, id.clone(), claims.sub).await {
+        Ok(rating) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": rating })),
+        )),
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Failed to fetch ride rating");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// History & Receipt Handlers
+// ============================================================================
+
+pub async fn ride_history(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<RideHistoryQuery>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let as_driver = Role::from_claim(&claims.role) == Some(Role::Driver);
+    info!(user_id = %claims.sub, as_driver, "Fetching ride history");
+
+    match ride_history_service(&pool, claims.sub, as_driver, query).await {
+        Ok(history) => {
+            info!(
+                count = history.items.len(),
+                total = history.total,
+                "Ride history fetched successfully"
+            );
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": history })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch ride history");
+            Err(e)
+        }
+    }
+}
+
+pub async fn get_ride_receipt(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Query(query): Query<ReceiptQuery>,
+) -> Result<Response, AppError> {
+    info!(ride_id = %id, user_id = %claims.sub, "Fetching ride receipt");
+
+    let receipt = match ride_receipt_service(&pool, id.clone(), claims.sub).await {
+        Ok(receipt) => receipt,
+        Err(e) => {
+            error!(ride_id = %id, error = %e, "Failed to build ride receipt");
+            return Err(e);
+        }
+    };
+
+    let response = match query.format.unwrap_or(ReceiptFormat::Json) {
+        ReceiptFormat::Json => (
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": receipt })),
+        )
+            .into_response(),
+        ReceiptFormat::Html => {
+            let disposition = format!(
+                "attachment; filename=\"receipt-{}.html\"",
+                receipt.receipt_number
+            );
+            (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                render_receipt_html(&receipt),
+            )
+                .into_response()
+        }
+    };
+    Ok(response)
+}
+
+/// Reloads a ride and pushes its current state to live subscribers.
+async fn publish_ride_status(pool: &PgPool, hub: &RealtimeHub, ride_id: String) {
+    match get_ride_service(pool, ride_id.clone()).await {
+        Ok(ride) => hub.publish(
+            Topic::Ride(ride_id),
+            RealtimeEvent::RideStatus {
+                ride: Box::new(ride),
+            },
+        ),
+        Err(e) => error!(ride_id = %ride_id, error = %e, "Failed to load ride for realtime update"),
+    }
+}
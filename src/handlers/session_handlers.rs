@@ -0,0 +1,91 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::auth_model::PushTokenRequest;
+use crate::services::session_service::{
+    list_sessions_service, register_push_token_service, remove_push_token_service,
+    revoke_account_session_service,
+};
+
+// ============================================================================
+// Session Handlers
+// ============================================================================
+
+pub async fn list_sessions(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match list_sessions_service(&pool, &claims.sub, Some(&claims.sid)).await {
+        Ok(sessions) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": sessions })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to list sessions");
+            Err(e)
+        }
+    }
+}
+
+pub async fn revoke_own_session(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(user_id = %claims.sub, session_id = %id, "Revoking session");
+
+    match revoke_account_session_service(&pool, &claims.sub, &id, "user_revoked").await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Session revoked" })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, session_id = %id, error = %e, "Failed to revoke session");
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Push Token Handlers
+// ============================================================================
+
+pub async fn register_push_token(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PushTokenRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match register_push_token_service(&pool, &claims, &payload.push_token).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Push token registered" })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to register push token");
+            Err(e)
+        }
+    }
+}
+
+pub async fn remove_push_token(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match remove_push_token_service(&pool, &claims).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Push token removed" })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to remove push token");
+            Err(e)
+        }
+    }
+}
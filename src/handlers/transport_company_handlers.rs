@@ -2,23 +2,28 @@ use axum::Extension;
 use axum::Json;
 use axum::extract::Path;
 use axum::http::StatusCode;
+use serde_json::{Value, json};
 use sqlx::PgPool;
 use tracing::{error, info};
 
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::{Permission, Principal};
+use crate::error::AppError;
 use crate::models::transport_company_model::{
-    CreateDriverRequest, CreateTransportCompanyRequest, CreateVehicleRequest, DriverResponse,
-    TransportCompanyResponse, UpdateDriverRequest, UpdateTransportCompanyRequest,
+    CreateCompanyAdminRequest, CreateDriverRequest, CreateTransportCompanyRequest,
+    CreateVehicleRequest, DriverResponse, TransportCompanyResponse,
+    UpdateDriverAvailabilityRequest, UpdateDriverRequest, UpdateTransportCompanyRequest,
     UpdateVehicleRequest, VehicleResponse,
 };
 use crate::services::transport_company_service::{
-    create_driver_service, create_transport_company_service, create_vehicle_service,
-    delete_driver_service, delete_transport_company_service, delete_vehicle_service,
-    get_driver_service, get_transport_company_service, get_vehicle_service, list_drivers_service,
-    list_transport_companies_service, list_vehicles_service, patch_driver_service,
-    patch_transport_company_service, patch_vehicle_service, update_driver_service,
+    create_company_admin_service, create_driver_service, create_transport_company_service,
+    create_vehicle_service, delete_driver_service, delete_transport_company_service,
+    delete_vehicle_service, get_driver_service, get_transport_company_service, get_vehicle_service,
+    list_company_admins_service, list_drivers_service, list_transport_companies_service,
+    list_vehicles_service, patch_driver_service, patch_transport_company_service,
+    patch_vehicle_service, update_driver_availability_service, update_driver_service,
     update_transport_company_service, update_vehicle_service,
 };
-use chrono::Utc;
 
 // ============================================================================
 // Transport Company Handlers
@@ -26,7 +31,7 @@ use chrono::Utc;
 
 pub async fn list_transport_companies(
     Extension(pool): Extension<PgPool>,
-) -> (StatusCode, Json<Vec<TransportCompanyResponse>>) {
+) -> Result<(StatusCode, Json<Vec<TransportCompanyResponse>>), AppError> {
     info!("Fetching all transport companies");
     let result = list_transport_companies_service(&pool).await;
     match result {
@@ -35,11 +40,11 @@ pub async fn list_transport_companies(
                 count = companies.len(),
                 "Transport companies fetched successfully"
             );
-            (StatusCode::OK, Json(companies))
+            Ok((StatusCode::OK, Json(companies)))
         }
         Err(e) => {
             error!(error = %e, "Failed to fetch transport companies");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -47,32 +52,18 @@ pub async fn list_transport_companies(
 pub async fn create_transport_company(
     Extension(pool): Extension<PgPool>,
     Json(payload): Json<CreateTransportCompanyRequest>,
-) -> (StatusCode, Json<TransportCompanyResponse>) {
+) -> Result<(StatusCode, Json<TransportCompanyResponse>), AppError> {
     info!(email = %payload.email, name = %payload.name, "Creating new transport company");
     let result = create_transport_company_service(&pool, payload).await;
 
     match result {
         Ok(company) => {
             info!(company_id = %company.id, "Transport company created successfully");
-            (StatusCode::CREATED, Json(company))
+            Ok((StatusCode::CREATED, Json(company)))
         }
         Err(e) => {
             error!(error = %e, "Failed to create transport company");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(TransportCompanyResponse {
-                    id: "".to_string(),
-                    name: "".to_string(),
-                    email: "".to_string(),
-                    phone: None,
-                    address: None,
-                    is_active: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    vehicles: None,
-                    drivers: None,
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -80,30 +71,16 @@ pub async fn create_transport_company(
 pub async fn get_transport_company(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, Json<TransportCompanyResponse>) {
+) -> Result<(StatusCode, Json<TransportCompanyResponse>), AppError> {
     info!(company_id = %id, "Fetching transport company by ID");
     match get_transport_company_service(&pool, id.clone()).await {
         Ok(company) => {
             info!(company_id = %id, "Transport company fetched successfully");
-            (StatusCode::OK, Json(company))
+            Ok((StatusCode::OK, Json(company)))
         }
         Err(e) => {
             error!(company_id = %id, error = %e, "Transport company not found");
-            (
-                StatusCode::NOT_FOUND,
-                Json(TransportCompanyResponse {
-                    id: "".to_string(),
-                    name: "".to_string(),
-                    email: "".to_string(),
-                    phone: None,
-                    address: None,
-                    is_active: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    vehicles: None,
-                    drivers: None,
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -112,30 +89,16 @@ pub async fn update_transport_company(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateTransportCompanyRequest>,
-) -> (StatusCode, Json<TransportCompanyResponse>) {
+) -> Result<(StatusCode, Json<TransportCompanyResponse>), AppError> {
     info!(company_id = %id, "Updating transport company");
     match update_transport_company_service(&pool, id.clone(), payload).await {
         Ok(company) => {
             info!(company_id = %id, "Transport company updated successfully");
-            (StatusCode::OK, Json(company))
+            Ok((StatusCode::OK, Json(company)))
         }
         Err(e) => {
             error!(company_id = %id, error = %e, "Failed to update transport company");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(TransportCompanyResponse {
-                    id: "".to_string(),
-                    name: "".to_string(),
-                    email: "".to_string(),
-                    phone: None,
-                    address: None,
-                    is_active: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    vehicles: None,
-                    drivers: None,
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -144,30 +107,16 @@ pub async fn patch_transport_company(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateTransportCompanyRequest>,
-) -> (StatusCode, Json<TransportCompanyResponse>) {
+) -> Result<(StatusCode, Json<TransportCompanyResponse>), AppError> {
     info!(company_id = %id, "Patching transport company");
     match patch_transport_company_service(&pool, id.clone(), payload).await {
         Ok(company) => {
             info!(company_id = %id, "Transport company patched successfully");
-            (StatusCode::OK, Json(company))
+            Ok((StatusCode::OK, Json(company)))
         }
         Err(e) => {
             error!(company_id = %id, error = %e, "Failed to patch transport company");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(TransportCompanyResponse {
-                    id: "".to_string(),
-                    name: "".to_string(),
-                    email: "".to_string(),
-                    phone: None,
-                    address: None,
-                    is_active: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    vehicles: None,
-                    drivers: None,
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -175,22 +124,19 @@ pub async fn patch_transport_company(
 pub async fn delete_transport_company(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, String) {
+) -> Result<(StatusCode, String), AppError> {
     info!(company_id = %id, "Deleting transport company");
     match delete_transport_company_service(&pool, id.clone()).await {
         Ok(_) => {
             info!(company_id = %id, "Transport company deleted successfully");
-            (
+            Ok((
                 StatusCode::OK,
                 format!("Transport company {} deleted successfully", id),
-            )
+            ))
         }
         Err(e) => {
             error!(company_id = %id, error = %e, "Failed to delete transport company");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to delete transport company: {}", e),
-            )
+            Err(e)
         }
     }
 }
@@ -202,17 +148,17 @@ pub async fn delete_transport_company(
 pub async fn list_vehicles(
     Extension(pool): Extension<PgPool>,
     Path(transport_company_id): Path<String>,
-) -> (StatusCode, Json<Vec<VehicleResponse>>) {
+) -> Result<(StatusCode, Json<Vec<VehicleResponse>>), AppError> {
     info!(company_id = %transport_company_id, "Fetching all vehicles");
     let result = list_vehicles_service(&pool, transport_company_id).await;
     match result {
         Ok(vehicles) => {
             info!(count = vehicles.len(), "Vehicles fetched successfully");
-            (StatusCode::OK, Json(vehicles))
+            Ok((StatusCode::OK, Json(vehicles)))
         }
         Err(e) => {
             error!(error = %e, "Failed to fetch vehicles");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -220,30 +166,18 @@ pub async fn list_vehicles(
 pub async fn create_vehicle(
     Extension(pool): Extension<PgPool>,
     Json(payload): Json<CreateVehicleRequest>,
-) -> (StatusCode, Json<VehicleResponse>) {
+) -> Result<(StatusCode, Json<VehicleResponse>), AppError> {
     info!(license_plate = %payload.license_plate, "Creating new vehicle");
     let result = create_vehicle_service(&pool, payload).await;
 
     match result {
         Ok(vehicle) => {
             info!(vehicle_id = %vehicle.id, "Vehicle created successfully");
-            (StatusCode::CREATED, Json(vehicle))
+            Ok((StatusCode::CREATED, Json(vehicle)))
         }
         Err(e) => {
             error!(error = %e, "Failed to create vehicle");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(VehicleResponse {
-                    id: "".to_string(),
-                    transport_company_id: "".to_string(),
-                    make: "".to_string(),
-                    model: "".to_string(),
-                    year: 0,
-                    license_plate: "".to_string(),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -251,28 +185,16 @@ pub async fn create_vehicle(
 pub async fn get_vehicle(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, Json<VehicleResponse>) {
+) -> Result<(StatusCode, Json<VehicleResponse>), AppError> {
     info!(vehicle_id = %id, "Fetching vehicle by ID");
     match get_vehicle_service(&pool, id.clone()).await {
         Ok(vehicle) => {
             info!(vehicle_id = %id, "Vehicle fetched successfully");
-            (StatusCode::OK, Json(vehicle))
+            Ok((StatusCode::OK, Json(vehicle)))
         }
         Err(e) => {
             error!(vehicle_id = %id, error = %e, "Vehicle not found");
-            (
-                StatusCode::NOT_FOUND,
-                Json(VehicleResponse {
-                    id: "".to_string(),
-                    transport_company_id: "".to_string(),
-                    make: "".to_string(),
-                    model: "".to_string(),
-                    year: 0,
-                    license_plate: "".to_string(),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -281,28 +203,16 @@ pub async fn update_vehicle(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateVehicleRequest>,
-) -> (StatusCode, Json<VehicleResponse>) {
+) -> Result<(StatusCode, Json<VehicleResponse>), AppError> {
     info!(vehicle_id = %id, "Updating vehicle");
     match update_vehicle_service(&pool, id.clone(), payload).await {
         Ok(vehicle) => {
             info!(vehicle_id = %id, "Vehicle updated successfully");
-            (StatusCode::OK, Json(vehicle))
+            Ok((StatusCode::OK, Json(vehicle)))
         }
         Err(e) => {
             error!(vehicle_id = %id, error = %e, "Failed to update vehicle");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(VehicleResponse {
-                    id: "".to_string(),
-                    transport_company_id: "".to_string(),
-                    make: "".to_string(),
-                    model: "".to_string(),
-                    year: 0,
-                    license_plate: "".to_string(),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -311,28 +221,16 @@ pub async fn patch_vehicle(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateVehicleRequest>,
-) -> (StatusCode, Json<VehicleResponse>) {
+) -> Result<(StatusCode, Json<VehicleResponse>), AppError> {
     info!(vehicle_id = %id, "Patching vehicle");
     match patch_vehicle_service(&pool, id.clone(), payload).await {
         Ok(vehicle) => {
             info!(vehicle_id = %id, "Vehicle patched successfully");
-            (StatusCode::OK, Json(vehicle))
+            Ok((StatusCode::OK, Json(vehicle)))
         }
         Err(e) => {
             error!(vehicle_id = %id, error = %e, "Failed to patch vehicle");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(VehicleResponse {
-                    id: "".to_string(),
-                    transport_company_id: "".to_string(),
-                    make: "".to_string(),
-                    model: "".to_string(),
-                    year: 0,
-                    license_plate: "".to_string(),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -340,22 +238,19 @@ pub async fn patch_vehicle(
 pub async fn delete_vehicle(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, String) {
+) -> Result<(StatusCode, String), AppError> {
     info!(vehicle_id = %id, "Deleting vehicle");
     match delete_vehicle_service(&pool, id.clone()).await {
         Ok(_) => {
             info!(vehicle_id = %id, "Vehicle deleted successfully");
-            (
+            Ok((
                 StatusCode::OK,
                 format!("Vehicle {} deleted successfully", id),
-            )
+            ))
         }
         Err(e) => {
             error!(vehicle_id = %id, error = %e, "Failed to delete vehicle");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to delete vehicle: {}", e),
-            )
+            Err(e)
         }
     }
 }
@@ -367,17 +262,17 @@ pub async fn delete_vehicle(
 pub async fn list_drivers(
     Extension(pool): Extension<PgPool>,
     Path(transport_company_id): Path<String>,
-) -> (StatusCode, Json<Vec<DriverResponse>>) {
+) -> Result<(StatusCode, Json<Vec<DriverResponse>>), AppError> {
     info!(company_id = %transport_company_id, "Fetching all drivers");
     let result = list_drivers_service(&pool, transport_company_id).await;
     match result {
         Ok(drivers) => {
             info!(count = drivers.len(), "Drivers fetched successfully");
-            (StatusCode::OK, Json(drivers))
+            Ok((StatusCode::OK, Json(drivers)))
         }
         Err(e) => {
             error!(error = %e, "Failed to fetch drivers");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -386,35 +281,18 @@ pub async fn create_driver(
     Extension(pool): Extension<PgPool>,
     Path(transport_company_id): Path<String>,
     Json(payload): Json<CreateDriverRequest>,
-) -> (StatusCode, Json<DriverResponse>) {
+) -> Result<(StatusCode, Json<DriverResponse>), AppError> {
     info!(email = %payload.email, username = %payload.username, "Creating new driver");
     let result = create_driver_service(&pool, transport_company_id, payload).await;
 
     match result {
         Ok(driver) => {
             info!(driver_id = %driver.id, "Driver created successfully");
-            (StatusCode::CREATED, Json(driver))
+            Ok((StatusCode::CREATED, Json(driver)))
         }
         Err(e) => {
             error!(error = %e, "Failed to create driver");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DriverResponse {
-                    id: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    username: "".to_string(),
-                    email: "".to_string(),
-                    phone: "".to_string(),
-                    vehicle_id: None,
-                    license_number: None,
-                    vehicle_type: None,
-                    rating: None,
-                    is_available: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -422,33 +300,16 @@ pub async fn create_driver(
 pub async fn get_driver(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, Json<DriverResponse>) {
+) -> Result<(StatusCode, Json<DriverResponse>), AppError> {
     info!(driver_id = %id, "Fetching driver by ID");
     match get_driver_service(&pool, id.clone()).await {
         Ok(driver) => {
             info!(driver_id = %id, "Driver fetched successfully");
-            (StatusCode::OK, Json(driver))
+            Ok((StatusCode::OK, Json(driver)))
         }
         Err(e) => {
             error!(driver_id = %id, error = %e, "Driver not found");
-            (
-                StatusCode::NOT_FOUND,
-                Json(DriverResponse {
-                    id: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    username: "".to_string(),
-                    email: "".to_string(),
-                    phone: "".to_string(),
-                    vehicle_id: None,
-                    license_number: None,
-                    vehicle_type: None,
-                    rating: None,
-                    is_available: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -457,33 +318,16 @@ pub async fn update_driver(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateDriverRequest>,
-) -> (StatusCode, Json<DriverResponse>) {
+) -> Result<(StatusCode, Json<DriverResponse>), AppError> {
     info!(driver_id = %id, "Updating driver");
     match update_driver_service(&pool, id.clone(), payload).await {
         Ok(driver) => {
             info!(driver_id = %id, "Driver updated successfully");
-            (StatusCode::OK, Json(driver))
+            Ok((StatusCode::OK, Json(driver)))
         }
         Err(e) => {
             error!(driver_id = %id, error = %e, "Failed to update driver");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DriverResponse {
-                    id: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    username: "".to_string(),
-                    email: "".to_string(),
-                    phone: "".to_string(),
-                    vehicle_id: None,
-                    license_number: None,
-                    vehicle_type: None,
-                    rating: None,
-                    is_available: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -492,33 +336,40 @@ pub async fn patch_driver(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateDriverRequest>,
-) -> (StatusCode, Json<DriverResponse>) {
+) -> Result<(StatusCode, Json<DriverResponse>), AppError> {
     info!(driver_id = %id, "Patching driver");
     match patch_driver_service(&pool, id.clone(), payload).await {
         Ok(driver) => {
             info!(driver_id = %id, "Driver patched successfully");
-            (StatusCode::OK, Json(driver))
+            Ok((StatusCode::OK, Json(driver)))
         }
         Err(e) => {
             error!(driver_id = %id, error = %e, "Failed to patch driver");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DriverResponse {
-                    id: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    username: "".to_string(),
-                    email: "".to_string(),
-                    phone: "".to_string(),
-                    vehicle_id: None,
-                    license_number: None,
-                    vehicle_type: None,
-                    rating: None,
-                    is_available: false,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                }),
-            )
+            Err(e)
+        }
+    }
+}
+
+pub async fn update_driver_availability(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateDriverAvailabilityRequest>,
+) -> Result<(StatusCode, Json<DriverResponse>), AppError> {
+    info!(driver_id = %id, is_available = payload.is_available, "Updating driver availability");
+    let principal = Principal::from_claims(&claims).map_err(AppError::Forbidden)?;
+    if !principal.can(Permission::DriveRides) || principal.id != id {
+        error!(user_id = %claims.sub, driver_id = %id, "Availability change for another driver denied");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+    match update_driver_availability_service(&pool, id.clone(), payload.is_available).await {
+        Ok(driver) => {
+            info!(driver_id = %id, "Driver availability updated successfully");
+            Ok((StatusCode::OK, Json(driver)))
+        }
+        Err(e) => {
+            error!(driver_id = %id, error = %e, "Failed to update driver availability");
+            Err(e)
         }
     }
 }
@@ -526,22 +377,84 @@ pub async fn patch_driver(
 pub async fn delete_driver(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, String) {
+) -> Result<(StatusCode, String), AppError> {
     info!(driver_id = %id, "Deleting driver");
     match delete_driver_service(&pool, id.clone()).await {
         Ok(_) => {
             info!(driver_id = %id, "Driver deleted successfully");
-            (
+            Ok((
                 StatusCode::OK,
                 format!("Driver {} deleted successfully", id),
-            )
+            ))
         }
         Err(e) => {
             error!(driver_id = %id, error = %e, "Failed to delete driver");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to delete driver: {}", e),
-            )
+            Err(e)
+        }
+    }
+}
+
+// ============================================================================
+// Company Admin Handlers
+// ============================================================================
+
+pub async fn list_company_admins(
+    Extension(pool): Extension<PgPool>,
+    Path(transport_company_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(company_id = %transport_company_id, "Fetching company admins");
+    match list_company_admins_service(&pool, transport_company_id).await {
+        Ok(admins) => {
+            info!(count = admins.len(), "Company admins fetched successfully");
+            Ok((
+                StatusCode::OK,
+                Json(json!({ "status": "success", "data": admins })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to fetch company admins");
+            Err(e)
         }
     }
 }
+
+pub async fn create_company_admin(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transport_company_id): Path<String>,
+    Json(payload): Json<CreateCompanyAdminRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(email = %payload.email, username = %payload.username, company_id = %transport_company_id, "Creating company admin");
+    authorize_company_admin(&claims, &transport_company_id)?;
+    match create_company_admin_service(&pool, transport_company_id, payload).await {
+        Ok(admin) => {
+            info!(admin_id = %admin.id, "Company admin created successfully");
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({ "status": "success", "data": admin })),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create company admin");
+            Err(e)
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Admins can only be added to a company by a platform admin or by an admin
+/// of that company. Checked here as well as on the route, since a new admin
+/// gets full control of the company.
+fn authorize_company_admin(claims: &Claims, transport_company_id: &str) -> Result<(), AppError> {
+    let principal = Principal::from_claims(claims).map_err(AppError::Forbidden)?;
+    if !principal.can(Permission::ManageFleet)
+        || !principal.can_access_company(Some(transport_company_id))
+    {
+        error!(user_id = %claims.sub, company_id = %transport_company_id, "Company admin creation denied");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+    Ok(())
+}
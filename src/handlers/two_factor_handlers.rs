@@ -0,0 +1,103 @@
+use axum::Extension;
+use axum::Json;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::two_factor_model::TwoFactorCodeRequest;
+use crate::services::two_factor_service::{
+    activate_two_factor_service, disable_two_factor_service, enroll_two_factor_service,
+    regenerate_recovery_codes_service, two_factor_status_service,
+};
+
+// ============================================================================
+// Two-Factor Handlers
+// ============================================================================
+
+pub async fn two_factor_status(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match two_factor_status_service(&pool, &claims).await {
+        Ok(status) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": status })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to fetch two-factor status");
+            Err(e)
+        }
+    }
+}
+
+pub async fn enroll_two_factor(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    info!(user_id = %claims.sub, "Starting two-factor enrollment");
+
+    match enroll_two_factor_service(&pool, &claims).await {
+        Ok(enrollment) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": enrollment })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to start two-factor enrollment");
+            Err(e)
+        }
+    }
+}
+
+pub async fn activate_two_factor(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match activate_two_factor_service(&pool, &claims, &payload.code).await {
+        Ok(codes) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": codes })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to enable two-factor authentication");
+            Err(e)
+        }
+    }
+}
+
+pub async fn disable_two_factor(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match disable_two_factor_service(&pool, &claims, &payload.code).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Two-factor authentication disabled" })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to disable two-factor authentication");
+            Err(e)
+        }
+    }
+}
+
+pub async fn regenerate_recovery_codes(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match regenerate_recovery_codes_service(&pool, &claims, &payload.code).await {
+        Ok(codes) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": codes })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to regenerate recovery codes");
+            Err(e)
+        }
+    }
+}
@@ -5,6 +5,9 @@ use axum::http::StatusCode;
 use sqlx::PgPool;
 use tracing::{error, info};
 
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::{Permission, Principal};
+use crate::error::AppError;
 use crate::models::user_model::{CreateUserRequest, UpdateUserRequest, UserResponse};
 use crate::services::user_service::{
     create_user_service, delete_user_service, get_user_service, list_users_service,
@@ -17,17 +20,17 @@ use crate::services::user_service::{
 
 pub async fn list_users(
     Extension(pool): Extension<PgPool>,
-) -> (StatusCode, Json<Vec<UserResponse>>) {
+) -> Result<(StatusCode, Json<Vec<UserResponse>>), AppError> {
     info!("Fetching all users");
     let result = list_users_service(&pool).await;
     match result {
         Ok(users) => {
             info!(count = users.len(), "Users fetched successfully");
-            (StatusCode::OK, Json(users))
+            Ok((StatusCode::OK, Json(users)))
         }
         Err(e) => {
             error!(error = %e, "Failed to fetch users");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(vec![]))
+            Err(e)
         }
     }
 }
@@ -35,32 +38,18 @@ pub async fn list_users(
 pub async fn create_user(
     Extension(pool): Extension<PgPool>,
     Json(payload): Json<CreateUserRequest>,
-) -> (StatusCode, Json<UserResponse>) {
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
     info!(email = %payload.email, username = %payload.username, "Creating new user");
     let result = create_user_service(&pool, payload).await;
 
     match result {
         Ok(user) => {
             info!(user_id = %user.id, "User created successfully");
-            (StatusCode::CREATED, Json(user))
+            Ok((StatusCode::CREATED, Json(user)))
         }
         Err(e) => {
             error!(error = %e, "Failed to create user");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(UserResponse {
-                    id: "".to_string(),
-                    email: "".to_string(),
-                    username: "".to_string(),
-                    password_hash: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    phone: None,
-                    profile_picture: None,
-                    is_active: false,
-                    created_at: chrono::Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -68,94 +57,56 @@ pub async fn create_user(
 pub async fn get_user(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, Json<UserResponse>) {
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
     info!(user_id = %id, "Fetching user by ID");
     match get_user_service(&pool, id.clone()).await {
         Ok(user) => {
             info!(user_id = %id, "User fetched successfully");
-            (StatusCode::OK, Json(user))
+            Ok((StatusCode::OK, Json(user)))
         }
         Err(e) => {
-            error!(user_id = %id, error = %e, "User not found");
-            (
-                StatusCode::NOT_FOUND,
-                Json(UserResponse {
-                    id: "".to_string(),
-                    email: "".to_string(),
-                    username: "".to_string(),
-                    password_hash: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    phone: None,
-                    profile_picture: None,
-                    is_active: false,
-                    created_at: chrono::Utc::now(),
-                }),
-            )
+            error!(user_id = %id, error = %e, "Failed to fetch user");
+            Err(e)
         }
     }
 }
 
 pub async fn update_user(
     Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateUserRequest>,
-) -> (StatusCode, Json<UserResponse>) {
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
     info!(user_id = %id, "Updating user");
+    authorize_update(&claims, &payload)?;
     match update_user_service(&pool, id.clone(), payload).await {
         Ok(user) => {
             info!(user_id = %id, "User updated successfully");
-            (StatusCode::OK, Json(user))
+            Ok((StatusCode::OK, Json(user)))
         }
         Err(e) => {
             error!(user_id = %id, error = %e, "Failed to update user");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(UserResponse {
-                    id: "".to_string(),
-                    email: "".to_string(),
-                    username: "".to_string(),
-                    password_hash: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    phone: None,
-                    profile_picture: None,
-                    is_active: false,
-                    created_at: chrono::Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
 
 pub async fn patch_user(
     Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateUserRequest>,
-) -> (StatusCode, Json<UserResponse>) {
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
     info!(user_id = %id, "Patching user");
+    authorize_update(&claims, &payload)?;
     match patch_user_service(&pool, id.clone(), payload).await {
         Ok(user) => {
             info!(user_id = %id, "User patched successfully");
-            (StatusCode::OK, Json(user))
+            Ok((StatusCode::OK, Json(user)))
         }
         Err(e) => {
             error!(user_id = %id, error = %e, "Failed to patch user");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(UserResponse {
-                    id: "".to_string(),
-                    email: "".to_string(),
-                    username: "".to_string(),
-                    password_hash: "".to_string(),
-                    first_name: "".to_string(),
-                    last_name: "".to_string(),
-                    phone: None,
-                    profile_picture: None,
-                    is_active: false,
-                    created_at: chrono::Utc::now(),
-                }),
-            )
+            Err(e)
         }
     }
 }
@@ -163,19 +114,33 @@ pub async fn patch_user(
 pub async fn delete_user(
     Extension(pool): Extension<PgPool>,
     Path(id): Path<String>,
-) -> (StatusCode, String) {
+) -> Result<(StatusCode, String), AppError> {
     info!(user_id = %id, "Deleting user");
     match delete_user_service(&pool, id.clone()).await {
         Ok(msg) => {
             info!(user_id = %id, "User deleted successfully");
-            (StatusCode::OK, msg)
+            Ok((StatusCode::OK, msg))
         }
         Err(e) => {
             error!(user_id = %id, error = %e, "Failed to delete user");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to delete user: {}", e),
-            )
+            Err(e)
         }
     }
 }
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Account owners edit their own profile, but only user admins may
+/// (de)activate an account.
+fn authorize_update(claims: &Claims, payload: &UpdateUserRequest) -> Result<(), AppError> {
+    let principal = Principal::from_claims(claims).map_err(AppError::Forbidden)?;
+    if payload.is_active.is_some() && !principal.can(Permission::ManageUsers) {
+        error!(user_id = %claims.sub, "Account activation changed without ManageUsers");
+        return Err(AppError::Forbidden(
+            "Only admins can change is_active".to_string(),
+        ));
+    }
+    Ok(())
+}
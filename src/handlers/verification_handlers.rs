@@ -0,0 +1,125 @@
+use axum::Extension;
+use axum::Json;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::Role;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
+use crate::models::verification_model::{ConfirmEmailQuery, ConfirmPhoneRequest};
+use crate::services::notification_service::Notifier;
+use crate::services::verification_service::{
+    confirm_email_service, confirm_phone_service, send_email_verification_service,
+    send_phone_verification_service, verification_status_service,
+};
+
+// ============================================================================
+// Verification Handlers
+// ============================================================================
+
+pub async fn verification_status(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let account_type = account_type(&claims)?;
+
+    match verification_status_service(&pool, &claims.sub, &account_type).await {
+        Ok(status) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "data": status })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to fetch verification status");
+            Err(e)
+        }
+    }
+}
+
+pub async fn send_email_verification(
+    Extension(pool): Extension<PgPool>,
+    Extension(notifier): Extension<Notifier>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let account_type = account_type(&claims)?;
+    info!(user_id = %claims.sub, "Sending email verification");
+
+    match send_email_verification_service(&pool, &notifier, &claims.sub, &account_type).await {
+        Ok(sent) => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "status": "success", "data": sent })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to send email verification");
+            Err(e)
+        }
+    }
+}
+
+pub async fn confirm_email(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<ConfirmEmailQuery>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    match confirm_email_service(&pool, &query.token).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Email verified" })),
+        )),
+        Err(e) => {
+            error!(error = %e, "Failed to confirm email");
+            Err(e)
+        }
+    }
+}
+
+pub async fn send_phone_verification(
+    Extension(pool): Extension<PgPool>,
+    Extension(notifier): Extension<Notifier>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let account_type = account_type(&claims)?;
+    info!(user_id = %claims.sub, "Sending phone verification code");
+
+    match send_phone_verification_service(&pool, &notifier, &claims.sub, &account_type).await {
+        Ok(sent) => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "status": "success", "data": sent })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to send phone verification code");
+            Err(e)
+        }
+    }
+}
+
+pub async fn confirm_phone(
+    Extension(pool): Extension<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ConfirmPhoneRequest>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let account_type = account_type(&claims)?;
+
+    match confirm_phone_service(&pool, &claims.sub, &account_type, &payload.code).await {
+        Ok(()) => Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "success", "message": "Phone number verified" })),
+        )),
+        Err(e) => {
+            error!(user_id = %claims.sub, error = %e, "Failed to confirm phone number");
+            Err(e)
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn account_type(claims: &Claims) -> Result<AccountType, AppError> {
+    Role::from_claim(&claims.role)
+        .map(|role| role.account_type())
+        .ok_or_else(|| AppError::Forbidden("Access denied".to_string()))
+}
@@ -1,7 +1,17 @@
+use auth::password_utils::AuthConfig;
 use axum::{Extension, Router, routing::get};
 use services::database_service::init_db_pool;
+use services::location_service::{LocationBuffer, LocationConfig, spawn_location_flusher};
+use services::notification_service::Notifier;
+use services::payment_gateway::PaymentProvider;
+use services::realtime_service::RealtimeHub;
+use services::schedule_service::spawn_ride_scheduler;
+use std::net::SocketAddr;
 use tracing::info;
 mod auth;
+mod config;
+mod crypto;
+mod error;
 mod handlers;
 mod models;
 mod routes;
@@ -18,12 +28,24 @@ async fn main() {
 
     info!("Starting RideNow Backend");
 
+    // Load the JWT signing keys up front so a bad key setup fails fast
+    AuthConfig::global();
+
     let db_pool = init_db_pool()
         .await
         .expect("Failed to initialize database pool");
 
     info!("Database pool initialized successfully");
 
+    let location_buffer = LocationBuffer::new(LocationConfig::from_env());
+    spawn_location_flusher(db_pool.clone(), location_buffer.clone());
+
+    let realtime_hub = RealtimeHub::new();
+    let notifier = Notifier::from_env();
+    spawn_ride_scheduler(db_pool.clone(), realtime_hub.clone(), notifier.clone());
+
+    let payment_provider = PaymentProvider::from_env();
+
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "3000".to_string())
         .parse::<u16>()
@@ -32,6 +54,7 @@ async fn main() {
     let app = Router::new()
         .route("/", get(|| async { "Hello from Axum! 🦀" }))
         .nest("/login", routes::login_route::login_route())
+        .nest("/auth", routes::auth_route::auth_routes())
         .nest("/protected", routes::protected_route::protected_routes())
         .nest("/users", routes::user_route::user_routes())
         .nest(
@@ -39,6 +62,19 @@ async fn main() {
             routes::transport_company_route::transport_company_routes(),
         )
         .nest("/payments", routes::payment_route::payment_routes())
+        .nest("/pricing", routes::pricing_route::pricing_routes())
+        .nest("/rides", routes::ride_route::ride_routes())
+        .nest("/locations", routes::location_route::location_routes())
+        .nest("/ws", routes::realtime_route::realtime_routes())
+        .nest("/admin", routes::admin_route::admin_routes())
+        .nest(
+            "/.well-known",
+            routes::well_known_route::well_known_routes(),
+        )
+        .layer(Extension(location_buffer))
+        .layer(Extension(realtime_hub))
+        .layer(Extension(notifier))
+        .layer(Extension(payment_provider))
         .layer(Extension(db_pool));
 
     let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port))
@@ -46,5 +82,11 @@ async fn main() {
         .unwrap();
 
     info!("Listening on http://127.0.0.1:{}", port);
-    axum::serve(listener, app).await.unwrap();
+    // Peer addresses feed the per-IP login lockout
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
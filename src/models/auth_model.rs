@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::login_model::AccountType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogoutRequest {
+    /// Revoke every session of the account instead of only the current one.
+    #[serde(default)]
+    pub all_devices: bool,
+}
+
+/// Access token plus the refresh token that replaces the one just used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+    pub refresh_token: String,
+    pub refresh_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+    /// `rider` (the default) or `driver`.
+    #[serde(default)]
+    pub account_type: AccountType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+/// Kind of device a session was opened on, used to pick the push service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DevicePlatform {
+    Ios,
+    Android,
+    Web,
+    Other,
+}
+
+impl DevicePlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DevicePlatform::Ios => "ios",
+            DevicePlatform::Android => "android",
+            DevicePlatform::Web => "web",
+            DevicePlatform::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<DevicePlatform> {
+        match value {
+            "ios" => Some(DevicePlatform::Ios),
+            "android" => Some(DevicePlatform::Android),
+            "web" => Some(DevicePlatform::Web),
+            "other" => Some(DevicePlatform::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Device details a client sends with the login request that opens a
+/// session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Shown in the session list, e.g. "Ada's iPhone".
+    pub device_name: Option<String>,
+    pub platform: Option<DevicePlatform>,
+}
+
+/// An open session as shown to its owner or to support.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub device_name: Option<String>,
+    pub platform: Option<DevicePlatform>,
+    /// Address of the last login or refresh.
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Last login or token refresh.
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub two_factor: bool,
+    pub push_enabled: bool,
+    /// The session of the token making the request.
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushTokenRequest {
+    /// FCM or APNs device token.
+    pub push_token: String,
+}
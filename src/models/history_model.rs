@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::location_model::LocationHistoryPoint;
+use crate::models::money_model::Money;
+use crate::models::ride_model::{RideResponse, RideStatus};
+
+// ============================================================================
+// Ride History
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideHistoryQuery {
+    /// 1-based page number.
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    pub status: Option<RideStatus>,
+}
+
+/// Latest payment recorded against a ride, matched to the ride by its
+/// gateway reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RidePaymentSummary {
+    pub reference: String,
+    pub status: String,
+    pub amount: Money,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideHistoryItem {
+    #[serde(flatten)]
+    pub ride: RideResponse,
+    pub payment: Option<RidePaymentSummary>,
+    /// Score the requesting user gave the other party, if they rated the trip.
+    pub rating_given: Option<i16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideHistoryPage {
+    pub items: Vec<RideHistoryItem>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+// ============================================================================
+// Receipts
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptFormat {
+    Json,
+    Html,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptQuery {
+    pub format: Option<ReceiptFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptLineItem {
+    pub label: String,
+    /// Minor currency units.
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideReceipt {
+    pub receipt_number: String,
+    pub ride_id: String,
+    pub status: RideStatus,
+    pub issued_at: DateTime<Utc>,
+    pub rider_name: String,
+    pub driver_name: Option<String>,
+    pub vehicle_type: Option<String>,
+    pub pickup_address: Option<String>,
+    pub pickup_lat: f64,
+    pub pickup_lng: f64,
+    pub dropoff_address: Option<String>,
+    pub dropoff_lat: f64,
+    pub dropoff_lng: f64,
+    pub requested_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub distance_km: Option<f64>,
+    pub duration_seconds: Option<i64>,
+    pub currency: String,
+    pub line_items: Vec<ReceiptLineItem>,
+    pub total: i64,
+    pub payment: Option<RidePaymentSummary>,
+    /// Recorded GPS trail of the trip.
+    pub route: Vec<LocationHistoryPoint>,
+}
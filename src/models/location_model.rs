@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationPing {
+    pub lat: f64,
+    pub lng: f64,
+    pub heading: Option<f64>,
+    pub speed: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationUpdateRequest {
+    pub pings: Vec<LocationPing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationUpdateResponse {
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverLocationResponse {
+    pub driver_id: String,
+    pub lat: f64,
+    pub lng: f64,
+    pub heading: Option<f64>,
+    pub speed: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub recorded_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationHistoryPoint {
+    pub lat: f64,
+    pub lng: f64,
+    pub heading: Option<f64>,
+    pub speed: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub recorded_at: DateTime<Utc>,
+}
@@ -1,16 +1,105 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::error::AppError;
+use crate::models::auth_model::DeviceInfo;
+use crate::models::two_factor_model::TwoFactorChallenge;
+
+/// Which kind of account is signing in. Riders, drivers and company admins
+/// live in separate tables, so usernames are only unique per account type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountType {
+    #[default]
+    Rider,
+    Driver,
+    CompanyAdmin,
+}
+
+impl AccountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountType::Rider => "rider",
+            AccountType::Driver => "driver",
+            AccountType::CompanyAdmin => "company_admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<AccountType, AppError> {
+        match value {
+            "rider" => Ok(AccountType::Rider),
+            "driver" => Ok(AccountType::Driver),
+            "company_admin" => Ok(AccountType::CompanyAdmin),
+            other => Err(AppError::Internal(format!(
+                "Unknown account type: {}",
+                other
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// Defaults to `rider`.
+    #[serde(default)]
+    pub account_type: AccountType,
+    /// Recorded on the session when the login completes without 2FA.
+    #[serde(default)]
+    pub device: DeviceInfo,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
+    /// Short-lived access token.
     pub token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+    /// Single-use token for `POST /auth/refresh`.
+    pub refresh_token: String,
     pub user_id: String,
     pub email: String,
     pub username: String,
     pub role: String,
+    pub account_type: AccountType,
+    /// Company of a driver or company admin.
+    pub transport_company_id: Option<String>,
+}
+
+/// Result of the password step of a login.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Authenticated(LoginResponse),
+    TwoFactorRequired(TwoFactorChallenge),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnlockAccountRequest {
+    #[serde(default)]
+    pub account_type: AccountType,
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoginAttemptQuery {
+    pub username: Option<String>,
+    pub ip_address: Option<String>,
+    /// Only failed attempts; defaults to true.
+    pub failed_only: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+/// Audit record of one login attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginAttempt {
+    pub id: String,
+    pub account_type: String,
+    pub username: String,
+    pub account_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub succeeded: bool,
+    pub failure_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
 }
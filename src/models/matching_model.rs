@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Expired,
+}
+
+impl OfferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OfferStatus::Pending => "pending",
+            OfferStatus::Accepted => "accepted",
+            OfferStatus::Declined => "declined",
+            OfferStatus::Expired => "expired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<OfferStatus, AppError> {
+        match value {
+            "pending" => Ok(OfferStatus::Pending),
+            "accepted" => Ok(OfferStatus::Accepted),
+            "declined" => Ok(OfferStatus::Declined),
+            "expired" => Ok(OfferStatus::Expired),
+            other => Err(AppError::Internal(format!(
+                "Unknown offer status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// An available driver near a pickup point, as considered by the matcher.
+#[derive(Debug, Clone)]
+pub struct DriverCandidate {
+    pub driver_id: String,
+    pub rating: Option<f32>,
+    pub distance_km: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideOfferResponse {
+    pub id: String,
+    pub ride_id: String,
+    pub driver_id: String,
+    pub status: OfferStatus,
+    pub rank: i32,
+    pub distance_km: f64,
+    pub offered_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    Matched {
+        driver_id: String,
+    },
+    NoDriverFound,
+    RideClosed,
+    /// Another dispatch loop is already offering the ride.
+    AlreadyDispatching,
+}
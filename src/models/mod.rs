@@ -1,4 +1,18 @@
+pub mod auth_model;
+pub mod history_model;
+pub mod location_model;
+pub mod login_model;
+pub mod matching_model;
+pub mod money_model;
 pub mod payment_model;
-pub mod user_model;
+pub mod pricing_model;
+pub mod rating_model;
+pub mod realtime_model;
+pub mod refund_model;
+pub mod ride_model;
+pub mod schedule_model;
 pub mod transport_company_model;
-pub mod login_model;
\ No newline at end of file
+pub mod two_factor_model;
+pub mod user_model;
+pub mod verification_model;
+pub mod webhook_event_model;
@@ -0,0 +1,143 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+// ============================================================================
+// Currency
+// ============================================================================
+
+/// An ISO 4217 currency code, e.g. `NGN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+/// Currencies without a minor unit; every other code has two decimals.
+const ZERO_DECIMAL_CURRENCIES: [&str; 16] = [
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "VND", "VUV",
+    "XAF", "XOF", "XPF",
+];
+
+impl Currency {
+    pub const NGN: Currency = Currency(*b"NGN");
+
+    /// Accepts three ASCII letters in either case.
+    pub fn parse(value: &str) -> Result<Currency, AppError> {
+        let code: [u8; 3] = value
+            .trim()
+            .as_bytes()
+            .try_into()
+            .ok()
+            .filter(|code: &[u8; 3]| code.iter().all(u8::is_ascii_alphabetic))
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Invalid currency: {} (expected an ISO 4217 code such as NGN)",
+                    value
+                ))
+            })?;
+        Ok(Currency(code.map(|b| b.to_ascii_uppercase())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("currency codes are ASCII")
+    }
+
+    /// Number of decimals between the minor and the major unit.
+    pub fn exponent(&self) -> u32 {
+        if ZERO_DECIMAL_CURRENCIES.contains(&self.as_str()) {
+            0
+        } else {
+            2
+        }
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Currency::parse(&value).map_err(|e| e.to_string())
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.as_str().to_string()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ============================================================================
+// Money
+// ============================================================================
+
+/// An amount in integer minor units (kobo for NGN) of a currency. Amounts are
+/// stored, charged and compared in this form; only providers that want major
+/// units see anything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    pub minor_units: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: Currency) -> Self {
+        Money {
+            minor_units,
+            currency,
+        }
+    }
+
+    /// Reads an amount back from the database.
+    pub fn stored(minor_units: i64, currency: &str) -> Result<Money, AppError> {
+        let currency = Currency::parse(currency)
+            .map_err(|_| AppError::Internal(format!("Unknown stored currency: {}", currency)))?;
+        Ok(Money::new(minor_units, currency))
+    }
+
+    /// An amount someone is asked to pay; it must be above zero.
+    pub fn charge(minor_units: i64, currency: Currency) -> Result<Money, AppError> {
+        if minor_units <= 0 {
+            return Err(AppError::Validation(
+                "amount must be a positive number of minor units".to_string(),
+            ));
+        }
+        Ok(Money::new(minor_units, currency))
+    }
+
+    /// Reads an amount a provider reports in major units, e.g. `1500.5`.
+    pub fn from_major_units(amount: f64, currency: Currency) -> Money {
+        let scale = 10f64.powi(currency.exponent() as i32);
+        Money::new((amount * scale).round() as i64, currency)
+    }
+
+    /// The decimal amount in major units, e.g. `1500.50`.
+    pub fn major_units(&self) -> String {
+        let exponent = self.currency.exponent();
+        if exponent == 0 {
+            return self.minor_units.to_string();
+        }
+        let scale = 10i64.pow(exponent);
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        let amount = self.minor_units.unsigned_abs();
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            amount / scale as u64,
+            amount % scale as u64,
+            width = exponent as usize
+        )
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.currency, self.major_units())
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::money_model::Money;
+
 // ============================================================================
 // Request/Response Models
 // ============================================================================
@@ -8,7 +10,11 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InitializeRequest {
     pub email: String,
-    pub amount: String,
+    /// Amount in minor units. Omit when paying for a ride: the fare is
+    /// taken from the completed ride instead.
+    pub amount: Option<i64>,
+    pub ride_id: Option<String>,
+    /// ISO 4217 code; defaults to NGN.
     pub currency: Option<String>,
     pub user_id: Option<String>,
     pub driver_id: Option<String>,
@@ -24,8 +30,10 @@ pub struct InitializeResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InitializeDataResponse {
     pub authorization_url: String,
-    pub access_code: String,
+    pub access_code: Option<String>,
     pub reference: String,
+    /// The gateway the payment was opened with, e.g. `paystack`.
+    pub gateway: String,
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -38,9 +46,13 @@ pub struct VerifyResponse {
 #[derive(Deserialize, Debug, Serialize)]
 pub struct VerifyResponseData {
     pub status: String,
-    pub amount: u64,
+    /// The amount the gateway confirmed.
+    pub amount: Money,
     pub reference: String,
     pub gateway_response: String,
+    /// The gateway confirmed a different amount or currency than the payment
+    /// was initialized with.
+    pub amount_mismatch: bool,
 }
 
 // #[derive(Debug, Deserialize)]
@@ -55,13 +67,18 @@ pub struct PaymentResponse {
     pub payer_type: String, // "user" or "driver"
     pub payer_id: String,
     pub email: String,
-    pub amount: String,
-    pub currency: String,
+    pub amount: Money,
     pub status: String,
     pub reference: String,
     pub authorization_url: Option<String>,
     pub access_code: Option<String>,
     pub gateway_response: Option<String>,
+    pub gateway: String,
+    /// What the gateway confirmed for a successful charge.
+    pub confirmed_amount: Option<Money>,
+    pub amount_mismatch: bool,
+    /// The total of the payment's processed refunds.
+    pub refunded_amount: Money,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
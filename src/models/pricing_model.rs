@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Pricing Rules
+// ============================================================================
+
+/// Rates for one pricing scope. Amounts are in minor currency units (kobo).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingRule {
+    pub id: String,
+    pub transport_company_id: Option<String>,
+    pub vehicle_type: Option<String>,
+    pub currency: String,
+    pub base_fare: i64,
+    pub per_km: i64,
+    pub per_minute: i64,
+    pub minimum_fare: i64,
+    pub booking_fee: i64,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePricingRuleRequest {
+    pub transport_company_id: Option<String>,
+    pub vehicle_type: Option<String>,
+    pub currency: Option<String>,
+    pub base_fare: i64,
+    pub per_km: i64,
+    pub per_minute: i64,
+    pub minimum_fare: i64,
+    pub booking_fee: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePricingRuleRequest {
+    pub currency: Option<String>,
+    pub base_fare: Option<i64>,
+    pub per_km: Option<i64>,
+    pub per_minute: Option<i64>,
+    pub minimum_fare: Option<i64>,
+    pub booking_fee: Option<i64>,
+    pub is_active: Option<bool>,
+}
+
+// ============================================================================
+// Fare Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FareEstimateRequest {
+    pub pickup_lat: f64,
+    pub pickup_lng: f64,
+    pub dropoff_lat: f64,
+    pub dropoff_lng: f64,
+    pub vehicle_type: Option<String>,
+    pub transport_company_id: Option<String>,
+}
+
+/// Itemised fare as computed by the pricing engine. Stored on the ride so the
+/// amount charged can always be explained.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FareBreakdown {
+    /// Rule that priced the trip; `None` when the built-in defaults were used.
+    pub pricing_rule_id: Option<String>,
+    pub currency: String,
+    pub distance_km: f64,
+    pub duration_seconds: i64,
+    pub base_fare: i64,
+    pub distance_fare: i64,
+    pub time_fare: i64,
+    pub booking_fee: i64,
+    /// Demand multiplier applied to the metered fare.
+    #[serde(default = "no_surge")]
+    pub surge_multiplier: f64,
+    /// Amount added to the metered fare by the surge multiplier.
+    #[serde(default)]
+    pub surge_fare: i64,
+    /// Top-up applied when the metered fare falls below the minimum fare.
+    pub minimum_fare_adjustment: i64,
+    pub total: i64,
+}
+
+fn no_surge() -> f64 {
+    1.0
+}
+
+// ============================================================================
+// Surge Models
+// ============================================================================
+
+/// Demand and supply observed in a pickup zone and the multiplier they
+/// produced. Recorded on each ride so a surged fare can be audited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurgeSnapshot {
+    pub zone_id: String,
+    /// Open ride requests in the zone within the demand window.
+    pub demand: i32,
+    /// Available drivers with a fresh position in the zone.
+    pub supply: i32,
+    pub multiplier: f64,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Which side of the trip left a rating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RaterRole {
+    /// The rider rating the driver.
+    Rider,
+    /// The driver rating the rider.
+    Driver,
+}
+
+impl RaterRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RaterRole::Rider => "rider",
+            RaterRole::Driver => "driver",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<RaterRole, AppError> {
+        match value {
+            "rider" => Ok(RaterRole::Rider),
+            "driver" => Ok(RaterRole::Driver),
+            other => Err(AppError::Internal(format!("Unknown rater role: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRatingRequest {
+    /// Whole stars from 1 to 5.
+    pub score: i16,
+    pub comment: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingResponse {
+    pub id: String,
+    pub ride_id: String,
+    pub rater_role: RaterRole,
+    pub rater_id: String,
+    pub ratee_id: String,
+    pub score: i16,
+    pub comment: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
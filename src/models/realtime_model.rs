@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::matching_model::RideOfferResponse;
+use crate::models::ride_model::RideResponse;
+
+/// Messages pushed to WebSocket subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeEvent {
+    RideStatus {
+        ride: Box<RideResponse>,
+    },
+    DriverLocation {
+        driver_id: String,
+        lat: f64,
+        lng: f64,
+        heading: Option<f64>,
+        speed: Option<f64>,
+        recorded_at: DateTime<Utc>,
+    },
+    EtaChanged {
+        ride_id: String,
+        eta_seconds: i64,
+    },
+    RideOffered {
+        offer: RideOfferResponse,
+    },
+    OfferExpired {
+        offer_id: String,
+        ride_id: String,
+    },
+    RideReminder {
+        ride_id: String,
+        pickup_at: DateTime<Utc>,
+    },
+}
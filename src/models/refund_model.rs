@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::models::money_model::Money;
+
+// ============================================================================
+// Refund Status
+// ============================================================================
+
+/// Where a refund stands. Refunds only move forward: `pending` →
+/// `processing` → `processed` or `failed`, possibly skipping steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundStatus {
+    /// Recorded, or accepted by the gateway but not started.
+    Pending,
+    Processing,
+    /// The money is back with the customer.
+    Processed,
+    Failed,
+}
+
+impl RefundStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefundStatus::Pending => "pending",
+            RefundStatus::Processing => "processing",
+            RefundStatus::Processed => "processed",
+            RefundStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<RefundStatus, AppError> {
+        match value {
+            "pending" => Ok(RefundStatus::Pending),
+            "processing" => Ok(RefundStatus::Processing),
+            "processed" => Ok(RefundStatus::Processed),
+            "failed" => Ok(RefundStatus::Failed),
+            other => Err(AppError::Internal(format!(
+                "Unknown refund status: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Maps a provider's refund status, e.g. Paystack's `needs-attention` or
+    /// Flutterwave's `completed`.
+    pub fn from_provider(value: &str) -> RefundStatus {
+        match value.to_ascii_lowercase().as_str() {
+            "processed" | "completed" | "successful" | "success" => RefundStatus::Processed,
+            "failed" => RefundStatus::Failed,
+            "processing" | "needs-attention" => RefundStatus::Processing,
+            _ => RefundStatus::Pending,
+        }
+    }
+
+    /// The states a refund may be in to move to this one.
+    pub fn previous_states(&self) -> &'static [&'static str] {
+        match self {
+            RefundStatus::Pending => &[],
+            RefundStatus::Processing => &["pending"],
+            RefundStatus::Processed | RefundStatus::Failed => &["pending", "processing"],
+        }
+    }
+}
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRefundRequest {
+    /// Minor units in the payment's currency; omit to refund everything not
+    /// refunded yet.
+    pub amount: Option<i64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundResponse {
+    pub id: String,
+    pub payment_reference: String,
+    pub gateway: String,
+    /// The gateway's id for the refund, once it has accepted it.
+    pub gateway_refund_id: Option<String>,
+    pub amount: Money,
+    pub status: RefundStatus,
+    pub reason: Option<String>,
+    pub failure_reason: Option<String>,
+    /// The admin who issued the refund.
+    pub requested_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
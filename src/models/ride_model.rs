@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::models::pricing_model::FareBreakdown;
+
+// ============================================================================
+// Ride Status
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RideStatus {
+    /// Booked ahead; becomes `Requested` when the scheduler dispatches it.
+    Scheduled,
+    Requested,
+    Accepted,
+    Arriving,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl RideStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RideStatus::Scheduled => "scheduled",
+            RideStatus::Requested => "requested",
+            RideStatus::Accepted => "accepted",
+            RideStatus::Arriving => "arriving",
+            RideStatus::InProgress => "in_progress",
+            RideStatus::Completed => "completed",
+            RideStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<RideStatus, AppError> {
+        match value {
+            "scheduled" => Ok(RideStatus::Scheduled),
+            "requested" => Ok(RideStatus::Requested),
+            "accepted" => Ok(RideStatus::Accepted),
+            "arriving" => Ok(RideStatus::Arriving),
+            "in_progress" => Ok(RideStatus::InProgress),
+            "completed" => Ok(RideStatus::Completed),
+            "cancelled" => Ok(RideStatus::Cancelled),
+            other => Err(AppError::Internal(format!(
+                "Unknown ride status: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Whether a ride in this status may move directly to `next`.
+    pub fn can_transition_to(&self, next: RideStatus) -> bool {
+        matches!(
+            (self, next),
+            (RideStatus::Scheduled, RideStatus::Requested)
+                | (RideStatus::Requested, RideStatus::Accepted)
+                | (RideStatus::Accepted, RideStatus::Arriving)
+                | (RideStatus::Arriving, RideStatus::InProgress)
+                | (RideStatus::InProgress, RideStatus::Completed)
+                | (RideStatus::Scheduled, RideStatus::Cancelled)
+                | (RideStatus::Requested, RideStatus::Cancelled)
+                | (RideStatus::Accepted, RideStatus::Cancelled)
+                | (RideStatus::Arriving, RideStatus::Cancelled)
+        )
+    }
+
+    /// Name of the `rides` column stamped when a ride enters this status.
+    /// Scheduled rides are created in that status, so it maps to `created_at`.
+    pub fn timestamp_column(&self) -> &'static str {
+        match self {
+            RideStatus::Scheduled => "created_at",
+            RideStatus::Requested => "requested_at",
+            RideStatus::Accepted => "accepted_at",
+            RideStatus::Arriving => "arriving_at",
+            RideStatus::InProgress => "started_at",
+            RideStatus::Completed => "completed_at",
+            RideStatus::Cancelled => "cancelled_at",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, RideStatus::Completed | RideStatus::Cancelled)
+    }
+}
+
+// ============================================================================
+// Cancellation
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelledBy {
+    Rider,
+    Driver,
+    /// Cancelled automatically, e.g. a booking that found no driver.
+    System,
+}
+
+impl CancelledBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancelledBy::Rider => "rider",
+            CancelledBy::Driver => "driver",
+            CancelledBy::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<CancelledBy, AppError> {
+        match value {
+            "rider" => Ok(CancelledBy::Rider),
+            "driver" => Ok(CancelledBy::Driver),
+            "system" => Ok(CancelledBy::System),
+            other => Err(AppError::Internal(format!(
+                "Unknown cancelling party: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancellationReason {
+    ChangedPlans,
+    DriverTooFar,
+    DriverLate,
+    RiderNoShow,
+    RiderUnreachable,
+    VehicleIssue,
+    SafetyConcern,
+    NoDriverFound,
+    Other,
+}
+
+impl CancellationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancellationReason::ChangedPlans => "changed_plans",
+            CancellationReason::DriverTooFar => "driver_too_far",
+            CancellationReason::DriverLate => "driver_late",
+            CancellationReason::RiderNoShow => "rider_no_show",
+            CancellationReason::RiderUnreachable => "rider_unreachable",
+            CancellationReason::VehicleIssue => "vehicle_issue",
+            CancellationReason::SafetyConcern => "safety_concern",
+            CancellationReason::NoDriverFound => "no_driver_found",
+            CancellationReason::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<CancellationReason, AppError> {
+        match value {
+            "changed_plans" => Ok(CancellationReason::ChangedPlans),
+            "driver_too_far" => Ok(CancellationReason::DriverTooFar),
+            "driver_late" => Ok(CancellationReason::DriverLate),
+            "rider_no_show" => Ok(CancellationReason::RiderNoShow),
+            "rider_unreachable" => Ok(CancellationReason::RiderUnreachable),
+            "vehicle_issue" => Ok(CancellationReason::VehicleIssue),
+            "safety_concern" => Ok(CancellationReason::SafetyConcern),
+            "no_driver_found" => Ok(CancellationReason::NoDriverFound),
+            "other" => Ok(CancellationReason::Other),
+            other => Err(AppError::Internal(format!(
+                "Unknown cancellation reason: {}",
+                other
+            ))),
+        }
+    }
+}
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRideRequest {
+    pub pickup_lat: f64,
+    pub pickup_lng: f64,
+    pub pickup_address: Option<String>,
+    pub dropoff_lat: f64,
+    pub dropoff_lng: f64,
+    pub dropoff_address: Option<String>,
+    pub vehicle_type: Option<String>,
+    /// Pickup time for a book-ahead ride; omit to request a ride now.
+    pub scheduled_for: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelRideRequest {
+    pub reason: CancellationReason,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideResponse {
+    pub id: String,
+    pub rider_id: String,
+    pub driver_id: Option<String>,
+    pub status: RideStatus,
+    pub pickup_lat: f64,
+    pub pickup_lng: f64,
+    pub pickup_address: Option<String>,
+    pub dropoff_lat: f64,
+    pub dropoff_lng: f64,
+    pub dropoff_address: Option<String>,
+    pub vehicle_type: Option<String>,
+    pub currency: Option<String>,
+    /// Upfront fare quoted at request time, in minor currency units.
+    pub estimated_fare: Option<i64>,
+    /// Fare charged for the trip, set on completion.
+    pub final_fare: Option<i64>,
+    pub fare_breakdown: Option<FareBreakdown>,
+    pub surge_multiplier: f64,
+    /// Pickup zone and the demand/supply counts behind the surge multiplier.
+    pub surge_zone: Option<String>,
+    pub surge_demand: Option<i32>,
+    pub surge_supply: Option<i32>,
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub cancelled_by: Option<CancelledBy>,
+    pub cancellation_reason: Option<CancellationReason>,
+    pub cancellation_note: Option<String>,
+    /// Fee owed by the rider for a late cancellation, in minor currency units.
+    pub cancellation_fee: Option<i64>,
+    pub requested_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub arriving_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [RideStatus; 7] = [
+        RideStatus::Scheduled,
+        RideStatus::Requested,
+        RideStatus::Accepted,
+        RideStatus::Arriving,
+        RideStatus::InProgress,
+        RideStatus::Completed,
+        RideStatus::Cancelled,
+    ];
+
+    #[test]
+    fn follows_the_trip_lifecycle() {
+        assert!(RideStatus::Scheduled.can_transition_to(RideStatus::Requested));
+        assert!(RideStatus::Requested.can_transition_to(RideStatus::Accepted));
+        assert!(RideStatus::Accepted.can_transition_to(RideStatus::Arriving));
+        assert!(RideStatus::Arriving.can_transition_to(RideStatus::InProgress));
+        assert!(RideStatus::InProgress.can_transition_to(RideStatus::Completed));
+    }
+
+    #[test]
+    fn cancels_only_before_the_trip_starts() {
+        let cancellable: Vec<RideStatus> = ALL
+            .into_iter()
+            .filter(|status| status.can_transition_to(RideStatus::Cancelled))
+            .collect();
+
+        assert_eq!(
+            cancellable,
+            vec![
+                RideStatus::Scheduled,
+                RideStatus::Requested,
+                RideStatus::Accepted,
+                RideStatus::Arriving,
+            ]
+        );
+    }
+
+    #[test]
+    fn never_skips_or_reverses_a_step() {
+        assert!(!RideStatus::Requested.can_transition_to(RideStatus::InProgress));
+        assert!(!RideStatus::Accepted.can_transition_to(RideStatus::Requested));
+        assert!(!RideStatus::Scheduled.can_transition_to(RideStatus::Accepted));
+        assert!(!RideStatus::InProgress.can_transition_to(RideStatus::InProgress));
+    }
+
+    #[test]
+    fn terminal_statuses_go_nowhere() {
+        for next in ALL {
+            assert!(!RideStatus::Completed.can_transition_to(next));
+            assert!(!RideStatus::Cancelled.can_transition_to(next));
+        }
+    }
+
+    #[test]
+    fn parses_what_it_stores() {
+        for status in ALL {
+            assert_eq!(RideStatus::parse(status.as_str()).ok(), Some(status));
+        }
+        assert!(RideStatus::parse("unknown").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    /// Waiting for its dispatch time.
+    Pending,
+    /// Handed to driver matching; still looking for a driver.
+    Dispatched,
+    Matched,
+    Cancelled,
+    /// No driver was found before the pickup deadline.
+    Expired,
+}
+
+impl ScheduleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleStatus::Pending => "pending",
+            ScheduleStatus::Dispatched => "dispatched",
+            ScheduleStatus::Matched => "matched",
+            ScheduleStatus::Cancelled => "cancelled",
+            ScheduleStatus::Expired => "expired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<ScheduleStatus, AppError> {
+        match value {
+            "pending" => Ok(ScheduleStatus::Pending),
+            "dispatched" => Ok(ScheduleStatus::Dispatched),
+            "matched" => Ok(ScheduleStatus::Matched),
+            "cancelled" => Ok(ScheduleStatus::Cancelled),
+            "expired" => Ok(ScheduleStatus::Expired),
+            other => Err(AppError::Internal(format!(
+                "Unknown schedule status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RideScheduleResponse {
+    pub id: String,
+    pub ride_id: String,
+    pub status: ScheduleStatus,
+    pub pickup_at: DateTime<Utc>,
+    pub dispatch_at: DateTime<Utc>,
+    pub remind_at: DateTime<Utc>,
+    pub reminder_sent_at: Option<DateTime<Utc>>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+    pub dispatch_attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,11 +1,25 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Role of a transport company account, stored as `TCDriver` or `TCAdmin`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Role {
-    TCUser,
-    TCDriver,
-    TCAdmin,
+    #[serde(rename = "TCUser")]
+    User,
+    #[serde(rename = "TCDriver")]
+    Driver,
+    #[serde(rename = "TCAdmin")]
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "TCUser",
+            Role::Driver => "TCDriver",
+            Role::Admin => "TCAdmin",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +32,9 @@ pub struct TransportCompany {
     pub vehicles: Option<Vec<Vehicle>>,
     pub drivers: Option<Vec<Driver>>,
     pub is_active: bool,
+    /// Gateway for the company's payments; `None` follows the platform's
+    /// routing.
+    pub payment_gateway: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -28,6 +45,7 @@ pub struct CreateTransportCompanyRequest {
     pub email: String,
     pub phone: Option<String>,
     pub address: Option<String>,
+    pub payment_gateway: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +55,8 @@ pub struct UpdateTransportCompanyRequest {
     pub phone: Option<String>,
     pub address: Option<String>,
     pub is_active: Option<bool>,
+    /// An empty string clears the override.
+    pub payment_gateway: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +67,7 @@ pub struct TransportCompanyResponse {
     pub phone: Option<String>,
     pub address: Option<String>,
     pub is_active: bool,
+    pub payment_gateway: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub vehicles: Option<Vec<Vehicle>>,
@@ -124,7 +145,6 @@ pub struct CreateDriverRequest {
     pub vehicle_id: Option<String>,
     pub license_number: Option<String>,
     pub vehicle_type: Option<String>,
-    pub rating: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,15 +153,19 @@ pub struct UpdateDriverRequest {
     pub last_name: Option<String>,
     pub username: Option<String>,
     pub email: Option<String>,
-    pub password_hash: Option<String>,
     pub phone: Option<String>,
     pub vehicle_id: Option<String>,
     pub license_number: Option<String>,
     pub vehicle_type: Option<String>,
-    pub rating: Option<f32>,
     pub is_available: Option<bool>,
 }
 
+/// A driver going online or offline.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateDriverAvailabilityRequest {
+    pub is_available: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DriverResponse {
     pub id: String,
@@ -153,8 +177,53 @@ pub struct DriverResponse {
     pub vehicle_id: Option<String>,
     pub license_number: Option<String>,
     pub vehicle_type: Option<String>,
+    /// Average of the driver's most recent trip ratings.
     pub rating: Option<f32>,
+    /// Trip ratings behind `rating`.
+    pub rating_count: i32,
     pub is_available: bool,
+    /// Rides this driver cancelled after accepting them.
+    pub cancellation_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyAdmin {
+    pub id: String,
+    pub transport_company_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub password_hash: String,
+    pub role: Role,
+    pub phone: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCompanyAdminRequest {
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub password: String,
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyAdminResponse {
+    pub id: String,
+    pub transport_company_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::auth_model::DeviceInfo;
+
+/// Secret to load into an authenticator app. Enrollment completes once a
+/// code from the app is confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// A code from the authenticator app, or one of the recovery codes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+/// Shown once; only hashes are stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// Whether the account's role must use two-factor authentication.
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+/// Second login step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    pub code: String,
+    /// Recorded on the session this step opens.
+    #[serde(default)]
+    pub device: DeviceInfo,
+}
+
+/// Returned by `POST /login` when the password was right but a second
+/// factor is still needed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_at: DateTime<Utc>,
+}
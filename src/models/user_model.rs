@@ -40,7 +40,6 @@ pub struct UpdateUserRequest {
     pub phone: Option<String>,
     pub profile_picture: Option<String>,
     pub is_active: Option<bool>,
-    pub password_hash: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,11 +47,13 @@ pub struct UserResponse {
     pub id: String,
     pub email: String,
     pub username: String,
-    pub password_hash: String,
     pub first_name: String,
     pub last_name: String,
     pub phone: Option<String>,
     pub profile_picture: Option<String>,
     pub is_active: bool,
+    /// Average of the most recent ratings drivers gave this rider.
+    pub rating: Option<f32>,
+    pub rating_count: i32,
     pub created_at: DateTime<Utc>,
 }
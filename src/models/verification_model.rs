@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What is being verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationChannel {
+    /// Confirmed by following a link sent to the address.
+    Email,
+    /// Confirmed by entering a code sent by SMS.
+    Phone,
+}
+
+impl VerificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationChannel::Email => "email",
+            VerificationChannel::Phone => "phone",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmEmailQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmPhoneRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationSent {
+    pub channel: VerificationChannel,
+    pub destination: String,
+    pub expires_at: DateTime<Utc>,
+    /// Earliest time another code may be requested.
+    pub resend_after: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationStatus {
+    pub email: String,
+    pub email_verified: bool,
+    pub phone: Option<String>,
+    pub phone_verified: bool,
+    /// Both verified: riders may book and drivers may go online.
+    pub complete: bool,
+}
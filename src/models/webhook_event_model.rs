@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AppError;
+
+// ============================================================================
+// Webhook Event Status
+// ============================================================================
+
+/// What became of a stored webhook delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventStatus {
+    /// Stored and being applied.
+    Received,
+    /// Applied to a payment or refund.
+    Processed,
+    /// Authentic but changed nothing: an event we do not act on, or one the
+    /// payment or refund has already moved past.
+    Ignored,
+    /// Applying it failed. A redelivery by the gateway or a replay retries it.
+    Failed,
+    /// The signature did not check out; it is kept for audits only.
+    Rejected,
+}
+
+impl WebhookEventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventStatus::Received => "received",
+            WebhookEventStatus::Processed => "processed",
+            WebhookEventStatus::Ignored => "ignored",
+            WebhookEventStatus::Failed => "failed",
+            WebhookEventStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<WebhookEventStatus, AppError> {
+        match value {
+            "received" => Ok(WebhookEventStatus::Received),
+            "processed" => Ok(WebhookEventStatus::Processed),
+            "ignored" => Ok(WebhookEventStatus::Ignored),
+            "failed" => Ok(WebhookEventStatus::Failed),
+            "rejected" => Ok(WebhookEventStatus::Rejected),
+            other => Err(AppError::Internal(format!(
+                "Unknown webhook event status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookEventQuery {
+    pub gateway: Option<String>,
+    pub status: Option<WebhookEventStatus>,
+    /// The payment reference the events name.
+    pub reference: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// One stored webhook delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEventResponse {
+    pub id: String,
+    pub gateway: String,
+    /// Deduplication key; unset when the delivery could not be read.
+    pub event_id: Option<String>,
+    pub event: Option<String>,
+    pub reference: Option<String>,
+    pub signature_valid: bool,
+    pub status: WebhookEventStatus,
+    pub error: Option<String>,
+    /// Deliveries and replays that were applied, including the first.
+    pub attempts: i32,
+    /// `None` when the body was not JSON.
+    pub payload: Option<Value>,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,29 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission};
+use crate::handlers::admin_handlers::{
+    list_account_sessions, list_login_attempts, revoke_account_session,
+    revoke_all_account_sessions, unlock_account,
+};
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post},
+};
+
+pub fn admin_routes() -> Router {
+    Router::new()
+        .route("/accounts/unlock", post(unlock_account))
+        .route("/login-attempts", get(list_login_attempts))
+        .route(
+            "/accounts/{account_id}/sessions",
+            get(list_account_sessions).delete(revoke_all_account_sessions),
+        )
+        .route(
+            "/accounts/{account_id}/sessions/{session_id}",
+            delete(revoke_account_session),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            Permission::ManageUsers,
+            require_permission,
+        ))
+        .layer(middleware::from_fn(auth_middleware))
+}
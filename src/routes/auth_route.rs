@@ -0,0 +1,46 @@
+use crate::auth::middleware::auth_middleware;
+use crate::handlers::auth_handlers::{forgot_password, logout, refresh_token, reset_password};
+use crate::handlers::session_handlers::{
+    list_sessions, register_push_token, remove_push_token, revoke_own_session,
+};
+use crate::handlers::two_factor_handlers::{
+    activate_two_factor, disable_two_factor, enroll_two_factor, regenerate_recovery_codes,
+    two_factor_status,
+};
+use crate::handlers::verification_handlers::{
+    confirm_email, confirm_phone, send_email_verification, send_phone_verification,
+    verification_status,
+};
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post, put},
+};
+
+pub fn auth_routes() -> Router {
+    let authenticated = Router::new()
+        .route("/logout", post(logout))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}", delete(revoke_own_session))
+        .route(
+            "/sessions/current/push-token",
+            put(register_push_token).delete(remove_push_token),
+        )
+        .route("/verification", get(verification_status))
+        .route("/verification/email/send", post(send_email_verification))
+        .route("/verification/phone/send", post(send_phone_verification))
+        .route("/verification/phone/confirm", post(confirm_phone))
+        .route("/2fa", get(two_factor_status))
+        .route("/2fa/enroll", post(enroll_two_factor))
+        .route("/2fa/activate", post(activate_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route("/2fa/recovery-codes", post(regenerate_recovery_codes))
+        .layer(middleware::from_fn(auth_middleware));
+
+    // The email link is opened from a mail client, without a token
+    Router::new()
+        .route("/refresh", post(refresh_token))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
+        .route("/verification/email/confirm", get(confirm_email))
+        .merge(authenticated)
+}
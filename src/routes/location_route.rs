@@ -0,0 +1,21 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission};
+use crate::handlers::location_handlers::{get_driver_location, get_ride_route, push_location};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub fn location_routes() -> Router {
+    Router::new()
+        .route(
+            "/",
+            post(push_location).route_layer(middleware::from_fn_with_state(
+                Permission::DriveRides,
+                require_permission,
+            )),
+        )
+        .route("/drivers/{driver_id}", get(get_driver_location))
+        .route("/rides/{ride_id}", get(get_ride_route))
+        .layer(middleware::from_fn(auth_middleware))
+}
@@ -1,6 +1,8 @@
-use crate::handlers::login_handler::login_handler;
+use crate::handlers::login_handler::{login_handler, login_two_factor_handler};
 use axum::{Router, routing::post};
 
 pub fn login_route() -> Router {
-    Router::new().route("/", post(login_handler))
+    Router::new()
+        .route("/", post(login_handler))
+        .route("/2fa", post(login_two_factor_handler))
 }
@@ -1,5 +1,12 @@
-
+pub mod admin_route;
+pub mod auth_route;
+pub mod location_route;
+pub mod login_route;
 pub mod payment_route;
+pub mod pricing_route;
 pub mod protected_route;
+pub mod realtime_route;
+pub mod ride_route;
 pub mod transport_company_route;
 pub mod user_route;
+pub mod well_known_route;
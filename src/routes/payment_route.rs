@@ -1,20 +1,70 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission, require_self_or_permission};
 use crate::handlers::payment_handlers::{
-    health_check, initialize_payment, initialize_payment_redirect, list_all_payments,
-    list_driver_payments, list_user_payments, paystack_webhook, verify_payment,
+    create_refund, gateway_webhook, health_check, initialize_payment, initialize_payment_redirect,
+    list_all_payments, list_driver_payments, list_refunds, list_user_payments, list_webhook_events,
+    payment_webhook, replay_webhook_event, verify_payment,
 };
 use axum::{
-    Router,
+    Router, middleware,
     routing::{get, post},
 };
 
 pub fn payment_routes() -> Router {
-    Router::new()
-        .route("/health", get(health_check))
-        .route("/", get(list_all_payments))
+    let authenticated = Router::new()
+        .route(
+            "/",
+            get(list_all_payments).route_layer(middleware::from_fn_with_state(
+                Permission::ManagePayments,
+                require_permission,
+            )),
+        )
         .route("/initialize", post(initialize_payment))
         .route("/initialize/redirect", post(initialize_payment_redirect))
         .route("/verify/{reference}", get(verify_payment))
-        .route("/webhook", post(paystack_webhook))
-        .route("/user/{user_id}", get(list_user_payments))
-        .route("/driver/{driver_id}", get(list_driver_payments))
+        .route(
+            "/user/{user_id}",
+            get(list_user_payments).route_layer(middleware::from_fn_with_state(
+                Permission::ManagePayments,
+                require_self_or_permission,
+            )),
+        )
+        .route(
+            "/driver/{driver_id}",
+            get(list_driver_payments).route_layer(middleware::from_fn_with_state(
+                Permission::ManagePayments,
+                require_self_or_permission,
+            )),
+        )
+        .route(
+            "/{reference}/refunds",
+            post(create_refund)
+                .get(list_refunds)
+                .route_layer(middleware::from_fn_with_state(
+                    Permission::ManagePayments,
+                    require_permission,
+                )),
+        )
+        .route(
+            "/webhook-events",
+            get(list_webhook_events).route_layer(middleware::from_fn_with_state(
+                Permission::ManagePayments,
+                require_permission,
+            )),
+        )
+        .route(
+            "/webhook-events/{id}/replay",
+            post(replay_webhook_event).route_layer(middleware::from_fn_with_state(
+                Permission::ManagePayments,
+                require_permission,
+            )),
+        )
+        .layer(middleware::from_fn(auth_middleware));
+
+    // The gateway calls the webhook itself; it is authenticated by signature
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/webhook", post(payment_webhook))
+        .route("/webhook/{gateway}", post(gateway_webhook))
+        .merge(authenticated)
 }
@@ -0,0 +1,23 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission};
+use crate::handlers::pricing_handlers::{
+    create_pricing_rule, delete_pricing_rule, get_pricing_rule, list_pricing_rules,
+    update_pricing_rule,
+};
+use axum::{Router, middleware, routing::get};
+
+pub fn pricing_routes() -> Router {
+    Router::new()
+        .route("/rules", get(list_pricing_rules).post(create_pricing_rule))
+        .route(
+            "/rules/{id}",
+            get(get_pricing_rule)
+                .put(update_pricing_rule)
+                .delete(delete_pricing_rule),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            Permission::ManagePricing,
+            require_permission,
+        ))
+        .layer(middleware::from_fn(auth_middleware))
+}
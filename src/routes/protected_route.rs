@@ -1,7 +1,11 @@
 use axum::{Extension, Router, middleware, response::IntoResponse, routing::get};
 use tracing::info;
 
-use crate::auth::{middleware::auth_middleware, password_utils::Claims};
+use crate::auth::{
+    middleware::auth_middleware,
+    password_utils::Claims,
+    permissions::{Permission, require_permission},
+};
 
 // Protected handler - users can access their own profile
 pub async fn get_profile(Extension(claims): Extension<Claims>) -> impl IntoResponse {
@@ -9,14 +13,9 @@ pub async fn get_profile(Extension(claims): Extension<Claims>) -> impl IntoRespo
     format!("Profile for user: {}, Role: {}", claims.sub, claims.role)
 }
 
-// Protected handler - only admins can access
+// Protected handler - only platform admins can access
 pub async fn admin_dashboard(Extension(claims): Extension<Claims>) -> impl IntoResponse {
     info!(user_id = %claims.sub, role = %claims.role, "Admin accessing dashboard");
-
-    if claims.role != "admin" {
-        return "Access denied. Admin role required.".to_string();
-    }
-
     format!("Welcome to admin dashboard, {}", claims.sub)
 }
 
@@ -30,6 +29,12 @@ pub fn protected_routes() -> Router {
     Router::new()
         .route("/profile", get(get_profile))
         .route("/settings", get(get_settings))
-        .route("/admin/dashboard", get(admin_dashboard))
+        .route(
+            "/admin/dashboard",
+            get(admin_dashboard).route_layer(middleware::from_fn_with_state(
+                Permission::ViewAdminDashboard,
+                require_permission,
+            )),
+        )
         .layer(middleware::from_fn(auth_middleware))
 }
@@ -0,0 +1,17 @@
+use crate::auth::middleware::ws_auth_middleware;
+use crate::auth::permissions::{Permission, require_permission};
+use crate::handlers::realtime_handlers::{offers_socket, ride_socket};
+use axum::{Router, middleware, routing::get};
+
+pub fn realtime_routes() -> Router {
+    Router::new()
+        .route("/rides/{ride_id}", get(ride_socket))
+        .route(
+            "/offers",
+            get(offers_socket).route_layer(middleware::from_fn_with_state(
+                Permission::DriveRides,
+                require_permission,
+            )),
+        )
+        .layer(middleware::from_fn(ws_auth_middleware))
+}
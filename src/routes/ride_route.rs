@@ -0,0 +1,44 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission};
+use crate::handlers::ride_handlers::{
+    accept_offer, accept_ride, arrive_ride, cancel_ride, complete_ride, create_ride, decline_offer,
+    dispatch_ride, estimate_fare, get_ride, get_ride_rating, get_ride_receipt, list_offers,
+    list_rides, list_scheduled_rides, rate_ride, ride_history, start_ride,
+};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
+
+pub fn ride_routes() -> Router {
+    let riders = || middleware::from_fn_with_state(Permission::RequestRides, require_permission);
+    let drivers = || middleware::from_fn_with_state(Permission::DriveRides, require_permission);
+
+    Router::new()
+        .route(
+            "/",
+            get(list_rides).merge(post(create_ride).route_layer(riders())),
+        )
+        .route("/estimate", post(estimate_fare))
+        .route("/scheduled", get(list_scheduled_rides))
+        .route("/history", get(ride_history))
+        .route("/offers", get(list_offers).route_layer(drivers()))
+        .route(
+            "/offers/{offer_id}/accept",
+            post(accept_offer).route_layer(drivers()),
+        )
+        .route(
+            "/offers/{offer_id}/decline",
+            post(decline_offer).route_layer(drivers()),
+        )
+        .route("/{id}", get(get_ride))
+        .route("/{id}/dispatch", post(dispatch_ride))
+        .route("/{id}/accept", post(accept_ride).route_layer(drivers()))
+        .route("/{id}/arrive", post(arrive_ride).route_layer(drivers()))
+        .route("/{id}/start", post(start_ride).route_layer(drivers()))
+        .route("/{id}/complete", post(complete_ride).route_layer(drivers()))
+        .route("/{id}/cancel", post(cancel_ride))
+        .route("/{id}/receipt", get(get_ride_receipt))
+        .route("/{id}/rating", get(get_ride_rating).post(rate_ride))
+        .layer(middleware::from_fn(auth_middleware))
+}
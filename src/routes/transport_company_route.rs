@@ -1,44 +1,79 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{
+    CompanyResource, Permission, require_company_access, require_permission,
+};
 use crate::handlers::transport_company_handlers::{
-    create_driver, create_transport_company, create_vehicle, delete_driver,
+    create_company_admin, create_driver, create_transport_company, create_vehicle, delete_driver,
     delete_transport_company, delete_vehicle, get_driver, get_transport_company, get_vehicle,
-    list_drivers, list_transport_companies, list_vehicles, patch_driver, patch_transport_company,
-    patch_vehicle, update_driver, update_transport_company, update_vehicle,
+    list_company_admins, list_drivers, list_transport_companies, list_vehicles, patch_driver,
+    patch_transport_company, patch_vehicle, update_driver, update_driver_availability,
+    update_transport_company, update_vehicle,
+};
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post, put},
 };
-use axum::{Router, routing::get};
 
 pub fn transport_company_routes() -> Router {
+    let manage_companies =
+        || middleware::from_fn_with_state(Permission::ManageCompanies, require_permission);
+    let company =
+        || middleware::from_fn_with_state(CompanyResource::Company, require_company_access);
+    let vehicle =
+        || middleware::from_fn_with_state(CompanyResource::Vehicle, require_company_access);
+    let driver = || middleware::from_fn_with_state(CompanyResource::Driver, require_company_access);
+
     Router::new()
         .route(
             "/",
-            get(list_transport_companies).post(create_transport_company),
+            get(list_transport_companies)
+                .merge(post(create_transport_company).route_layer(manage_companies())),
         )
         .route(
             "/{id}",
             get(get_transport_company)
-                .put(update_transport_company)
-                .patch(patch_transport_company)
-                .delete(delete_transport_company),
+                .merge(
+                    put(update_transport_company)
+                        .patch(patch_transport_company)
+                        .route_layer(company()),
+                )
+                .merge(delete(delete_transport_company).route_layer(manage_companies())),
         )
         .route(
             "/{transport_company_id}/vehicles",
-            get(list_vehicles).post(create_vehicle),
+            get(list_vehicles)
+                .post(create_vehicle)
+                .route_layer(company()),
         )
         .route(
             "/vehicles/{id}",
             get(get_vehicle)
                 .put(update_vehicle)
                 .patch(patch_vehicle)
-                .delete(delete_vehicle),
+                .delete(delete_vehicle)
+                .route_layer(vehicle()),
         )
         .route(
             "/{transport_company_id}/drivers",
-            get(list_drivers).post(create_driver),
+            get(list_drivers).post(create_driver).route_layer(company()),
+        )
+        .route(
+            "/{transport_company_id}/admins",
+            get(list_company_admins)
+                .post(create_company_admin)
+                .route_layer(company()),
         )
         .route(
             "/drivers/{id}",
             get(get_driver)
                 .put(update_driver)
                 .patch(patch_driver)
-                .delete(delete_driver),
+                .delete(delete_driver)
+                .route_layer(driver()),
+        )
+        .route(
+            "/drivers/{id}/availability",
+            put(update_driver_availability),
         )
+        .layer(middleware::from_fn(auth_middleware))
 }
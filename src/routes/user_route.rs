@@ -1,16 +1,37 @@
+use crate::auth::middleware::auth_middleware;
+use crate::auth::permissions::{Permission, require_permission, require_self_or_permission};
 use crate::handlers::user_handlers::{
     create_user, delete_user, get_user, list_users, patch_user, update_user,
 };
-use axum::{Router, routing::get};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 
 pub fn user_routes() -> Router {
-    Router::new()
-        .route("/", get(list_users).post(create_user))
+    let authenticated = Router::new()
+        .route(
+            "/",
+            get(list_users).route_layer(middleware::from_fn_with_state(
+                Permission::ManageUsers,
+                require_permission,
+            )),
+        )
         .route(
             "/{id}",
             get(get_user)
                 .put(update_user)
                 .patch(patch_user)
-                .delete(delete_user),
+                .delete(delete_user)
+                .route_layer(middleware::from_fn_with_state(
+                    Permission::ManageUsers,
+                    require_self_or_permission,
+                )),
         )
+        .layer(middleware::from_fn(auth_middleware));
+
+    // Sign-up stays public
+    Router::new()
+        .route("/", post(create_user))
+        .merge(authenticated)
 }
@@ -0,0 +1,6 @@
+use crate::handlers::auth_handlers::jwks_document;
+use axum::{Router, routing::get};
+
+pub fn well_known_routes() -> Router {
+    Router::new().route("/jwks.json", get(jwks_document))
+}
@@ -0,0 +1,402 @@
+use crate::auth::client_info::ClientInfo;
+use crate::auth::password_utils::{Claims, access_token_ttl, create_token};
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::auth_model::{DeviceInfo, DevicePlatform, TokenPair};
+use crate::models::login_model::AccountType;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    /// How long a session survives without being refreshed.
+    pub refresh_ttl_days: i64,
+}
+
+impl SessionConfig {
+    pub fn from_env() -> Self {
+        SessionConfig {
+            refresh_ttl_days: env_or("REFRESH_TOKEN_TTL_DAYS", 30),
+        }
+    }
+}
+
+/// The account a session belongs to.
+#[derive(Debug, Clone)]
+pub struct SessionAccount {
+    pub account_id: String,
+    pub account_type: AccountType,
+    pub role: String,
+    pub transport_company_id: Option<String>,
+    /// Signed in with a second factor.
+    pub two_factor: bool,
+}
+
+/// The device a session is opened on.
+#[derive(Debug, Clone, Default)]
+pub struct SessionDevice {
+    pub device_name: Option<String>,
+    pub platform: Option<DevicePlatform>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl SessionDevice {
+    pub fn new(device: DeviceInfo, client: ClientInfo) -> Self {
+        SessionDevice {
+            device_name: device
+                .device_name
+                .map(|name| {
+                    name.trim()
+                        .chars()
+                        .take(MAX_DEVICE_NAME_LEN)
+                        .collect::<String>()
+                })
+                .filter(|name| !name.is_empty()),
+            platform: device.platform,
+            ip_address: client.ip_address,
+            user_agent: client.user_agent,
+        }
+    }
+}
+
+const MAX_DEVICE_NAME_LEN: usize = 100;
+
+// ============================================================================
+// Session Services
+// ============================================================================
+
+/// Opens a session for a freshly authenticated account and issues its first
+/// access and refresh tokens.
+pub async fn start_session(
+    pool: &PgPool,
+    account: &SessionAccount,
+    device: &SessionDevice,
+) -> Result<TokenPair, AppError> {
+    let config = SessionConfig::from_env();
+    let now = Utc::now();
+    let session_id = Uuid::new_v4().to_string();
+    let expires_at = now + Duration::days(config.refresh_ttl_days);
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO auth_sessions \
+        (id, account_id, account_type, role, transport_company_id, two_factor, created_at, last_used_at, expires_at, \
+        device_name, platform, ip_address, user_agent) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$7,$8,$9,$10,$11,$12)",
+    )
+    .bind(&session_id)
+    .bind(&account.account_id)
+    .bind(account.account_type.as_str())
+    .bind(&account.role)
+    .bind(&account.transport_company_id)
+    .bind(account.two_factor)
+    .bind(now)
+    .bind(expires_at)
+    .bind(&device.device_name)
+    .bind(device.platform.map(|platform| platform.as_str()))
+    .bind(&device.ip_address)
+    .bind(&device.user_agent)
+    .execute(&mut *tx)
+    .await?;
+
+    let tokens = issue_tokens(&mut tx, &session_id, account, &config).await?;
+
+    tx.commit().await?;
+
+    info!(session_id = %session_id, account_id = %account.account_id, "Session started");
+
+    Ok(tokens)
+}
+
+/// Exchanges a refresh token for a new token pair. Every refresh token is
+/// single-use: presenting one that was already used means it leaked, so the
+/// whole session is revoked. The session's last-seen time and address are
+/// updated.
+pub async fn refresh_session_service(
+    pool: &PgPool,
+    refresh_token: String,
+    client: &ClientInfo,
+) -> Result<TokenPair, AppError> {
+    let config = SessionConfig::from_env();
+    let now = Utc::now();
+    let token_hash = hash_token(&refresh_token);
+
+    let mut tx = pool.begin().await?;
+
+    let claimed = sqlx::query(
+        "UPDATE refresh_tokens SET used_at = $2 \
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2 \
+        RETURNING session_id",
+    )
+    .bind(&token_hash)
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(claimed) = claimed else {
+        drop(tx);
+        return Err(AppError::Unauthorized(
+            reject_refresh_token(pool, &token_hash).await,
+        ));
+    };
+    let session_id: String = claimed.get("session_id");
+
+    let session = sqlx::query(
+        "UPDATE auth_sessions SET last_used_at = $2, expires_at = $3, \
+        ip_address = COALESCE($4, ip_address), user_agent = COALESCE($5, user_agent) \
+        WHERE id = $1 AND revoked_at IS NULL AND expires_at > $2 \
+        RETURNING account_id, account_type, role, transport_company_id, two_factor",
+    )
+    .bind(&session_id)
+    .bind(now)
+    .bind(now + Duration::days(config.refresh_ttl_days))
+    .bind(&client.ip_address)
+    .bind(&client.user_agent)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        debug!(session_id = %session_id, "Refresh attempted on closed session");
+        AppError::Unauthorized("Session has ended, please log in again".to_string())
+    })?;
+
+    let account = SessionAccount {
+        account_id: session.get("account_id"),
+        account_type: AccountType::parse(session.get("account_type"))?,
+        role: session.get("role"),
+        transport_company_id: session.get("transport_company_id"),
+        two_factor: session.get("two_factor"),
+    };
+
+    // The account may have been deleted, disabled or given another role
+    // since the session started; such a session ends here.
+    if let Some(reason) = account_change(&mut tx, &account).await? {
+        sqlx::query(
+            "UPDATE auth_sessions SET revoked_at = $2, revoked_reason = $3, push_token = NULL \
+            WHERE id = $1",
+        )
+        .bind(&session_id)
+        .bind(now)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        warn!(session_id = %session_id, account_id = %account.account_id, reason, "Refresh refused, session revoked");
+        return Err(AppError::Unauthorized(
+            "Session has ended, please log in again".to_string(),
+        ));
+    }
+
+    let tokens = issue_tokens(&mut tx, &session_id, &account, &config).await?;
+
+    tx.commit().await?;
+
+    debug!(session_id = %session_id, "Session refreshed");
+
+    Ok(tokens)
+}
+
+/// Ends the caller's session, or every session of the account.
+pub async fn logout_service(
+    pool: &PgPool,
+    claims: &Claims,
+    all_devices: bool,
+) -> Result<u64, AppError> {
+    let revoked = if all_devices {
+        revoke_account_sessions(pool, &claims.sub, "logout_all").await?
+    } else {
+        revoke_session(pool, &claims.sid, "logout").await?
+    };
+
+    info!(account_id = %claims.sub, session_id = %claims.sid, all_devices, revoked, "Logged out");
+
+    Ok(revoked)
+}
+
+/// Whether access tokens of a session are still honoured.
+pub async fn is_session_active(pool: &PgPool, session_id: &str) -> Result<bool, AppError> {
+    let row = sqlx::query(
+        "SELECT 1 FROM auth_sessions WHERE id = $1 AND revoked_at IS NULL AND expires_at > $2",
+    )
+    .bind(session_id)
+    .bind(Utc::now())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Closes a session. Its push token is dropped so the device stops getting
+/// notifications.
+pub async fn revoke_session(
+    pool: &PgPool,
+    session_id: &str,
+    reason: &str,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        "UPDATE auth_sessions SET revoked_at = $2, revoked_reason = $3, push_token = NULL \
+        WHERE id = $1 AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(Utc::now())
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revokes every open session of an account, e.g. after a password change.
+pub async fn revoke_account_sessions(
+    pool: &PgPool,
+    account_id: &str,
+    reason: &str,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        "UPDATE auth_sessions SET revoked_at = $2, revoked_reason = $3, push_token = NULL \
+        WHERE account_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(account_id)
+    .bind(Utc::now())
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Current state of each account type, checked the way login checks it.
+const RIDER_STATE_QUERY: &str = "SELECT role, NULL::TEXT AS transport_company_id, is_active \
+    FROM users WHERE id = $1";
+const DRIVER_STATE_QUERY: &str = "SELECT d.role, d.transport_company_id, c.is_active \
+    FROM transport_company_drivers d \
+    JOIN transport_companies c ON c.id = d.transport_company_id \
+    WHERE d.id = $1";
+const COMPANY_ADMIN_STATE_QUERY: &str = "SELECT a.role, a.transport_company_id, \
+    (a.is_active AND c.is_active) AS is_active \
+    FROM transport_company_admins a \
+    JOIN transport_companies c ON c.id = a.transport_company_id \
+    WHERE a.id = $1";
+
+/// Why a session's account no longer matches it, if it doesn't.
+async fn account_change(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account: &SessionAccount,
+) -> Result<Option<&'static str>, AppError> {
+    let query = match account.account_type {
+        AccountType::Rider => RIDER_STATE_QUERY,
+        AccountType::Driver => DRIVER_STATE_QUERY,
+        AccountType::CompanyAdmin => COMPANY_ADMIN_STATE_QUERY,
+    };
+    let Some(row) = sqlx::query(query)
+        .bind(&account.account_id)
+        .fetch_optional(&mut **tx)
+        .await?
+    else {
+        return Ok(Some("account_deleted"));
+    };
+
+    if !row.get::<bool, _>("is_active") {
+        return Ok(Some("account_disabled"));
+    }
+    if row.get::<String, _>("role") != account.role
+        || row.get::<Option<String>, _>("transport_company_id") != account.transport_company_id
+    {
+        return Ok(Some("account_changed"));
+    }
+
+    Ok(None)
+}
+
+async fn issue_tokens(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: &str,
+    account: &SessionAccount,
+    config: &SessionConfig,
+) -> Result<TokenPair, AppError> {
+    let now = Utc::now();
+    let access_ttl = access_token_ttl();
+    let refresh_token = generate_token();
+    let refresh_expires_at = now + Duration::days(config.refresh_ttl_days);
+
+    sqlx::query(
+        "INSERT INTO refresh_tokens (id, session_id, token_hash, created_at, expires_at) \
+        VALUES ($1,$2,$3,$4,$5)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(session_id)
+    .bind(hash_token(&refresh_token))
+    .bind(now)
+    .bind(refresh_expires_at)
+    .execute(&mut **tx)
+    .await?;
+
+    let token = create_token(
+        &account.account_id,
+        &account.role,
+        account.transport_company_id.as_deref(),
+        session_id,
+        account.two_factor,
+        now + access_ttl,
+    )
+    .map_err(|e| AppError::Internal(format!("Failed to create token: {}", e)))?;
+
+    Ok(TokenPair {
+        token,
+        expires_in: access_ttl.num_seconds(),
+        refresh_token,
+        refresh_expires_at,
+    })
+}
+
+/// Works out why a refresh token was refused. A token that exists but was
+/// already used has been replayed, so its session is revoked.
+async fn reject_refresh_token(pool: &PgPool, token_hash: &str) -> String {
+    let row = sqlx::query("SELECT session_id, used_at FROM refresh_tokens WHERE token_hash = $1")
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await;
+
+    match row {
+        Ok(Some(row))
+            if row
+                .get::<Option<chrono::DateTime<Utc>>, _>("used_at")
+                .is_some() =>
+        {
+            let session_id: String = row.get("session_id");
+            warn!(session_id = %session_id, "Refresh token reused, revoking session");
+            if let Err(e) = revoke_session(pool, &session_id, "refresh_token_reuse").await {
+                error!(session_id = %session_id, error = %e, "Failed to revoke session");
+            }
+            "Refresh token has already been used; session revoked".to_string()
+        }
+        Ok(_) => "Invalid or expired refresh token".to_string(),
+        Err(e) => {
+            error!(error = %e, "Failed to look up refresh token");
+            "Invalid or expired refresh token".to_string()
+        }
+    }
+}
+
+/// 256-bit random token, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Refresh and password reset tokens are stored as SHA-256 digests so a
+/// database leak does not hand out live credentials.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
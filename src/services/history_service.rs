@@ -0,0 +1,403 @@
+use crate::error::AppError;
+use std::collections::HashMap;
+
+use crate::models::history_model::{
+    ReceiptLineItem, RideHistoryItem, RideHistoryPage, RideHistoryQuery, RidePaymentSummary,
+    RideReceipt,
+};
+use crate::models::money_model::Money;
+use crate::models::ride_model::{RideResponse, RideStatus};
+use crate::services::location_service::get_ride_route_service;
+use crate::services::ride_service::{get_ride_service, list_rides_page_service};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error};
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+// ============================================================================
+// Ride History Services
+// ============================================================================
+
+/// Paginated trips of the authenticated rider, or of the authenticated driver
+/// when `as_driver` is set, with payment status and the user's own rating.
+pub async fn ride_history_service(
+    pool: &PgPool,
+    user_id: String,
+    as_driver: bool,
+    query: RideHistoryQuery,
+) -> Result<RideHistoryPage, AppError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    debug!(user_id = %user_id, as_driver, page, per_page, "Fetching ride history");
+
+    let (rides, total) = list_rides_page_service(
+        pool,
+        &user_id,
+        as_driver,
+        query.status,
+        per_page,
+        (page - 1) * per_page,
+    )
+    .await?;
+
+    let ride_ids: Vec<String> = rides.iter().map(|ride| ride.id.clone()).collect();
+    let mut payments = ride_payments(pool, &ride_ids).await?;
+    let mut ratings = ratings_given(pool, &ride_ids, &user_id).await?;
+
+    let items = rides
+        .into_iter()
+        .map(|ride| RideHistoryItem {
+            payment: payments.remove(&ride.id),
+            rating_given: ratings.remove(&ride.id),
+            ride,
+        })
+        .collect();
+
+    Ok(RideHistoryPage {
+        items,
+        page,
+        per_page,
+        total,
+    })
+}
+
+// ============================================================================
+// Receipt Services
+// ============================================================================
+
+/// Itemised receipt for a completed ride, or for the fee of a cancelled one.
+/// Only the ride's rider and driver can fetch it.
+pub async fn ride_receipt_service(
+    pool: &PgPool,
+    ride_id: String,
+    user_id: String,
+) -> Result<RideReceipt, AppError> {
+    debug!(ride_id = %ride_id, user_id = %user_id, "Building ride receipt");
+
+    let ride = get_ride_service(pool, ride_id.clone()).await?;
+
+    if ride.rider_id != user_id && ride.driver_id.as_deref() != Some(user_id.as_str()) {
+        error!(ride_id = %ride_id, user_id = %user_id, "User is not a participant of the ride");
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let (line_items, total) = receipt_lines(&ride)?;
+
+    let rider_name = sqlx::query("SELECT first_name, last_name FROM users WHERE id = $1")
+        .bind(&ride.rider_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| full_name(&row))
+        .unwrap_or_default();
+
+    let driver_name = match ride.driver_id.as_deref() {
+        Some(driver_id) => {
+            sqlx::query("SELECT first_name, last_name FROM transport_company_drivers WHERE id = $1")
+                .bind(driver_id)
+                .fetch_optional(pool)
+                .await?
+                .map(|row| full_name(&row))
+        }
+        None => None,
+    };
+
+    let payment = ride_payments(pool, std::slice::from_ref(&ride.id))
+        .await?
+        .remove(&ride.id);
+    let route = get_ride_route_service(pool, ride.id.clone()).await?;
+    let breakdown = ride.fare_breakdown.as_ref();
+
+    Ok(RideReceipt {
+        receipt_number: receipt_number(&ride),
+        ride_id: ride.id.clone(),
+        status: ride.status,
+        issued_at: issued_at(&ride),
+        rider_name,
+        driver_name,
+        vehicle_type: ride.vehicle_type.clone(),
+        pickup_address: ride.pickup_address.clone(),
+        pickup_lat: ride.pickup_lat,
+        pickup_lng: ride.pickup_lng,
+        dropoff_address: ride.dropoff_address.clone(),
+        dropoff_lat: ride.dropoff_lat,
+        dropoff_lng: ride.dropoff_lng,
+        requested_at: ride.requested_at,
+        started_at: ride.started_at,
+        completed_at: ride.completed_at,
+        cancelled_at: ride.cancelled_at,
+        distance_km: breakdown.map(|breakdown| breakdown.distance_km),
+        duration_seconds: breakdown.map(|breakdown| breakdown.duration_seconds),
+        currency: ride
+            .currency
+            .clone()
+            .or_else(|| breakdown.map(|breakdown| breakdown.currency.clone()))
+            .unwrap_or_else(|| "NGN".to_string()),
+        line_items,
+        total,
+        payment,
+        route,
+    })
+}
+
+/// Renders a receipt as a standalone HTML document for download or printing.
+pub fn render_receipt_html(receipt: &RideReceipt) -> String {
+    let money = |amount: i64| format_amount(amount, &receipt.currency);
+    let timestamp = |value: Option<chrono::DateTime<Utc>>| {
+        value
+            .map(|value| value.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let place = |address: &Option<String>, lat: f64, lng: f64| {
+        address
+            .clone()
+            .unwrap_or_else(|| format!("{:.5}, {:.5}", lat, lng))
+    };
+
+    let lines: String = receipt
+        .line_items
+        .iter()
+        .map(|item| {
+            format!(
+                "<tr><td>{}</td><td class=\"amount\">{}</td></tr>",
+                escape_html(&item.label),
+                money(item.amount)
+            )
+        })
+        .collect();
+
+    let payment = match &receipt.payment {
+        Some(payment) => format!(
+            "<p>Payment {} &middot; reference {}</p>",
+            escape_html(&payment.status),
+            escape_html(&payment.reference)
+        ),
+        None => "<p>No payment recorded</p>".to_string(),
+    };
+
+    let trip = match (receipt.distance_km, receipt.duration_seconds) {
+        (Some(distance_km), Some(duration_seconds)) => format!(
+            "<p>{:.2} km &middot; {} min</p>",
+            distance_km,
+            (duration_seconds + 59) / 60
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html lang=\"en\">\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <title>RideNow receipt {number}</title>\n\
+        <style>\n\
+        body {{ font-family: sans-serif; max-width: 36rem; margin: 2rem auto; color: #222; }}\n\
+        table {{ width: 100%; border-collapse: collapse; }}\n\
+        td {{ padding: 0.25rem 0; border-bottom: 1px solid #eee; }}\n\
+        .amount {{ text-align: right; }}\n\
+        .total td {{ font-weight: bold; border-bottom: none; }}\n\
+        </style>\n\
+        </head>\n\
+        <body>\n\
+        <h1>RideNow receipt</h1>\n\
+        <p>Receipt {number} &middot; issued {issued}</p>\n\
+        <p>Rider: {rider}<br>Driver: {driver}<br>Vehicle: {vehicle}</p>\n\
+        <p>From: {pickup}<br>To: {dropoff}</p>\n\
+        <p>Requested {requested} &middot; started {started} &middot; {ended_label} {ended}</p>\n\
+        {trip}\n\
+        <table>\n{lines}\n<tr class=\"total\"><td>Total</td><td class=\"amount\">{total}</td></tr>\n</table>\n\
+        {payment}\n\
+        </body>\n\
+        </html>\n",
+        number = escape_html(&receipt.receipt_number),
+        issued = timestamp(Some(receipt.issued_at)),
+        rider = escape_html(&receipt.rider_name),
+        driver = escape_html(receipt.driver_name.as_deref().unwrap_or("-")),
+        vehicle = escape_html(receipt.vehicle_type.as_deref().unwrap_or("-")),
+        pickup = escape_html(&place(
+            &receipt.pickup_address,
+            receipt.pickup_lat,
+            receipt.pickup_lng
+        )),
+        dropoff = escape_html(&place(
+            &receipt.dropoff_address,
+            receipt.dropoff_lat,
+            receipt.dropoff_lng
+        )),
+        requested = timestamp(Some(receipt.requested_at)),
+        started = timestamp(receipt.started_at),
+        ended_label = if receipt.status == RideStatus::Cancelled {
+            "cancelled"
+        } else {
+            "completed"
+        },
+        ended = timestamp(receipt.completed_at.or(receipt.cancelled_at)),
+        trip = trip,
+        lines = lines,
+        total = money(receipt.total),
+        payment = payment,
+    )
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Line items and total of what the ride charged. Rides that ended without a
+/// charge have no receipt.
+fn receipt_lines(ride: &RideResponse) -> Result<(Vec<ReceiptLineItem>, i64), AppError> {
+    let line = |label: String, amount: i64| ReceiptLineItem { label, amount };
+
+    match (
+        ride.status,
+        ride.fare_breakdown.as_ref(),
+        ride.cancellation_fee,
+    ) {
+        (RideStatus::Completed, Some(breakdown), _) => {
+            let mut lines = vec![
+                line("Base fare".to_string(), breakdown.base_fare),
+                line(
+                    format!("Distance ({:.2} km)", breakdown.distance_km),
+                    breakdown.distance_fare,
+                ),
+                line(
+                    format!("Time ({} min)", (breakdown.duration_seconds + 59) / 60),
+                    breakdown.time_fare,
+                ),
+            ];
+            if breakdown.surge_fare > 0 {
+                lines.push(line(
+                    format!("Surge (x{:.1})", breakdown.surge_multiplier),
+                    breakdown.surge_fare,
+                ));
+            }
+            if breakdown.minimum_fare_adjustment > 0 {
+                lines.push(line(
+                    "Minimum fare adjustment".to_string(),
+                    breakdown.minimum_fare_adjustment,
+                ));
+            }
+            if breakdown.booking_fee > 0 {
+                lines.push(line("Booking fee".to_string(), breakdown.booking_fee));
+            }
+            Ok((lines, ride.final_fare.unwrap_or(breakdown.total)))
+        }
+        (RideStatus::Cancelled, _, Some(fee)) if fee > 0 => {
+            Ok((vec![line("Cancellation fee".to_string(), fee)], fee))
+        }
+        _ => {
+            error!(ride_id = %ride.id, status = %ride.status.as_str(), "Ride has no charge to receipt");
+            Err(AppError::Validation(
+                "Receipts are only available for charged rides".to_string(),
+            ))
+        }
+    }
+}
+
+/// Latest payment per ride, preferring a successful one.
+async fn ride_payments(
+    pool: &PgPool,
+    ride_ids: &[String],
+) -> Result<HashMap<String, RidePaymentSummary>, AppError> {
+    if ride_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query(
+        "SELECT DISTINCT ON (ride_id) ride_id, reference, status, amount, currency, updated_at \
+        FROM user_payments WHERE ride_id = ANY($1) \
+        ORDER BY ride_id, (status = 'success') DESC, created_at DESC",
+    )
+    .bind(ride_ids)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok((
+                row.get("ride_id"),
+                RidePaymentSummary {
+                    reference: row.get("reference"),
+                    status: row.get("status"),
+                    amount: Money::stored(row.get("amount"), &row.get::<String, _>("currency"))?,
+                    updated_at: row.get("updated_at"),
+                },
+            ))
+        })
+        .collect()
+}
+
+async fn ratings_given(
+    pool: &PgPool,
+    ride_ids: &[String],
+    rater_id: &str,
+) -> Result<HashMap<String, i16>, AppError> {
+    if ride_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query(
+        "SELECT ride_id, score FROM ride_ratings WHERE ride_id = ANY($1) AND rater_id = $2",
+    )
+    .bind(ride_ids)
+    .bind(rater_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("ride_id"), row.get("score")))
+        .collect())
+}
+
+/// When the charge was incurred, so a receipt reads the same every time it
+/// is fetched.
+fn issued_at(ride: &RideResponse) -> DateTime<Utc> {
+    ride.completed_at
+        .or(ride.cancelled_at)
+        .unwrap_or(ride.requested_at)
+}
+
+/// Stable, human-friendly receipt number derived from the ride.
+fn receipt_number(ride: &RideResponse) -> String {
+    let suffix: String = ride
+        .id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(8)
+        .collect();
+    format!(
+        "RN-{}-{}",
+        ride.requested_at.format("%Y%m%d"),
+        suffix.to_uppercase()
+    )
+}
+
+fn full_name(row: &sqlx::postgres::PgRow) -> String {
+    format!(
+        "{} {}",
+        row.get::<String, _>("first_name"),
+        row.get::<String, _>("last_name")
+    )
+}
+
+/// Amount in major units with as many decimals as the currency has.
+fn format_amount(amount: i64, currency: &str) -> String {
+    match Money::stored(amount, currency) {
+        Ok(money) => escape_html(&money.to_string()),
+        Err(_) => format!("{} {}", escape_html(currency), amount),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,504 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::location_model::{
+    DriverLocationResponse, LocationHistoryPoint, LocationPing, LocationUpdateResponse,
+};
+use crate::services::matching_service::haversine_km;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, error, info};
+
+const MAX_PINGS_PER_REQUEST: usize = 100;
+/// Pings timestamped further ahead than this are treated as clock errors.
+const MAX_CLOCK_SKEW_SECONDS: i64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct LocationConfig {
+    pub flush_interval_ms: u64,
+    pub max_ping_age_seconds: i64,
+    pub history_min_interval_seconds: i64,
+    pub history_max_points_per_ride: i64,
+}
+
+impl LocationConfig {
+    pub fn from_env() -> Self {
+        LocationConfig {
+            flush_interval_ms: env_or("LOCATION_FLUSH_INTERVAL_MS", 1000),
+            max_ping_age_seconds: env_or("LOCATION_MAX_PING_AGE_SECONDS", 300),
+            history_min_interval_seconds: env_or("LOCATION_HISTORY_MIN_INTERVAL_SECONDS", 5),
+            history_max_points_per_ride: env_or("LOCATION_HISTORY_MAX_POINTS_PER_RIDE", 2000),
+        }
+    }
+}
+
+// ============================================================================
+// Location Buffer
+// ============================================================================
+
+#[derive(Default)]
+struct PendingPings {
+    /// Newest ping per driver; older ones are superseded before reaching the DB.
+    latest: HashMap<String, LocationPing>,
+    /// Thinned pings that may become part of a trip's route history.
+    history: Vec<(String, LocationPing)>,
+    last_history_at: HashMap<String, DateTime<Utc>>,
+}
+
+/// Collects driver pings in memory and writes them to Postgres in batches,
+/// so ingestion cost is one round-trip per flush instead of one per ping.
+#[derive(Clone)]
+pub struct LocationBuffer {
+    pending: Arc<Mutex<PendingPings>>,
+    config: LocationConfig,
+}
+
+impl LocationBuffer {
+    pub fn new(config: LocationConfig) -> Self {
+        LocationBuffer {
+            pending: Arc::new(Mutex::new(PendingPings::default())),
+            config,
+        }
+    }
+
+    /// Validates and queues pings for a driver, returning how many were kept
+    /// along with the newest valid ping.
+    pub fn record_pings(
+        &self,
+        driver_id: &str,
+        mut pings: Vec<LocationPing>,
+    ) -> Result<(LocationUpdateResponse, Option<LocationPing>), AppError> {
+        if pings.is_empty() {
+            return Err(AppError::Validation(
+                "At least one ping is required".to_string(),
+            ));
+        }
+        if pings.len() > MAX_PINGS_PER_REQUEST {
+            return Err(AppError::Validation(format!(
+                "At most {} pings may be sent per request",
+                MAX_PINGS_PER_REQUEST
+            )));
+        }
+
+        let now = Utc::now();
+        let total = pings.len();
+        pings.retain(|ping| is_valid_ping(ping, now, &self.config));
+        pings.sort_by_key(|ping| ping.recorded_at);
+        let accepted = pings.len();
+        let newest = pings.last().cloned();
+
+        let mut pending = self
+            .pending
+            .lock()
+            .map_err(|_| AppError::Internal("Location buffer poisoned".to_string()))?;
+        for ping in pings {
+            let last_history_at = pending.last_history_at.get(driver_id).copied();
+            if last_history_at.is_none_or(|at| {
+                ping.recorded_at - at >= Duration::seconds(self.config.history_min_interval_seconds)
+            }) {
+                pending
+                    .last_history_at
+                    .insert(driver_id.to_string(), ping.recorded_at);
+                pending.history.push((driver_id.to_string(), ping.clone()));
+            }
+
+            let is_newer = pending
+                .latest
+                .get(driver_id)
+                .is_none_or(|current| ping.recorded_at > current.recorded_at);
+            if is_newer {
+                pending.latest.insert(driver_id.to_string(), ping);
+            }
+        }
+
+        Ok((
+            LocationUpdateResponse {
+                accepted,
+                rejected: total - accepted,
+            },
+            newest,
+        ))
+    }
+
+    /// Writes everything queued since the last flush.
+    pub async fn flush(&self, pool: &PgPool) -> Result<(), AppError> {
+        let (latest, history) = {
+            let mut pending = self
+                .pending
+                .lock()
+                .map_err(|_| AppError::Internal("Location buffer poisoned".to_string()))?;
+            (
+                std::mem::take(&mut pending.latest),
+                std::mem::take(&mut pending.history),
+            )
+        };
+
+        if latest.is_empty() && history.is_empty() {
+            return Ok(());
+        }
+
+        let latest_count = latest.len();
+        let history_count = history.len();
+
+        // Put back whatever was not written so the next flush retries it.
+        if let Err(e) = upsert_latest_locations(pool, &latest).await {
+            self.restore(latest, history)?;
+            return Err(e);
+        }
+        if let Err(e) =
+            append_ride_history(pool, &history, self.config.history_max_points_per_ride).await
+        {
+            self.restore(HashMap::new(), history)?;
+            return Err(e);
+        }
+
+        debug!(
+            drivers = latest_count,
+            history_points = history_count,
+            "Flushed driver locations"
+        );
+        Ok(())
+    }
+
+    /// Returns pings taken by a failed flush to the buffer. Pings queued
+    /// since then are newer and win over the restored ones.
+    fn restore(
+        &self,
+        latest: HashMap<String, LocationPing>,
+        mut history: Vec<(String, LocationPing)>,
+    ) -> Result<(), AppError> {
+        let mut pending = self
+            .pending
+            .lock()
+            .map_err(|_| AppError::Internal("Location buffer poisoned".to_string()))?;
+
+        for (driver_id, ping) in latest {
+            let is_newer = pending
+                .latest
+                .get(&driver_id)
+                .is_none_or(|current| ping.recorded_at > current.recorded_at);
+            if is_newer {
+                pending.latest.insert(driver_id, ping);
+            }
+        }
+        history.append(&mut pending.history);
+        pending.history = history;
+
+        Ok(())
+    }
+}
+
+/// Periodically flushes the buffer for the lifetime of the process.
+pub fn spawn_location_flusher(pool: PgPool, buffer: LocationBuffer) {
+    let interval = std::time::Duration::from_millis(buffer.config.flush_interval_ms);
+    info!(
+        interval_ms = buffer.config.flush_interval_ms,
+        "Starting location flusher"
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = buffer.flush(&pool).await {
+                error!(error = %e, "Failed to flush driver locations");
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Location Query Services
+// ============================================================================
+
+pub async fn get_driver_location_service(
+    pool: &PgPool,
+    driver_id: String,
+) -> Result<DriverLocationResponse, AppError> {
+    debug!(driver_id = %driver_id, "Fetching latest driver location");
+
+    let row = sqlx::query(
+        "SELECT driver_id, lat, lng, heading, speed, accuracy, recorded_at, updated_at \
+        FROM driver_locations WHERE driver_id = $1",
+    )
+    .bind(&driver_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        error!(driver_id = %driver_id, "No location for driver");
+        AppError::NotFound("No location recorded for driver".to_string())
+    })?;
+
+    Ok(DriverLocationResponse {
+        driver_id: row.get("driver_id"),
+        lat: row.get("lat"),
+        lng: row.get("lng"),
+        heading: row.get("heading"),
+        speed: row.get("speed"),
+        accuracy: row.get("accuracy"),
+        recorded_at: row.get("recorded_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+pub async fn get_ride_route_service(
+    pool: &PgPool,
+    ride_id: String,
+) -> Result<Vec<LocationHistoryPoint>, AppError> {
+    debug!(ride_id = %ride_id, "Fetching ride location history");
+
+    let rows = sqlx::query(
+        "SELECT lat, lng, heading, speed, accuracy, recorded_at \
+        FROM ride_location_history WHERE ride_id = $1 ORDER BY recorded_at",
+    )
+    .bind(&ride_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LocationHistoryPoint {
+            lat: row.get("lat"),
+            lng: row.get("lng"),
+            heading: row.get("heading"),
+            speed: row.get("speed"),
+            accuracy: row.get("accuracy"),
+            recorded_at: row.get("recorded_at"),
+        })
+        .collect())
+}
+
+/// Whether `rider_id` is currently riding with `driver_id`.
+pub async fn is_active_rider_of_driver(
+    pool: &PgPool,
+    rider_id: &str,
+    driver_id: &str,
+) -> Result<bool, AppError> {
+    let row = sqlx::query(
+        "SELECT 1 FROM rides WHERE rider_id = $1 AND driver_id = $2 \
+        AND status IN ('accepted', 'arriving', 'in_progress') LIMIT 1",
+    )
+    .bind(rider_id)
+    .bind(driver_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn is_valid_ping(ping: &LocationPing, now: DateTime<Utc>, config: &LocationConfig) -> bool {
+    let in_range = (-90.0..=90.0).contains(&ping.lat) && (-180.0..=180.0).contains(&ping.lng);
+    let not_future = ping.recorded_at <= now + Duration::seconds(MAX_CLOCK_SKEW_SECONDS);
+    let not_stale = ping.recorded_at >= now - Duration::seconds(config.max_ping_age_seconds);
+    let sane_heading = ping.heading.is_none_or(|h| (0.0..=360.0).contains(&h));
+    let sane_speed = ping.speed.is_none_or(|s| s >= 0.0);
+    let sane_accuracy = ping.accuracy.is_none_or(|a| a >= 0.0);
+
+    in_range && not_future && not_stale && sane_heading && sane_speed && sane_accuracy
+}
+
+async fn upsert_latest_locations(
+    pool: &PgPool,
+    latest: &HashMap<String, LocationPing>,
+) -> Result<(), AppError> {
+    if latest.is_empty() {
+        return Ok(());
+    }
+
+    let mut driver_ids = Vec::with_capacity(latest.len());
+    let mut lats = Vec::with_capacity(latest.len());
+    let mut lngs = Vec::with_capacity(latest.len());
+    let mut headings = Vec::with_capacity(latest.len());
+    let mut speeds = Vec::with_capacity(latest.len());
+    let mut accuracies = Vec::with_capacity(latest.len());
+    let mut recorded = Vec::with_capacity(latest.len());
+    for (driver_id, ping) in latest {
+        driver_ids.push(driver_id.clone());
+        lats.push(ping.lat);
+        lngs.push(ping.lng);
+        headings.push(ping.heading);
+        speeds.push(ping.speed);
+        accuracies.push(ping.accuracy);
+        recorded.push(ping.recorded_at);
+    }
+
+    // Joining on drivers drops pings for ids that are not drivers instead of
+    // failing the whole batch on the foreign key.
+    sqlx::query(
+        "INSERT INTO driver_locations \
+        (driver_id, lat, lng, heading, speed, accuracy, recorded_at, updated_at) \
+        SELECT p.driver_id, p.lat, p.lng, p.heading, p.speed, p.accuracy, p.recorded_at, $8 \
+        FROM UNNEST($1::text[], $2::float8[], $3::float8[], $4::float8[], $5::float8[], $6::float8[], $7::timestamptz[]) \
+            AS p(driver_id, lat, lng, heading, speed, accuracy, recorded_at) \
+        JOIN transport_company_drivers d ON d.id = p.driver_id \
+        ON CONFLICT (driver_id) DO UPDATE SET \
+            lat = EXCLUDED.lat, \
+            lng = EXCLUDED.lng, \
+            heading = EXCLUDED.heading, \
+            speed = EXCLUDED.speed, \
+            accuracy = EXCLUDED.accuracy, \
+            recorded_at = EXCLUDED.recorded_at, \
+            updated_at = EXCLUDED.updated_at \
+        WHERE EXCLUDED.recorded_at > driver_locations.recorded_at",
+    )
+    .bind(driver_ids)
+    .bind(lats)
+    .bind(lngs)
+    .bind(headings)
+    .bind(speeds)
+    .bind(accuracies)
+    .bind(recorded)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn append_ride_history(
+    pool: &PgPool,
+    history: &[(String, LocationPing)],
+    max_points_per_ride: i64,
+) -> Result<(), AppError> {
+    if history.is_empty() {
+        return Ok(());
+    }
+
+    let mut driver_ids = Vec::with_capacity(history.len());
+    let mut lats = Vec::with_capacity(history.len());
+    let mut lngs = Vec::with_capacity(history.len());
+    let mut headings = Vec::with_capacity(history.len());
+    let mut speeds = Vec::with_capacity(history.len());
+    let mut accuracies = Vec::with_capacity(history.len());
+    let mut recorded = Vec::with_capacity(history.len());
+    for (driver_id, ping) in history {
+        driver_ids.push(driver_id.clone());
+        lats.push(ping.lat);
+        lngs.push(ping.lng);
+        headings.push(ping.heading);
+        speeds.push(ping.speed);
+        accuracies.push(ping.accuracy);
+        recorded.push(ping.recorded_at);
+    }
+
+    // Points are only kept once they are counted and trimmed, so a failed
+    // flush can put them back without writing them twice.
+    let mut tx = pool.begin().await?;
+
+    // Only pings from drivers on an active trip become route history.
+    let ride_rows = sqlx::query(
+        "INSERT INTO ride_location_history \
+        (ride_id, driver_id, lat, lng, heading, speed, accuracy, recorded_at) \
+        SELECT r.id, p.driver_id, p.lat, p.lng, p.heading, p.speed, p.accuracy, p.recorded_at \
+        FROM UNNEST($1::text[], $2::float8[], $3::float8[], $4::float8[], $5::float8[], $6::float8[], $7::timestamptz[]) \
+            AS p(driver_id, lat, lng, heading, speed, accuracy, recorded_at) \
+        JOIN rides r ON r.driver_id = p.driver_id \
+            AND r.status IN ('accepted', 'arriving', 'in_progress') \
+        RETURNING ride_id",
+    )
+    .bind(driver_ids)
+    .bind(lats)
+    .bind(lngs)
+    .bind(headings)
+    .bind(speeds)
+    .bind(accuracies)
+    .bind(recorded)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut ride_ids: Vec<String> = ride_rows
+        .into_iter()
+        .map(|row| row.get("ride_id"))
+        .collect();
+    ride_ids.sort();
+    ride_ids.dedup();
+    if ride_ids.is_empty() {
+        tx.commit().await?;
+        return Ok(());
+    }
+
+    // Count the new points before trimming can drop any of them.
+    add_trail_distance(&mut tx, &ride_ids).await?;
+
+    // Keep only the newest points per trip so long rides stay bounded.
+    sqlx::query(
+        "DELETE FROM ride_location_history WHERE id IN ( \
+            SELECT id FROM ( \
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY ride_id ORDER BY recorded_at DESC) AS position \
+                FROM ride_location_history WHERE ride_id = ANY($1) \
+            ) ranked WHERE position > $2 \
+        )",
+    )
+    .bind(ride_ids)
+    .bind(max_points_per_ride)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Adds the distance driven since the last flush to each trip in progress.
+/// Counting resumes from the newest point counted before, which trimming
+/// always keeps; points that arrive older than it are not counted.
+async fn add_trail_distance(
+    tx: &mut Transaction<'_, Postgres>,
+    ride_ids: &[String],
+) -> Result<(), AppError> {
+    let rows = sqlx::query(
+        "SELECT h.ride_id, h.lat, h.lng, h.recorded_at, r.trail_counted_until \
+        FROM ride_location_history h JOIN rides r ON r.id = h.ride_id \
+        WHERE h.ride_id = ANY($1) AND r.status = 'in_progress' AND r.started_at IS NOT NULL \
+        AND h.recorded_at >= COALESCE(r.trail_counted_until, r.started_at) \
+        ORDER BY h.ride_id, h.recorded_at, h.id",
+    )
+    .bind(ride_ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut trails: HashMap<String, TrailProgress> = HashMap::new();
+    for row in rows {
+        let point = (row.get::<f64, _>("lat"), row.get::<f64, _>("lng"));
+        let recorded_at: DateTime<Utc> = row.get("recorded_at");
+        let trail = trails
+            .entry(row.get("ride_id"))
+            .or_insert_with(|| TrailProgress {
+                counted_until: row.get("trail_counted_until"),
+                last: point,
+                last_recorded_at: recorded_at,
+                distance_km: 0.0,
+            });
+        trail.distance_km += haversine_km(trail.last.0, trail.last.1, point.0, point.1);
+        trail.last = point;
+        trail.last_recorded_at = recorded_at;
+    }
+
+    for (ride_id, trail) in trails {
+        // Another instance may have counted these points already.
+        sqlx::query(
+            "UPDATE rides SET trail_distance_km = trail_distance_km + $2, \
+            trail_counted_until = $3 \
+            WHERE id = $1 AND trail_counted_until IS NOT DISTINCT FROM $4",
+        )
+        .bind(&ride_id)
+        .bind(trail.distance_km)
+        .bind(trail.last_recorded_at)
+        .bind(trail.counted_until)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+struct TrailProgress {
+    counted_until: Option<DateTime<Utc>>,
+    last: (f64, f64),
+    last_recorded_at: DateTime<Utc>,
+    distance_km: f64,
+}
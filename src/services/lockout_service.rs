@@ -0,0 +1,325 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::login_model::{
+    AccountType, LoginAttempt, LoginAttemptQuery, UnlockAccountRequest,
+};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+const DEFAULT_ATTEMPT_LIMIT: i64 = 100;
+const MAX_ATTEMPT_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone)]
+pub struct LockoutConfig {
+    /// Consecutive failures before an account is locked.
+    pub max_failed_attempts: i32,
+    /// Failures from one IP, across accounts, before the IP is locked.
+    pub ip_max_failed_attempts: i32,
+    /// Failures older than this no longer count.
+    pub failure_window_minutes: i64,
+    /// First lockout; each further failure doubles it.
+    pub lockout_base_seconds: i64,
+    pub lockout_max_seconds: i64,
+}
+
+impl LockoutConfig {
+    pub fn from_env() -> Self {
+        LockoutConfig {
+            max_failed_attempts: env_or("LOGIN_MAX_FAILED_ATTEMPTS", 5),
+            ip_max_failed_attempts: env_or("LOGIN_IP_MAX_FAILED_ATTEMPTS", 20),
+            failure_window_minutes: env_or("LOGIN_FAILURE_WINDOW_MINUTES", 15),
+            lockout_base_seconds: env_or("LOGIN_LOCKOUT_BASE_SECONDS", 60),
+            lockout_max_seconds: env_or("LOGIN_LOCKOUT_MAX_SECONDS", 3600),
+        }
+    }
+
+    /// Lockout after `failed_count` consecutive failures, if any.
+    fn lockout_for(&self, failed_count: i32, threshold: i32) -> Option<Duration> {
+        if failed_count < threshold {
+            return None;
+        }
+        let doublings = (failed_count - threshold).min(20) as u32;
+        let seconds = self
+            .lockout_base_seconds
+            .saturating_mul(2i64.pow(doublings))
+            .min(self.lockout_max_seconds);
+        Some(Duration::seconds(seconds))
+    }
+}
+
+/// Why a login attempt failed, as stored in the audit trail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginFailure {
+    UnknownAccount,
+    BadPassword,
+    BadSecondFactor,
+    AccountDisabled,
+    LockedOut,
+}
+
+impl LoginFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginFailure::UnknownAccount => "unknown_account",
+            LoginFailure::BadPassword => "bad_password",
+            LoginFailure::BadSecondFactor => "bad_second_factor",
+            LoginFailure::AccountDisabled => "account_disabled",
+            LoginFailure::LockedOut => "locked_out",
+        }
+    }
+
+    /// Guesses count towards a lockout; refusals of a known state do not.
+    fn counts_towards_lockout(&self) -> bool {
+        matches!(
+            self,
+            LoginFailure::UnknownAccount
+                | LoginFailure::BadPassword
+                | LoginFailure::BadSecondFactor
+        )
+    }
+}
+
+/// Who is trying to sign in. Unknown usernames are tracked too, so lockouts
+/// do not reveal which accounts exist.
+#[derive(Debug, Clone)]
+pub struct LoginAttemptKey<'a> {
+    pub account_type: AccountType,
+    pub username: &'a str,
+    pub ip_address: Option<&'a str>,
+}
+
+impl LoginAttemptKey<'_> {
+    fn account_key(&self) -> String {
+        format!(
+            "{}:{}",
+            self.account_type.as_str(),
+            self.username.trim().to_lowercase()
+        )
+    }
+}
+
+// ============================================================================
+// Lockout Services
+// ============================================================================
+
+/// Refuses the attempt while the account or the client IP is locked out.
+pub async fn check_login_allowed(pool: &PgPool, key: &LoginAttemptKey<'_>) -> Result<(), AppError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        "SELECT MAX(locked_until) AS locked_until FROM login_throttles \
+        WHERE locked_until > $1 AND ((scope = 'account' AND key = $2) OR (scope = 'ip' AND key = $3))",
+    )
+    .bind(now)
+    .bind(key.account_key())
+    .bind(key.ip_address)
+    .fetch_one(pool)
+    .await?;
+
+    let Some(locked_until) = row.get::<Option<DateTime<Utc>>, _>("locked_until") else {
+        return Ok(());
+    };
+
+    record_login_failure(pool, key, None, LoginFailure::LockedOut).await?;
+    let retry_after = (locked_until - now).num_seconds() + 1;
+    Err(AppError::rate_limited(
+        format!(
+            "Too many failed login attempts, try again in {} seconds",
+            retry_after
+        ),
+        retry_after,
+    ))
+}
+
+/// Audits a failed attempt and, for guesses, extends the account and IP
+/// failure counters, locking them out past their thresholds.
+pub async fn record_login_failure(
+    pool: &PgPool,
+    key: &LoginAttemptKey<'_>,
+    account_id: Option<&str>,
+    reason: LoginFailure,
+) -> Result<(), AppError> {
+    insert_attempt(pool, key, account_id, Some(reason)).await?;
+    if !reason.counts_towards_lockout() {
+        return Ok(());
+    }
+
+    let config = LockoutConfig::from_env();
+    bump_throttle(
+        pool,
+        &config,
+        "account",
+        &key.account_key(),
+        config.max_failed_attempts,
+    )
+    .await?;
+    if let Some(ip_address) = key.ip_address {
+        bump_throttle(
+            pool,
+            &config,
+            "ip",
+            ip_address,
+            config.ip_max_failed_attempts,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Audits a successful login and clears the account's failure counter. The
+/// IP counter is left alone so one valid account cannot mask guessing.
+pub async fn record_login_success(
+    pool: &PgPool,
+    key: &LoginAttemptKey<'_>,
+    account_id: &str,
+) -> Result<(), AppError> {
+    insert_attempt(pool, key, Some(account_id), None).await?;
+
+    sqlx::query("DELETE FROM login_throttles WHERE scope = 'account' AND key = $1")
+        .bind(key.account_key())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Lifts an account lockout and resets its failure counter.
+pub async fn unlock_account_service(
+    pool: &PgPool,
+    payload: UnlockAccountRequest,
+) -> Result<bool, AppError> {
+    let key = LoginAttemptKey {
+        account_type: payload.account_type,
+        username: &payload.username,
+        ip_address: None,
+    };
+
+    let result = sqlx::query("DELETE FROM login_throttles WHERE scope = 'account' AND key = $1")
+        .bind(key.account_key())
+        .execute(pool)
+        .await?;
+
+    info!(account = %key.account_key(), "Account unlocked");
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_login_attempts_service(
+    pool: &PgPool,
+    query: LoginAttemptQuery,
+) -> Result<Vec<LoginAttempt>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ATTEMPT_LIMIT)
+        .clamp(1, MAX_ATTEMPT_LIMIT);
+
+    let rows = sqlx::query(
+        "SELECT id, account_type, username, account_id, ip_address, succeeded, failure_reason, created_at \
+        FROM login_attempts \
+        WHERE ($1::TEXT IS NULL OR LOWER(username) = LOWER($1)) \
+        AND ($2::TEXT IS NULL OR ip_address = $2) \
+        AND (NOT $3 OR succeeded = FALSE) \
+        ORDER BY created_at DESC LIMIT $4",
+    )
+    .bind(query.username)
+    .bind(query.ip_address)
+    .bind(query.failed_only.unwrap_or(true))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LoginAttempt {
+            id: row.get("id"),
+            account_type: row.get("account_type"),
+            username: row.get("username"),
+            account_id: row.get("account_id"),
+            ip_address: row.get("ip_address"),
+            succeeded: row.get("succeeded"),
+            failure_reason: row.get("failure_reason"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+async fn insert_attempt(
+    pool: &PgPool,
+    key: &LoginAttemptKey<'_>,
+    account_id: Option<&str>,
+    failure: Option<LoginFailure>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO login_attempts \
+        (id, account_type, username, account_id, ip_address, succeeded, failure_reason, created_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(key.account_type.as_str())
+    .bind(key.username)
+    .bind(account_id)
+    .bind(key.ip_address)
+    .bind(failure.is_none())
+    .bind(failure.map(|failure| failure.as_str()))
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn bump_throttle(
+    pool: &PgPool,
+    config: &LockoutConfig,
+    scope: &str,
+    key: &str,
+    threshold: i32,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    // A failure after a quiet window starts a new streak
+    let failed_count: i32 = sqlx::query(
+        "INSERT INTO login_throttles (scope, key, failed_count, last_failed_at) \
+        VALUES ($1,$2,1,$3) \
+        ON CONFLICT (scope, key) DO UPDATE SET \
+            failed_count = CASE WHEN login_throttles.last_failed_at < $4 THEN 1 \
+                ELSE login_throttles.failed_count + 1 END, \
+            last_failed_at = $3 \
+        RETURNING failed_count",
+    )
+    .bind(scope)
+    .bind(key)
+    .bind(now)
+    .bind(now - Duration::minutes(config.failure_window_minutes))
+    .fetch_one(pool)
+    .await?
+    .get("failed_count");
+
+    let Some(lockout) = config.lockout_for(failed_count, threshold) else {
+        debug!(scope, key, failed_count, "Failed login recorded");
+        return Ok(());
+    };
+
+    sqlx::query("UPDATE login_throttles SET locked_until = $3 WHERE scope = $1 AND key = $2")
+        .bind(scope)
+        .bind(key)
+        .bind(now + lockout)
+        .execute(pool)
+        .await?;
+
+    warn!(
+        scope,
+        key,
+        failed_count,
+        lockout_seconds = lockout.num_seconds(),
+        "Login locked out"
+    );
+
+    Ok(())
+}
@@ -1,58 +1,187 @@
-use crate::auth::password_utils::create_token;
+use crate::auth::client_info::ClientInfo;
 use crate::auth::password_utils::verify_password;
-use crate::auth::password_utils::verify_token;
-use crate::models::login_model::{LoginRequest, LoginResponse};
+use crate::error::AppError;
+use crate::models::login_model::{AccountType, LoginOutcome, LoginRequest, LoginResponse};
+use crate::models::two_factor_model::TwoFactorLoginRequest;
+use crate::services::auth_service::{SessionAccount, SessionDevice, start_session};
+use crate::services::lockout_service::{
+    LoginAttemptKey, LoginFailure, check_login_allowed, record_login_failure, record_login_success,
+};
+use crate::services::two_factor_service::{
+    complete_login_challenge, create_login_challenge, is_two_factor_enabled,
+};
 use sqlx::{PgPool, Row};
+use tracing::debug;
+
+/// Each account type signs in against its own table. Drivers and company
+/// admins are refused once their company is deactivated.
+const RIDER_LOGIN_QUERY: &str = "SELECT id, username, email, password_hash, role, \
+    NULL::TEXT AS transport_company_id, is_active \
+    FROM users WHERE username = $1";
+const DRIVER_LOGIN_QUERY: &str = "SELECT d.id, d.username, d.email, d.password_hash, d.role, \
+    d.transport_company_id, c.is_active \
+    FROM transport_company_drivers d \
+    JOIN transport_companies c ON c.id = d.transport_company_id \
+    WHERE d.username = $1";
+const COMPANY_ADMIN_LOGIN_QUERY: &str = "SELECT a.id, a.username, a.email, a.password_hash, a.role, \
+    a.transport_company_id, (a.is_active AND c.is_active) AS is_active \
+    FROM transport_company_admins a \
+    JOIN transport_companies c ON c.id = a.transport_company_id \
+    WHERE a.username = $1";
+
+pub async fn login_service(
+    pool: &PgPool,
+    payload: LoginRequest,
+    client: ClientInfo,
+) -> Result<LoginOutcome, AppError> {
+    debug!(username = %payload.username, account_type = %payload.account_type.as_str(), "Login attempt");
 
-pub async fn login_service(pool: &PgPool, payload: LoginRequest) -> Result<LoginResponse, String> {
     // Validate input
     if payload.username.is_empty() || payload.password.is_empty() {
-        return Err("Username and password are required".to_string());
+        return Err(AppError::Validation(
+            "Username and password are required".to_string(),
+        ));
     }
 
-    // Fetch user by username
-    let row =
-        sqlx::query("SELECT id, username, password_hash, role FROM users WHERE username = $1")
-            .bind(&payload.username)
-            .fetch_one(pool)
-            .await
-            .map_err(|_| "Invalid username or password".to_string())?;
+    let attempt = LoginAttemptKey {
+        account_type: payload.account_type,
+        username: &payload.username,
+        ip_address: client.ip_address.as_deref(),
+    };
+    check_login_allowed(pool, &attempt).await?;
+
+    let query = match payload.account_type {
+        AccountType::Rider => RIDER_LOGIN_QUERY,
+        AccountType::Driver => DRIVER_LOGIN_QUERY,
+        AccountType::CompanyAdmin => COMPANY_ADMIN_LOGIN_QUERY,
+    };
+
+    // Fetch account by username
+    let row = sqlx::query(query)
+        .bind(&payload.username)
+        .fetch_optional(pool)
+        .await?;
 
-    let new_row: sqlx::postgres::PgRow = row;
+    let Some(new_row) = row else {
+        record_login_failure(pool, &attempt, None, LoginFailure::UnknownAccount).await?;
+        return Err(invalid_credentials());
+    };
 
     let user_id: String = new_row
         .try_get::<String, _>("id")
-        .map_err(|_| "Invalid username or password".to_string())?;
+        .map_err(|_| invalid_credentials())?;
     let username: String = new_row
         .try_get::<String, _>("username")
-        .map_err(|_| "Invalid username or password".to_string())?;
+        .map_err(|_| invalid_credentials())?;
+    let email: String = new_row
+        .try_get::<String, _>("email")
+        .map_err(|_| invalid_credentials())?;
     let password_hash: String = new_row
         .try_get::<String, _>("password_hash")
-        .map_err(|_| "Invalid username or password".to_string())?;
+        .map_err(|_| invalid_credentials())?;
     let role: String = new_row
         .try_get::<String, _>("role")
-        .map_err(|_| "Invalid username or password".to_string())?;
+        .map_err(|_| invalid_credentials())?;
+    let transport_company_id: Option<String> = new_row
+        .try_get::<Option<String>, _>("transport_company_id")
+        .map_err(|_| invalid_credentials())?;
+    let is_active: bool = new_row
+        .try_get::<bool, _>("is_active")
+        .map_err(|_| invalid_credentials())?;
     // Verify password
     if !verify_password(&payload.password, &password_hash) {
-        return Err("Invalid username or password".to_string());
+        record_login_failure(pool, &attempt, Some(&user_id), LoginFailure::BadPassword).await?;
+        return Err(invalid_credentials());
+    }
+    if !is_active {
+        record_login_failure(
+            pool,
+            &attempt,
+            Some(&user_id),
+            LoginFailure::AccountDisabled,
+        )
+        .await?;
+        return Err(AppError::Forbidden("Account is disabled".to_string()));
+    }
+
+    let account = SessionAccount {
+        account_id: user_id,
+        account_type: payload.account_type,
+        role,
+        transport_company_id,
+        two_factor: false,
+    };
+
+    // The token is only issued after the second login step
+    if is_two_factor_enabled(pool, &account.account_id).await? {
+        debug!(user_id = %account.account_id, "Second factor required");
+        let challenge = create_login_challenge(pool, &account, &username, &email).await?;
+        return Ok(LoginOutcome::TwoFactorRequired(challenge));
     }
 
-    // Generate token (placeholder - implement JWT or similar)
-    let token = create_token(&user_id, &role).map_err(|e| {
-        eprintln!("Failed to create token: {}", e);
-        "Failed to create token".to_string()
-    })?;
+    record_login_success(pool, &attempt, &account.account_id).await?;
+    let device = SessionDevice::new(payload.device, client);
+    let response = finish_login(pool, account, &device, username, email).await?;
+    Ok(LoginOutcome::Authenticated(response))
+}
+
+/// Second login step: exchanges a login challenge and a TOTP or recovery
+/// code for tokens.
+pub async fn login_two_factor_service(
+    pool: &PgPool,
+    payload: TwoFactorLoginRequest,
+    client: ClientInfo,
+) -> Result<LoginResponse, AppError> {
+    let verified = complete_login_challenge(
+        pool,
+        &payload.challenge_token,
+        &payload.code,
+        client.ip_address.as_deref(),
+    )
+    .await?;
+
+    let attempt = LoginAttemptKey {
+        account_type: verified.account.account_type,
+        username: &verified.username,
+        ip_address: client.ip_address.as_deref(),
+    };
+    record_login_success(pool, &attempt, &verified.account.account_id).await?;
+
+    let device = SessionDevice::new(payload.device, client);
+    finish_login(
+        pool,
+        verified.account,
+        &device,
+        verified.username,
+        verified.email,
+    )
+    .await
+}
+
+async fn finish_login(
+    pool: &PgPool,
+    account: SessionAccount,
+    device: &SessionDevice,
+    username: String,
+    email: String,
+) -> Result<LoginResponse, AppError> {
+    let tokens = start_session(pool, &account, device).await?;
 
     Ok(LoginResponse {
-        token,
-        user_id,
-        email: username.clone(),
+        token: tokens.token,
+        expires_in: tokens.expires_in,
+        refresh_token: tokens.refresh_token,
+        user_id: account.account_id,
+        email,
         username,
-        role,
+        role: account.role,
+        account_type: account.account_type,
+        transport_company_id: account.transport_company_id,
     })
 }
 
-pub fn login_verify_token(token: &str) -> Result<String, String> {
-    let claims = verify_token(token)?;
-    Ok(claims.sub)
+/// Same error for unknown users and wrong passwords, so logins cannot be used
+/// to probe usernames.
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid username or password".to_string())
 }
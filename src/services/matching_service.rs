@@ -0,0 +1,474 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::matching_model::{
+    DispatchOutcome, DriverCandidate, OfferStatus, RideOfferResponse,
+};
+use crate::models::realtime_model::RealtimeEvent;
+use crate::models::ride_model::{RideResponse, RideStatus};
+use crate::services::realtime_service::{RealtimeHub, Topic};
+use crate::services::ride_service::{accept_ride_service, get_ride_service};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+const KM_PER_DEGREE_LAT: f64 = 111.32;
+const DEFAULT_AVERAGE_SPEED_KMH: f64 = 30.0;
+const DEFAULT_DRIVER_RATING: f32 = 4.0;
+/// How strongly a low rating inflates a driver's effective distance.
+const RATING_WEIGHT: f64 = 0.5;
+const OFFER_POLL_INTERVAL_MS: u64 = 500;
+/// Slack on top of a full dispatch loop before its claim counts as stale.
+const DISPATCH_CLAIM_GRACE_SECONDS: i64 = 60;
+
+const OFFER_COLUMNS: &str =
+    "id, ride_id, driver_id, status, rank, distance_km, offered_at, expires_at, responded_at";
+
+#[derive(Debug, Clone)]
+pub struct MatchingConfig {
+    pub search_radius_km: f64,
+    pub max_candidates: usize,
+    pub offer_timeout_seconds: i64,
+    pub location_max_age_seconds: i64,
+}
+
+impl MatchingConfig {
+    pub fn from_env() -> Self {
+        MatchingConfig {
+            search_radius_km: env_or("MATCH_SEARCH_RADIUS_KM", 5.0),
+            max_candidates: env_or("MATCH_MAX_CANDIDATES", 10),
+            offer_timeout_seconds: env_or("MATCH_OFFER_TIMEOUT_SECONDS", 15),
+            location_max_age_seconds: env_or("MATCH_LOCATION_MAX_AGE_SECONDS", 120),
+        }
+    }
+}
+
+// ============================================================================
+// Candidate Search
+// ============================================================================
+
+/// Great-circle distance between two points in kilometres.
+pub fn haversine_km(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Rough driving time for a distance at the configured average city speed.
+pub fn estimate_travel_seconds(distance_km: f64) -> i64 {
+    let speed_kmh: f64 = env_or("TRIP_AVERAGE_SPEED_KMH", DEFAULT_AVERAGE_SPEED_KMH);
+    (distance_km / speed_kmh.max(1.0) * 3600.0).round() as i64
+}
+
+/// Orders candidates by distance, weighted so that better-rated drivers
+/// win against slightly closer but poorly rated ones.
+pub fn rank_candidates(mut candidates: Vec<DriverCandidate>) -> Vec<DriverCandidate> {
+    let score = |candidate: &DriverCandidate| {
+        let rating = candidate
+            .rating
+            .unwrap_or(DEFAULT_DRIVER_RATING)
+            .clamp(1.0, 5.0) as f64;
+        let normalized = (rating - 1.0) / 4.0;
+        candidate.distance_km * (1.0 + RATING_WEIGHT * (1.0 - normalized))
+    };
+    candidates.sort_by(|a, b| score(a).total_cmp(&score(b)));
+    candidates
+}
+
+/// Available drivers with a fresh position within the search radius of the
+/// pickup point who have not already been offered this ride. When the rider
+/// asked for a vehicle type, only drivers of that type qualify.
+pub async fn find_candidate_drivers(
+    pool: &PgPool,
+    ride_id: &str,
+    pickup_lat: f64,
+    pickup_lng: f64,
+    vehicle_type: Option<&str>,
+    config: &MatchingConfig,
+) -> Result<Vec<DriverCandidate>, AppError> {
+    let lat_delta = config.search_radius_km / KM_PER_DEGREE_LAT;
+    let lng_delta = config.search_radius_km
+        / (KM_PER_DEGREE_LAT * pickup_lat.to_radians().cos().abs().max(0.01));
+    let fresh_after = Utc::now() - Duration::seconds(config.location_max_age_seconds);
+
+    // Bounding-box prefilter in SQL; exact distance is computed below.
+    let rows = sqlx::query(
+        "SELECT d.id, d.rating, l.lat, l.lng \
+        FROM transport_company_drivers d \
+        JOIN driver_locations l ON l.driver_id = d.id \
+        WHERE d.is_available = TRUE \
+        AND d.email_verified_at IS NOT NULL AND d.phone_verified_at IS NOT NULL \
+        AND l.updated_at >= $1 \
+        AND l.lat BETWEEN $2 AND $3 \
+        AND l.lng BETWEEN $4 AND $5 \
+        AND ($7::TEXT IS NULL OR d.vehicle_type = $7) \
+        AND NOT EXISTS (SELECT 1 FROM ride_offers o WHERE o.ride_id = $6 AND o.driver_id = d.id)",
+    )
+    .bind(fresh_after)
+    .bind(pickup_lat - lat_delta)
+    .bind(pickup_lat + lat_delta)
+    .bind(pickup_lng - lng_delta)
+    .bind(pickup_lng + lng_delta)
+    .bind(ride_id)
+    .bind(vehicle_type)
+    .fetch_all(pool)
+    .await?;
+
+    let candidates = rows
+        .into_iter()
+        .map(|row| DriverCandidate {
+            driver_id: row.get("id"),
+            rating: row.get("rating"),
+            distance_km: haversine_km(pickup_lat, pickup_lng, row.get("lat"), row.get("lng")),
+        })
+        .filter(|candidate| candidate.distance_km <= config.search_radius_km)
+        .collect();
+
+    Ok(rank_candidates(candidates))
+}
+
+// ============================================================================
+// Dispatch
+// ============================================================================
+
+/// Offers a requested ride to the best available drivers one at a time,
+/// waiting up to the offer timeout for each before moving on. Only one loop
+/// runs per ride; a second one stops straight away.
+pub async fn dispatch_ride(
+    pool: PgPool,
+    hub: RealtimeHub,
+    ride_id: String,
+) -> Result<DispatchOutcome, AppError> {
+    let config = MatchingConfig::from_env();
+    let Some(claimed_at) = claim_dispatch(&pool, &ride_id, &config).await? else {
+        info!(ride_id = %ride_id, "Ride is already being dispatched");
+        return Ok(DispatchOutcome::AlreadyDispatching);
+    };
+
+    let result = run_dispatch(&pool, &hub, &ride_id, &config).await;
+    release_dispatch(&pool, &ride_id, claimed_at).await?;
+    result
+}
+
+/// Starts matching in the background once this caller holds the ride's
+/// dispatch claim. Returns `false` when another loop is already running.
+pub async fn start_dispatch(
+    pool: PgPool,
+    hub: RealtimeHub,
+    ride_id: String,
+) -> Result<bool, AppError> {
+    let config = MatchingConfig::from_env();
+    let Some(claimed_at) = claim_dispatch(&pool, &ride_id, &config).await? else {
+        return Ok(false);
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = run_dispatch(&pool, &hub, &ride_id, &config).await {
+            error!(ride_id = %ride_id, error = %e, "Driver matching failed");
+        }
+        if let Err(e) = release_dispatch(&pool, &ride_id, claimed_at).await {
+            error!(ride_id = %ride_id, error = %e, "Failed to release dispatch claim");
+        }
+    });
+    Ok(true)
+}
+
+/// Runs matching in the background so the caller does not wait on drivers.
+pub fn spawn_dispatch(pool: PgPool, hub: RealtimeHub, ride_id: String) {
+    tokio::spawn(async move {
+        if let Err(e) = dispatch_ride(pool, hub, ride_id.clone()).await {
+            error!(ride_id = %ride_id, error = %e, "Driver matching failed");
+        }
+    });
+}
+
+/// The dispatch loop itself; the caller holds the ride's dispatch claim.
+async fn run_dispatch(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    ride_id: &str,
+    config: &MatchingConfig,
+) -> Result<DispatchOutcome, AppError> {
+    info!(ride_id = %ride_id, "Starting driver matching");
+
+    let mut offers_made = 0;
+    while offers_made < config.max_candidates {
+        let ride = get_ride_service(pool, ride_id.to_string()).await?;
+        if ride.status != RideStatus::Requested {
+            info!(ride_id = %ride_id, status = %ride.status.as_str(), "Ride no longer open, stopping dispatch");
+            return Ok(closed_outcome(&ride));
+        }
+
+        // Re-query every round: availability and positions change while we wait.
+        let candidates = find_candidate_drivers(
+            pool,
+            ride_id,
+            ride.pickup_lat,
+            ride.pickup_lng,
+            ride.vehicle_type.as_deref(),
+            config,
+        )
+        .await?;
+        let Some(candidate) = candidates.into_iter().next() else {
+            break;
+        };
+
+        offers_made += 1;
+        let offer = create_offer(pool, ride_id, &candidate, offers_made as i32, config).await?;
+        info!(
+            ride_id = %ride_id,
+            driver_id = %candidate.driver_id,
+            distance_km = candidate.distance_km,
+            "Ride offered to driver"
+        );
+        hub.publish(
+            Topic::DriverOffers(offer.driver_id.clone()),
+            RealtimeEvent::RideOffered {
+                offer: offer.clone(),
+            },
+        );
+
+        match wait_for_offer_response(pool, &offer).await? {
+            OfferStatus::Accepted => {
+                info!(ride_id = %ride_id, driver_id = %offer.driver_id, "Ride matched");
+                return Ok(DispatchOutcome::Matched {
+                    driver_id: offer.driver_id,
+                });
+            }
+            OfferStatus::Expired => {
+                hub.publish(
+                    Topic::DriverOffers(offer.driver_id.clone()),
+                    RealtimeEvent::OfferExpired {
+                        offer_id: offer.id.clone(),
+                        ride_id: ride_id.to_string(),
+                    },
+                );
+                debug!(ride_id = %ride_id, driver_id = %offer.driver_id, "Offer expired, trying next driver");
+            }
+            status => {
+                debug!(ride_id = %ride_id, driver_id = %offer.driver_id, status = %status.as_str(), "Offer not accepted, trying next driver");
+            }
+        }
+    }
+
+    warn!(ride_id = %ride_id, offers_made, "No driver accepted the ride");
+    Ok(DispatchOutcome::NoDriverFound)
+}
+
+/// Marks a requested ride as being dispatched and returns the claim's time,
+/// or `None` when a live loop already holds it. A claim older than a full
+/// loop belongs to a loop that died and is taken over.
+async fn claim_dispatch(
+    pool: &PgPool,
+    ride_id: &str,
+    config: &MatchingConfig,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    let now = Utc::now();
+    let stale_before = now
+        - Duration::seconds(
+            (config.offer_timeout_seconds + 1) * config.max_candidates as i64
+                + DISPATCH_CLAIM_GRACE_SECONDS,
+        );
+
+    let claimed = sqlx::query(
+        "UPDATE rides SET dispatching_since = $2 \
+        WHERE id = $1 AND status = $3 \
+        AND (dispatching_since IS NULL OR dispatching_since < $4)",
+    )
+    .bind(ride_id)
+    .bind(now)
+    .bind(RideStatus::Requested.as_str())
+    .bind(stale_before)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok((claimed == 1).then_some(now))
+}
+
+/// Drops the dispatch claim, unless another loop has taken it over since.
+async fn release_dispatch(
+    pool: &PgPool,
+    ride_id: &str,
+    claimed_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE rides SET dispatching_since = NULL WHERE id = $1 AND dispatching_since = $2",
+    )
+    .bind(ride_id)
+    .bind(claimed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_offer(
+    pool: &PgPool,
+    ride_id: &str,
+    candidate: &DriverCandidate,
+    rank: i32,
+    config: &MatchingConfig,
+) -> Result<RideOfferResponse, AppError> {
+    let now = Utc::now();
+    let row = sqlx::query(&format!(
+        "INSERT INTO ride_offers \
+        (id, ride_id, driver_id, status, rank, distance_km, offered_at, expires_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8) \
+        RETURNING {}",
+        OFFER_COLUMNS
+    ))
+    .bind(Uuid::new_v4().to_string())
+    .bind(ride_id)
+    .bind(&candidate.driver_id)
+    .bind(OfferStatus::Pending.as_str())
+    .bind(rank)
+    .bind(candidate.distance_km)
+    .bind(now)
+    .bind(now + Duration::seconds(config.offer_timeout_seconds))
+    .fetch_one(pool)
+    .await?;
+
+    row_to_offer_response(row)
+}
+
+async fn wait_for_offer_response(
+    pool: &PgPool,
+    offer: &RideOfferResponse,
+) -> Result<OfferStatus, AppError> {
+    loop {
+        let status: String = sqlx::query("SELECT status FROM ride_offers WHERE id = $1")
+            .bind(&offer.id)
+            .fetch_one(pool)
+            .await?
+            .get("status");
+        let status = OfferStatus::parse(&status)?;
+
+        if status != OfferStatus::Pending {
+            return Ok(status);
+        }
+
+        if Utc::now() >= offer.expires_at {
+            let expired = sqlx::query(
+                "UPDATE ride_offers SET status = $1, responded_at = $2 \
+                WHERE id = $3 AND status = $4",
+            )
+            .bind(OfferStatus::Expired.as_str())
+            .bind(Utc::now())
+            .bind(&offer.id)
+            .bind(OfferStatus::Pending.as_str())
+            .execute(pool)
+            .await?;
+
+            // Lost the race to a last-moment response; read it on the next pass.
+            if expired.rows_affected() == 1 {
+                return Ok(OfferStatus::Expired);
+            }
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(OFFER_POLL_INTERVAL_MS)).await;
+    }
+}
+
+// ============================================================================
+// Driver Offer Services
+// ============================================================================
+
+pub async fn list_driver_offers_service(
+    pool: &PgPool,
+    driver_id: String,
+) -> Result<Vec<RideOfferResponse>, AppError> {
+    debug!(driver_id = %driver_id, "Fetching pending ride offers");
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM ride_offers \
+        WHERE driver_id = $1 AND status = $2 AND expires_at > $3 \
+        ORDER BY offered_at DESC",
+        OFFER_COLUMNS
+    ))
+    .bind(&driver_id)
+    .bind(OfferStatus::Pending.as_str())
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(row_to_offer_response).collect()
+}
+
+pub async fn respond_to_offer_service(
+    pool: &PgPool,
+    offer_id: String,
+    driver_id: String,
+    accept: bool,
+) -> Result<RideOfferResponse, AppError> {
+    debug!(offer_id = %offer_id, driver_id = %driver_id, accept, "Driver responding to ride offer");
+
+    let next = if accept {
+        OfferStatus::Accepted
+    } else {
+        OfferStatus::Declined
+    };
+
+    let row = sqlx::query(&format!(
+        "UPDATE ride_offers SET status = $1, responded_at = $2 \
+        WHERE id = $3 AND driver_id = $4 AND status = $5 AND expires_at > $2 \
+        RETURNING {}",
+        OFFER_COLUMNS
+    ))
+    .bind(next.as_str())
+    .bind(Utc::now())
+    .bind(&offer_id)
+    .bind(&driver_id)
+    .bind(OfferStatus::Pending.as_str())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        error!(offer_id = %offer_id, driver_id = %driver_id, "Offer not found or no longer pending");
+        AppError::conflict("Offer not found, expired or already answered")
+    })?;
+
+    let offer = row_to_offer_response(row)?;
+
+    if accept && let Err(e) = accept_ride_service(pool, offer.ride_id.clone(), driver_id).await {
+        // The ride could not be assigned; release the offer so dispatch moves on.
+        sqlx::query("UPDATE ride_offers SET status = $1 WHERE id = $2")
+            .bind(OfferStatus::Expired.as_str())
+            .bind(&offer.id)
+            .execute(pool)
+            .await?;
+        return Err(e);
+    }
+
+    info!(offer_id = %offer.id, status = %offer.status.as_str(), "Ride offer answered");
+
+    Ok(offer)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn closed_outcome(ride: &RideResponse) -> DispatchOutcome {
+    match (&ride.status, &ride.driver_id) {
+        (RideStatus::Accepted, Some(driver_id)) => DispatchOutcome::Matched {
+            driver_id: driver_id.clone(),
+        },
+        _ => DispatchOutcome::RideClosed,
+    }
+}
+
+fn row_to_offer_response(row: sqlx::postgres::PgRow) -> Result<RideOfferResponse, AppError> {
+    Ok(RideOfferResponse {
+        id: row.get("id"),
+        ride_id: row.get("ride_id"),
+        driver_id: row.get("driver_id"),
+        status: OfferStatus::parse(row.get("status"))?,
+        rank: row.get("rank"),
+        distance_km: row.get("distance_km"),
+        offered_at: row.get("offered_at"),
+        expires_at: row.get("expires_at"),
+        responded_at: row.get("responded_at"),
+    })
+}
@@ -1,5 +1,24 @@
+pub mod auth_service;
 pub mod database_service;
-pub mod user_service;
+pub mod history_service;
+pub mod location_service;
+pub mod lockout_service;
+pub mod login_service;
+pub mod matching_service;
+pub mod notification_service;
+pub mod password_reset_service;
+pub mod payment_gateway;
 pub mod payment_service;
+pub mod pricing_service;
+pub mod rating_service;
+pub mod realtime_service;
+pub mod refund_service;
+pub mod ride_service;
+pub mod schedule_service;
+pub mod session_service;
+pub mod surge_service;
 pub mod transport_company_service;
-pub mod login_service;
+pub mod two_factor_service;
+pub mod user_service;
+pub mod verification_service;
+pub mod webhook_service;
@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationChannel {
+    Email,
+    Sms,
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Email => "email",
+            NotificationChannel::Sms => "sms",
+        }
+    }
+}
+
+/// A message for a single account, e.g. an email with a reset link.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub channel: NotificationChannel,
+    /// Email address or phone number, depending on the channel.
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// Delivers notifications to users. Implement this for a real email or SMS
+/// provider; development uses [`LogSender`] or [`FileSender`].
+pub trait NotificationSender: Send + Sync {
+    fn send<'a>(&'a self, notification: &'a Notification) -> SendFuture<'a>;
+}
+
+/// Writes notifications to the application log.
+pub struct LogSender;
+
+impl NotificationSender for LogSender {
+    fn send<'a>(&'a self, notification: &'a Notification) -> SendFuture<'a> {
+        Box::pin(async move {
+            info!(
+                channel = notification.channel.as_str(),
+                recipient = %notification.recipient,
+                subject = %notification.subject,
+                body = %notification.body,
+                "Notification (log sender)"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Appends notifications to a local outbox file.
+pub struct FileSender {
+    path: PathBuf,
+}
+
+impl FileSender {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileSender { path: path.into() }
+    }
+}
+
+impl NotificationSender for FileSender {
+    fn send<'a>(&'a self, notification: &'a Notification) -> SendFuture<'a> {
+        Box::pin(async move {
+            let entry = format!(
+                "--- {} ({})\nTo: {}\nSubject: {}\n\n{}\n\n",
+                Utc::now().to_rfc3339(),
+                notification.channel.as_str(),
+                notification.recipient,
+                notification.subject,
+                notification.body
+            );
+
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(|e| e.to_string())?;
+            file.write_all(entry.as_bytes())
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Shared handle to the configured sender, passed to handlers as an extension.
+#[derive(Clone)]
+pub struct Notifier {
+    sender: Arc<dyn NotificationSender>,
+}
+
+impl Notifier {
+    pub fn new(sender: impl NotificationSender + 'static) -> Self {
+        Notifier {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Picks the sender from `NOTIFICATION_SENDER` (`log` or `file`); the
+    /// file sender writes to `NOTIFICATION_OUTBOX_PATH`.
+    pub fn from_env() -> Self {
+        match std::env::var("NOTIFICATION_SENDER").as_deref() {
+            Ok("file") => {
+                let path = std::env::var("NOTIFICATION_OUTBOX_PATH")
+                    .unwrap_or_else(|_| "notifications.log".to_string());
+                info!(path = %path, "Notifications are written to a file");
+                Notifier::new(FileSender::new(path))
+            }
+            _ => Notifier::new(LogSender),
+        }
+    }
+
+    pub async fn send(&self, notification: Notification) -> Result<(), String> {
+        self.sender.send(&notification).await.map_err(|e| {
+            error!(recipient = %notification.recipient, error = %e, "Failed to send notification");
+            e
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use crate::auth::password_utils::hash_password;
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::auth_model::{ForgotPasswordRequest, ResetPasswordRequest};
+use crate::models::login_model::AccountType;
+use crate::services::auth_service::{generate_token, hash_token, revoke_account_sessions};
+use crate::services::notification_service::{Notification, NotificationChannel, Notifier};
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetConfig {
+    pub token_ttl_minutes: i64,
+    /// Page of the client app that takes the token, e.g.
+    /// `https://app.ridenow.ng/reset-password`. Without it the raw token is sent.
+    pub reset_url: Option<String>,
+}
+
+impl PasswordResetConfig {
+    pub fn from_env() -> Self {
+        PasswordResetConfig {
+            token_ttl_minutes: env_or("PASSWORD_RESET_TOKEN_TTL_MINUTES", 30),
+            reset_url: std::env::var("PASSWORD_RESET_URL").ok(),
+        }
+    }
+}
+
+// ============================================================================
+// Password Reset Services
+// ============================================================================
+
+/// Sends a reset token to the account with the given email. Succeeds whether
+/// or not the account exists so the endpoint cannot be used to probe emails.
+pub async fn forgot_password_service(
+    pool: &PgPool,
+    notifier: &Notifier,
+    payload: ForgotPasswordRequest,
+) -> Result<(), AppError> {
+    let table = account_table(&payload.account_type)?;
+    let email = payload.email.trim().to_lowercase();
+    if email.is_empty() {
+        return Err(AppError::Validation("Email is required".to_string()));
+    }
+
+    let query = format!("SELECT id, email FROM {} WHERE LOWER(email) = $1", table);
+    let Some(account) = sqlx::query(&query)
+        .bind(&email)
+        .fetch_optional(pool)
+        .await?
+    else {
+        debug!(
+            account_type = payload.account_type.as_str(),
+            "Password reset requested for unknown email"
+        );
+        return Ok(());
+    };
+    let account_id: String = account.get("id");
+    let recipient: String = account.get("email");
+
+    let config = PasswordResetConfig::from_env();
+    let now = Utc::now();
+    let token = generate_token();
+
+    let mut tx = pool.begin().await?;
+
+    // Only the newest token works
+    sqlx::query(
+        "UPDATE password_reset_tokens SET used_at = $2 WHERE account_id = $1 AND used_at IS NULL",
+    )
+    .bind(&account_id)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO password_reset_tokens \
+        (id, account_id, account_type, token_hash, created_at, expires_at) \
+        VALUES ($1,$2,$3,$4,$5,$6)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&account_id)
+    .bind(payload.account_type.as_str())
+    .bind(hash_token(&token))
+    .bind(now)
+    .bind(now + Duration::minutes(config.token_ttl_minutes))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let link = match &config.reset_url {
+        Some(url) => format!("{}?token={}", url, token),
+        None => format!("Reset token: {}", token),
+    };
+    notifier
+        .send(Notification {
+            channel: NotificationChannel::Email,
+            recipient,
+            subject: "Reset your RideNow password".to_string(),
+            body: format!(
+                "Someone asked to reset the password of your RideNow account.\n\n{}\n\n\
+                This link expires in {} minutes and works once. If you did not ask for it, ignore this message.",
+                link, config.token_ttl_minutes
+            ),
+        })
+        .await
+        .map_err(AppError::Internal)?;
+
+    info!(account_id = %account_id, "Password reset token issued");
+
+    Ok(())
+}
+
+/// Sets a new password with a reset token and signs the account out
+/// everywhere.
+pub async fn reset_password_service(
+    pool: &PgPool,
+    payload: ResetPasswordRequest,
+) -> Result<(), AppError> {
+    if payload.new_password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    let password_hash = hash_password(&payload.new_password)
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?;
+    let now = Utc::now();
+
+    let mut tx = pool.begin().await?;
+
+    let claimed = sqlx::query(
+        "UPDATE password_reset_tokens SET used_at = $2 \
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2 \
+        RETURNING account_id, account_type",
+    )
+    .bind(hash_token(&payload.token))
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Validation("Invalid or expired reset token".to_string()))?;
+
+    let account_id: String = claimed.get("account_id");
+    let account_type = AccountType::parse(claimed.get("account_type"))?;
+    let table = account_table(&account_type)?;
+
+    let query = format!(
+        "UPDATE {} SET password_hash = $1, updated_at = $2 WHERE id = $3",
+        table
+    );
+    let updated = sqlx::query(&query)
+        .bind(&password_hash)
+        .bind(now)
+        .bind(&account_id)
+        .execute(&mut *tx)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::NotFound("Account no longer exists".to_string()));
+    }
+
+    tx.commit().await?;
+
+    let revoked = revoke_account_sessions(pool, &account_id, "password_reset").await?;
+
+    info!(account_id = %account_id, sessions_revoked = revoked, "Password reset");
+
+    Ok(())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn account_table(account_type: &AccountType) -> Result<&'static str, AppError> {
+    match account_type {
+        AccountType::Rider => Ok("users"),
+        AccountType::Driver => Ok("transport_company_drivers"),
+        AccountType::CompanyAdmin => Err(AppError::Validation(
+            "Password reset is only available to riders and drivers".to_string(),
+        )),
+    }
+}
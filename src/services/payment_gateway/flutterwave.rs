@@ -0,0 +1,338 @@
+use axum::http::HeaderMap;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{Value, json};
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use super::{
+    ChargeRequest, ChargeStatus, ChargeVerification, GatewayFuture, InitializedCharge,
+    PaymentGateway, ProviderReply, RefundOutcome, RefundRequest, RefundResult, WebhookEvent,
+    WebhookKind, event_id, id_value, signature_header,
+};
+use crate::config::env_or;
+use crate::crypto::constant_time_eq;
+use crate::error::AppError;
+use crate::models::money_model::{Currency, Money};
+use crate::models::refund_model::RefundStatus;
+
+#[derive(Debug, Clone)]
+pub struct FlutterwaveConfig {
+    pub secret_key: Option<String>,
+    pub base_url: String,
+    /// The secret hash set on the Flutterwave dashboard. Flutterwave sends it
+    /// back verbatim in the `verif-hash` header of every webhook.
+    pub webhook_hash: Option<String>,
+    /// Where the checkout sends the customer once they have paid.
+    pub redirect_url: Option<String>,
+}
+
+impl FlutterwaveConfig {
+    pub fn from_env() -> Self {
+        FlutterwaveConfig {
+            secret_key: std::env::var("FLUTTERWAVE_SECRET_KEY").ok(),
+            base_url: env_or(
+                "FLUTTERWAVE_BASE_URL",
+                "https://api.flutterwave.com/v3".to_string(),
+            ),
+            webhook_hash: std::env::var("FLUTTERWAVE_WEBHOOK_HASH").ok(),
+            redirect_url: std::env::var("FLUTTERWAVE_REDIRECT_URL").ok(),
+        }
+    }
+}
+
+/// Flutterwave's v3 API. Flutterwave takes amounts in major units, so they
+/// are converted at this boundary, and the transaction reference (`tx_ref`)
+/// is ours rather than the provider's.
+pub struct FlutterwaveGateway {
+    config: FlutterwaveConfig,
+    client: Client,
+}
+
+impl FlutterwaveGateway {
+    pub fn new(config: FlutterwaveConfig) -> Self {
+        FlutterwaveGateway {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        FlutterwaveGateway::new(FlutterwaveConfig::from_env())
+    }
+
+    fn secret_key(&self) -> Result<&str, AppError> {
+        self.config.secret_key.as_deref().ok_or_else(|| {
+            AppError::Internal("FLUTTERWAVE_SECRET_KEY not set in environment".to_string())
+        })
+    }
+
+    /// Sends an authenticated request and returns the body of a successful
+    /// response.
+    async fn send(&self, request: RequestBuilder, action: &str) -> Result<Value, AppError> {
+        match self.call(request, action).await? {
+            ProviderReply::Accepted(body) => Ok(body),
+            ProviderReply::Refused(message) => Err(AppError::PaymentGateway(format!(
+                "Flutterwave {} failed: {}",
+                action, message
+            ))),
+        }
+    }
+
+    /// Sends an authenticated request. Flutterwave answers a 4xx, or
+    /// `{ "status": "error", "message": ... }`, when it refuses a request; an
+    /// `Err` means the outcome is unknown.
+    async fn call(&self, request: RequestBuilder, action: &str) -> Result<ProviderReply, AppError> {
+        let response = request
+            .bearer_auth(self.secret_key()?)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(action, error = %e, "Flutterwave request failed");
+                AppError::PaymentGateway(format!("Flutterwave {} request failed: {}", action, e))
+            })?;
+
+        let http_status = response.status();
+        let body: Value = match response.json().await {
+            Ok(body) => body,
+            Err(_) if http_status.is_client_error() => Value::Null,
+            Err(e) => {
+                error!(action, error = %e, "Failed to parse Flutterwave response");
+                return Err(AppError::PaymentGateway(format!(
+                    "Failed to parse Flutterwave {} response: {}",
+                    action, e
+                )));
+            }
+        };
+
+        if http_status.is_server_error() {
+            error!(action, status = %http_status, "Flutterwave failed to answer");
+            return Err(AppError::PaymentGateway(format!(
+                "Flutterwave {} failed with status {}",
+                action, http_status
+            )));
+        }
+        if !http_status.is_success() || body["status"].as_str() != Some("success") {
+            let message = body["message"].as_str().unwrap_or("request refused");
+            error!(action, status = %http_status, message, "Flutterwave refused the request");
+            return Ok(ProviderReply::Refused(message.to_string()));
+        }
+
+        Ok(ProviderReply::Accepted(body))
+    }
+
+    async fn fetch_transaction(&self, reference: &str) -> Result<Value, AppError> {
+        let request = self
+            .client
+            .get(format!(
+                "{}/transactions/verify_by_reference",
+                self.config.base_url
+            ))
+            .query(&[("tx_ref", reference)]);
+        self.send(request, "verification").await
+    }
+}
+
+impl PaymentGateway for FlutterwaveGateway {
+    fn name(&self) -> &'static str {
+        "flutterwave"
+    }
+
+    fn initialize<'a>(&'a self, charge: &'a ChargeRequest) -> GatewayFuture<'a, InitializedCharge> {
+        Box::pin(async move {
+            let reference = format!("rn_{}", Uuid::new_v4().simple());
+            debug!(email = %charge.email, amount = %charge.amount, reference = %reference, "Initializing Flutterwave payment");
+
+            let mut payload = json!({
+                "tx_ref": reference,
+                "amount": charge.amount.major_units(),
+                "currency": charge.amount.currency.as_str(),
+                "customer": { "email": charge.email },
+            });
+            if let Some(redirect_url) = &self.config.redirect_url {
+                payload["redirect_url"] = json!(redirect_url);
+            }
+            let request = self
+                .client
+                .post(format!("{}/payments", self.config.base_url))
+                .json(&payload);
+            let body = self.send(request, "initialization").await?;
+
+            let authorization_url = body["data"]["link"]
+                .as_str()
+                .ok_or_else(|| {
+                    AppError::PaymentGateway(
+                        "Flutterwave response is missing data.link".to_string(),
+                    )
+                })?
+                .to_string();
+
+            Ok(InitializedCharge {
+                reference,
+                authorization_url,
+                access_code: None,
+            })
+        })
+    }
+
+    fn verify<'a>(&'a self, reference: &'a str) -> GatewayFuture<'a, ChargeVerification> {
+        Box::pin(async move {
+            let body = self.fetch_transaction(reference).await?;
+            let data = &body["data"];
+
+            Ok(ChargeVerification {
+                reference: data["tx_ref"].as_str().unwrap_or(reference).to_string(),
+                status: ChargeStatus::from_provider(data["status"].as_str().unwrap_or_default()),
+                amount: reported_amount(data)?,
+                gateway_response: data["processor_response"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                raw: body,
+            })
+        })
+    }
+
+    /// Flutterwave refunds by its own transaction id, so the charge is looked
+    /// up by reference first.
+    fn refund<'a>(&'a self, refund: &'a RefundRequest) -> GatewayFuture<'a, RefundOutcome> {
+        Box::pin(async move {
+            if self.secret_key().is_err() {
+                return Ok(RefundOutcome::Rejected(
+                    "Flutterwave is not configured".to_string(),
+                ));
+            }
+            // Nothing has been refunded until the refund request is sent.
+            let transaction = match self.fetch_transaction(&refund.reference).await {
+                Ok(transaction) => transaction,
+                Err(e) => return Ok(RefundOutcome::Rejected(e.message().to_string())),
+            };
+            let Some(transaction_id) = transaction["data"]["id"].as_u64() else {
+                return Ok(RefundOutcome::Rejected(
+                    "Flutterwave response is missing data.id".to_string(),
+                ));
+            };
+
+            let mut payload = json!({});
+            if let Some(amount) = refund.amount {
+                payload["amount"] = json!(amount.major_units());
+            }
+            if let Some(reason) = &refund.reason {
+                payload["comments"] = json!(reason);
+            }
+            let request = self
+                .client
+                .post(format!(
+                    "{}/transactions/{}/refund",
+                    self.config.base_url, transaction_id
+                ))
+                .json(&payload);
+            let body = match self.call(request, "refund").await? {
+                ProviderReply::Accepted(body) => body,
+                ProviderReply::Refused(message) => {
+                    return Ok(RefundOutcome::Rejected(format!(
+                        "Flutterwave refund failed: {}",
+                        message
+                    )));
+                }
+            };
+
+            Ok(RefundOutcome::Accepted(RefundResult {
+                refund_id: id_value(&body["data"]["id"]).ok_or_else(|| {
+                    AppError::PaymentGateway("Flutterwave response is missing data.id".to_string())
+                })?,
+                status: RefundStatus::from_provider(
+                    body["data"]["status"].as_str().unwrap_or("pending"),
+                ),
+                raw: body,
+            }))
+        })
+    }
+
+    /// Flutterwave does not sign the body; it echoes the configured secret
+    /// hash in `verif-hash`.
+    fn verify_webhook(&self, headers: &HeaderMap, _body: &[u8]) -> Result<(), AppError> {
+        let expected = self.config.webhook_hash.as_deref().ok_or_else(|| {
+            AppError::Internal("FLUTTERWAVE_WEBHOOK_HASH not set in environment".to_string())
+        })?;
+        let hash = signature_header(headers, "verif-hash")?;
+        if !constant_time_eq(hash.as_bytes(), expected.as_bytes()) {
+            return Err(AppError::Unauthorized(
+                "Invalid webhook signature".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Anyone who learns the `verif-hash` secret can forge deliveries, so a
+    /// successful charge is only applied as Flutterwave reports it when asked
+    /// directly.
+    fn confirm_webhook<'a>(&'a self, mut event: WebhookEvent) -> GatewayFuture<'a, WebhookEvent> {
+        Box::pin(async move {
+            if event.kind != WebhookKind::Charge(ChargeStatus::Success) {
+                return Ok(event);
+            }
+
+            let verification = self.verify(&event.reference).await?;
+            if verification.status != ChargeStatus::Success {
+                warn!(
+                    reference = %event.reference,
+                    status = verification.status.as_str(),
+                    "Flutterwave does not confirm the webhook's successful charge"
+                );
+            }
+            event.kind = WebhookKind::Charge(verification.status);
+            event.amount = Some(verification.amount);
+            event.gateway_response = Some(verification.gateway_response);
+            Ok(event)
+        })
+    }
+
+    fn parse_webhook(&self, payload: Value) -> Result<WebhookEvent, AppError> {
+        let event = payload["event"].as_str().unwrap_or_default().to_string();
+        let reference = payload["data"]["tx_ref"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        if reference.is_empty() {
+            return Err(AppError::Validation(
+                "Webhook payload missing tx_ref".to_string(),
+            ));
+        }
+
+        let kind = match event.as_str() {
+            "charge.completed" => WebhookKind::Charge(ChargeStatus::from_provider(
+                payload["data"]["status"].as_str().unwrap_or("pending"),
+            )),
+            _ => WebhookKind::Other,
+        };
+        let gateway_response = payload["data"]["processor_response"]
+            .as_str()
+            .map(|value| value.to_string());
+        let amount = reported_amount(&payload["data"]).ok();
+
+        Ok(WebhookEvent {
+            event_id: event_id(&event, &payload["data"], &payload),
+            event,
+            kind,
+            reference,
+            refund_id: None,
+            amount,
+            gateway_response,
+            payload,
+        })
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Flutterwave reports amounts as JSON numbers in major units.
+fn reported_amount(data: &Value) -> Result<Money, AppError> {
+    let currency = Currency::parse(data["currency"].as_str().unwrap_or_default())
+        .map_err(|e| AppError::PaymentGateway(e.to_string()))?;
+    let amount = data["amount"].as_f64().ok_or_else(|| {
+        AppError::PaymentGateway("Flutterwave response is missing data.amount".to_string())
+    })?;
+    Ok(Money::from_major_units(amount, currency))
+}
@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{Value, json};
+use sha2::Sha512;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+    ChargeRequest, ChargeStatus, ChargeVerification, GatewayFuture, InitializedCharge,
+    PaymentGateway, RefundOutcome, RefundRequest, RefundResult, WebhookEvent,
+    paystack_webhook_event, signature_header,
+};
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::money_model::Money;
+use crate::models::refund_model::RefundStatus;
+
+type HmacSha512 = Hmac<Sha512>;
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    /// How charges end unless the payer's email asks for something else.
+    pub outcome: ChargeStatus,
+    /// How long a charge stays pending before it settles and its webhook
+    /// fires.
+    pub settle_seconds: u64,
+    /// Where to deliver webhooks, e.g. this server's `/payments/webhook`.
+    /// Webhooks are not sent when unset.
+    pub webhook_url: Option<String>,
+    pub webhook_secret: String,
+    /// The reference is appended to build each charge's checkout URL.
+    pub checkout_url: String,
+}
+
+impl MockConfig {
+    pub fn from_env() -> Self {
+        MockConfig {
+            outcome: std::env::var("MOCK_PAYMENT_OUTCOME")
+                .ok()
+                .and_then(|value| parse_outcome(&value))
+                .unwrap_or(ChargeStatus::Success),
+            settle_seconds: env_or("MOCK_PAYMENT_SETTLE_SECONDS", 2),
+            webhook_url: std::env::var("MOCK_PAYMENT_WEBHOOK_URL").ok(),
+            webhook_secret: env_or(
+                "MOCK_PAYMENT_WEBHOOK_SECRET",
+                "mock_webhook_secret".to_string(),
+            ),
+            checkout_url: env_or(
+                "MOCK_PAYMENT_CHECKOUT_URL",
+                "http://localhost:3000/mock-checkout".to_string(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockCharge {
+    amount: Money,
+    outcome: ChargeStatus,
+    settles_at: DateTime<Utc>,
+    /// Minor units refunded so far.
+    refunded: i64,
+}
+
+impl MockCharge {
+    fn status(&self, now: DateTime<Utc>) -> ChargeStatus {
+        if now < self.settles_at {
+            ChargeStatus::Pending
+        } else if self.outcome == ChargeStatus::Success && self.refunded >= self.amount.minor_units
+        {
+            ChargeStatus::Reversed
+        } else {
+            self.outcome
+        }
+    }
+}
+
+/// An in-process gateway for development and tests. Charges are kept in
+/// memory and settle after `settle_seconds`; a payer email with a plus tag,
+/// e.g. `rider+failed@example.com`, picks that charge's outcome.
+pub struct MockGateway {
+    config: MockConfig,
+    client: Client,
+    charges: Arc<Mutex<HashMap<String, MockCharge>>>,
+}
+
+impl MockGateway {
+    pub fn new(config: MockConfig) -> Self {
+        MockGateway {
+            config,
+            client: Client::new(),
+            charges: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        MockGateway::new(MockConfig::from_env())
+    }
+
+    /// Posts a signed `charge.*` webhook once the charge settles. Pending and
+    /// abandoned charges never notify, as with the live providers.
+    fn schedule_webhook(&self, reference: &str, charge: &MockCharge) {
+        let event = match charge.outcome {
+            ChargeStatus::Success => "charge.success",
+            ChargeStatus::Failed => "charge.failed",
+            _ => return,
+        };
+
+        self.deliver_webhook(
+            reference,
+            event,
+            json!({
+                "reference": reference,
+                "status": charge.outcome.as_str(),
+                "amount": charge.amount.minor_units,
+                "currency": charge.amount.currency.as_str(),
+                "gateway_response": gateway_response(charge.outcome),
+            }),
+        );
+    }
+
+    /// Signs `{ "event": event, "data": data }` and posts it after
+    /// `settle_seconds`. Does nothing when no webhook URL is configured.
+    fn deliver_webhook(&self, reference: &str, event: &'static str, data: Value) {
+        let Some(url) = self.config.webhook_url.clone() else {
+            return;
+        };
+
+        let body = json!({ "event": event, "data": data }).to_string();
+        let signature = sign(&self.config.webhook_secret, body.as_bytes());
+        let client = self.client.clone();
+        let delay = Duration::from_secs(self.config.settle_seconds);
+        let reference = reference.to_string();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let result = client
+                .post(&url)
+                .header("content-type", "application/json")
+                .header("x-mock-signature", signature)
+                .body(body)
+                .send()
+                .await;
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    info!(reference = %reference, event, "Mock webhook delivered");
+                }
+                Ok(response) => {
+                    warn!(reference = %reference, status = %response.status(), "Mock webhook rejected");
+                }
+                Err(e) => {
+                    warn!(reference = %reference, error = %e, "Mock webhook delivery failed");
+                }
+            }
+        });
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, MockCharge>>, AppError> {
+        self.charges
+            .lock()
+            .map_err(|_| AppError::Internal("Mock gateway state is poisoned".to_string()))
+    }
+}
+
+impl PaymentGateway for MockGateway {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn initialize<'a>(&'a self, charge: &'a ChargeRequest) -> GatewayFuture<'a, InitializedCharge> {
+        Box::pin(async move {
+            let reference = format!("mock_{}", Uuid::new_v4().simple());
+            let outcome = email_outcome(&charge.email).unwrap_or(self.config.outcome);
+            let mock_charge = MockCharge {
+                amount: charge.amount,
+                outcome,
+                settles_at: Utc::now()
+                    + chrono::Duration::seconds(self.config.settle_seconds as i64),
+                refunded: 0,
+            };
+
+            self.schedule_webhook(&reference, &mock_charge);
+            self.lock()?.insert(reference.clone(), mock_charge);
+            info!(reference = %reference, outcome = outcome.as_str(), "Mock charge initialized");
+
+            Ok(InitializedCharge {
+                authorization_url: format!("{}/{}", self.config.checkout_url, reference),
+                access_code: Some(Uuid::new_v4().simple().to_string()),
+                reference,
+            })
+        })
+    }
+
+    fn verify<'a>(&'a self, reference: &'a str) -> GatewayFuture<'a, ChargeVerification> {
+        Box::pin(async move {
+            let charge = self.lock()?.get(reference).cloned().ok_or_else(|| {
+                AppError::PaymentGateway(format!("Unknown mock reference: {}", reference))
+            })?;
+            let status = charge.status(Utc::now());
+
+            Ok(ChargeVerification {
+                reference: reference.to_string(),
+                status,
+                amount: charge.amount,
+                gateway_response: gateway_response(status).to_string(),
+                raw: json!({
+                    "reference": reference,
+                    "status": status.as_str(),
+                    "amount": charge.amount.minor_units,
+                    "currency": charge.amount.currency.as_str(),
+                    "refunded": charge.refunded,
+                }),
+            })
+        })
+    }
+
+    fn refund<'a>(&'a self, refund: &'a RefundRequest) -> GatewayFuture<'a, RefundOutcome> {
+        Box::pin(async move {
+            let mut charges = self.lock()?;
+            let Some(charge) = charges.get_mut(&refund.reference) else {
+                return Ok(RefundOutcome::Rejected(format!(
+                    "Unknown mock reference: {}",
+                    refund.reference
+                )));
+            };
+            if charge.status(Utc::now()) != ChargeStatus::Success {
+                return Ok(RefundOutcome::Rejected(
+                    "Only settled, successful charges can be refunded".to_string(),
+                ));
+            }
+
+            let remaining = charge.amount.minor_units - charge.refunded;
+            let amount = match refund.amount {
+                Some(amount) if amount.currency != charge.amount.currency => {
+                    return Ok(RefundOutcome::Rejected(format!(
+                        "Refund must be in {}",
+                        charge.amount.currency
+                    )));
+                }
+                Some(amount) => amount.minor_units,
+                None => remaining,
+            };
+            if amount <= 0 || amount > remaining {
+                return Ok(RefundOutcome::Rejected(format!(
+                    "Refund amount must be between 1 and {}",
+                    remaining
+                )));
+            }
+            charge.refunded += amount;
+            let currency = charge.amount.currency;
+            drop(charges);
+
+            // With webhooks on, refunds complete through `refund.processed`
+            // like Paystack's; otherwise they complete immediately.
+            let refund_id = format!("mock_rf_{}", Uuid::new_v4().simple());
+            let status = if self.config.webhook_url.is_some() {
+                self.deliver_webhook(
+                    &refund.reference,
+                    "refund.processed",
+                    json!({
+                        "id": refund_id,
+                        "transaction_reference": refund.reference,
+                        "status": "processed",
+                        "amount": amount.to_string(),
+                        "currency": currency.as_str(),
+                    }),
+                );
+                RefundStatus::Pending
+            } else {
+                RefundStatus::Processed
+            };
+            info!(reference = %refund.reference, refund_id = %refund_id, amount, status = status.as_str(), "Mock refund accepted");
+
+            Ok(RefundOutcome::Accepted(RefundResult {
+                raw: json!({
+                    "id": refund_id,
+                    "transaction": refund.reference,
+                    "amount": amount,
+                    "currency": currency.as_str(),
+                    "status": status.as_str(),
+                }),
+                refund_id,
+                status,
+            }))
+        })
+    }
+
+    fn verify_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), AppError> {
+        let signature = signature_header(headers, "x-mock-signature")?;
+        let signature = hex::decode(signature)
+            .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))?;
+
+        let mut mac = HmacSha512::new_from_slice(self.config.webhook_secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))
+    }
+
+    fn parse_webhook(&self, payload: Value) -> Result<WebhookEvent, AppError> {
+        paystack_webhook_event(payload)
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn parse_outcome(value: &str) -> Option<ChargeStatus> {
+    match value.to_ascii_lowercase().as_str() {
+        "success" => Some(ChargeStatus::Success),
+        "failed" => Some(ChargeStatus::Failed),
+        "abandoned" => Some(ChargeStatus::Abandoned),
+        "pending" => Some(ChargeStatus::Pending),
+        _ => None,
+    }
+}
+
+/// Reads the outcome from a plus tag, e.g. `rider+abandoned@example.com`.
+fn email_outcome(email: &str) -> Option<ChargeStatus> {
+    let local = email.split('@').next()?;
+    let (_, tag) = local.split_once('+')?;
+    parse_outcome(tag)
+}
+
+fn gateway_response(status: ChargeStatus) -> &'static str {
+    match status {
+        ChargeStatus::Success => "Approved",
+        ChargeStatus::Failed => "Declined",
+        ChargeStatus::Abandoned => "The customer did not complete the payment",
+        ChargeStatus::Reversed => "Refunded",
+        ChargeStatus::Pending => "Awaiting payment",
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha512::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::money_model::Currency;
+
+    fn gateway() -> MockGateway {
+        MockGateway::new(MockConfig {
+            outcome: ChargeStatus::Success,
+            settle_seconds: 0,
+            webhook_url: None,
+            webhook_secret: "secret".to_string(),
+            checkout_url: "http://localhost/checkout".to_string(),
+        })
+    }
+
+    async fn initialize(gateway: &MockGateway, email: &str) -> InitializedCharge {
+        gateway
+            .initialize(&ChargeRequest {
+                email: email.to_string(),
+                amount: Money::new(50_000, Currency::NGN),
+            })
+            .await
+            .unwrap()
+    }
+
+    async fn refund(gateway: &MockGateway, reference: &str, amount: Option<i64>) -> RefundOutcome {
+        gateway
+            .refund(&RefundRequest {
+                reference: reference.to_string(),
+                amount: amount.map(|amount| Money::new(amount, Currency::NGN)),
+                reason: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn initialize_verify_and_refund_a_charge() {
+        let gateway = gateway();
+        let charge = initialize(&gateway, "rider@example.com").await;
+        assert!(charge.reference.starts_with("mock_"));
+        assert!(charge.authorization_url.ends_with(&charge.reference));
+
+        let verified = gateway.verify(&charge.reference).await.unwrap();
+        assert_eq!(verified.status, ChargeStatus::Success);
+        assert_eq!(verified.amount, Money::new(50_000, Currency::NGN));
+
+        let RefundOutcome::Accepted(partial) =
+            refund(&gateway, &charge.reference, Some(20_000)).await
+        else {
+            panic!("partial refund was rejected");
+        };
+        assert_eq!(partial.status, RefundStatus::Processed);
+        let verified = gateway.verify(&charge.reference).await.unwrap();
+        assert_eq!(verified.status, ChargeStatus::Success);
+
+        let RefundOutcome::Rejected(_) = refund(&gateway, &charge.reference, Some(40_000)).await
+        else {
+            panic!("refund above the remaining amount was accepted");
+        };
+
+        let RefundOutcome::Accepted(_) = refund(&gateway, &charge.reference, None).await else {
+            panic!("refund of the remainder was rejected");
+        };
+        let verified = gateway.verify(&charge.reference).await.unwrap();
+        assert_eq!(verified.status, ChargeStatus::Reversed);
+    }
+
+    #[tokio::test]
+    async fn failed_charges_cannot_be_refunded() {
+        let gateway = gateway();
+        let charge = initialize(&gateway, "rider+failed@example.com").await;
+
+        let verified = gateway.verify(&charge.reference).await.unwrap();
+        assert_eq!(verified.status, ChargeStatus::Failed);
+
+        let RefundOutcome::Rejected(_) = refund(&gateway, &charge.reference, None).await else {
+            panic!("refund of a failed charge was accepted");
+        };
+    }
+
+    #[tokio::test]
+    async fn unknown_references_are_errors() {
+        assert!(gateway().verify("mock_missing").await.is_err());
+    }
+}
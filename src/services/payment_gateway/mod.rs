@@ -0,0 +1,456 @@
+mod flutterwave;
+mod mock;
+mod paystack;
+
+pub use flutterwave::FlutterwaveGateway;
+pub use mock::MockGateway;
+pub use paystack::PaystackGateway;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::models::money_model::{Currency, Money};
+use crate::models::refund_model::RefundStatus;
+
+/// Where a charge stands at the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChargeStatus {
+    Pending,
+    Success,
+    Failed,
+    /// The customer left the checkout without paying.
+    Abandoned,
+    /// The charge was paid and later refunded in full.
+    Reversed,
+}
+
+impl ChargeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChargeStatus::Pending => "pending",
+            ChargeStatus::Success => "success",
+            ChargeStatus::Failed => "failed",
+            ChargeStatus::Abandoned => "abandoned",
+            ChargeStatus::Reversed => "reversed",
+        }
+    }
+
+    /// Maps a provider's status string. Anything still in flight, e.g.
+    /// `ongoing` or `processing`, is pending.
+    pub fn from_provider(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "success" | "successful" => ChargeStatus::Success,
+            "failed" => ChargeStatus::Failed,
+            "abandoned" => ChargeStatus::Abandoned,
+            "reversed" => ChargeStatus::Reversed,
+            _ => ChargeStatus::Pending,
+        }
+    }
+
+    /// The stored statuses a payment may be in to move to this one. Payments
+    /// only move forward, so a late `charge.failed` cannot undo a success;
+    /// repeating the current status refreshes its details.
+    pub fn previous_states(&self) -> &'static [&'static str] {
+        match self {
+            ChargeStatus::Pending => &["pending"],
+            ChargeStatus::Success => &["pending", "abandoned", "failed", "success"],
+            ChargeStatus::Failed => &["pending", "abandoned", "failed"],
+            ChargeStatus::Abandoned => &["pending", "abandoned"],
+            ChargeStatus::Reversed => &["success", "reversed"],
+        }
+    }
+}
+
+/// A payment the customer is about to be asked for.
+#[derive(Debug, Clone)]
+pub struct ChargeRequest {
+    pub email: String,
+    pub amount: Money,
+}
+
+/// A charge opened at the provider, waiting for the customer at the checkout
+/// page.
+#[derive(Debug, Clone)]
+pub struct InitializedCharge {
+    pub reference: String,
+    pub authorization_url: String,
+    /// Paystack's checkout token; not every provider issues one.
+    pub access_code: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChargeVerification {
+    pub reference: String,
+    pub status: ChargeStatus,
+    /// The amount and currency the provider charged.
+    pub amount: Money,
+    pub gateway_response: String,
+    /// The provider's response, kept with the payment for audits.
+    pub raw: Value,
+}
+
+/// Gives back all of a settled charge, or part of it.
+#[derive(Debug, Clone)]
+pub struct RefundRequest {
+    pub reference: String,
+    /// The whole charge when `None`.
+    pub amount: Option<Money>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RefundResult {
+    /// The provider's id for the refund.
+    pub refund_id: String,
+    pub status: RefundStatus,
+    pub raw: Value,
+}
+
+/// How a refund request ended. An `Err` from [`PaymentGateway::refund`]
+/// means the outcome is unknown, e.g. the request timed out after the
+/// provider may have accepted it.
+#[derive(Debug, Clone)]
+pub enum RefundOutcome {
+    Accepted(RefundResult),
+    /// No refund was made: the provider refused it, or it was never sent.
+    Rejected(String),
+}
+
+/// How a provider answered a request that reached it.
+enum ProviderReply {
+    Accepted(Value),
+    /// The provider's reason for refusing the request.
+    Refused(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookKind {
+    /// A charge settled, failed or was reversed.
+    Charge(ChargeStatus),
+    /// A refund moved forward.
+    Refund(RefundStatus),
+    /// Anything we do not act on yet.
+    Other,
+}
+
+/// A webhook delivery whose signature has been checked.
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    /// The provider's event name, e.g. `charge.success`.
+    pub event: String,
+    /// Identifies the event across redeliveries: the event name and the
+    /// provider's id for its object, or a hash of the payload without one.
+    pub event_id: String,
+    pub kind: WebhookKind,
+    /// The charge's reference, also for refund events.
+    pub reference: String,
+    /// The provider's id for the refund, on refund events.
+    pub refund_id: Option<String>,
+    /// The amount the provider reports for the charge or refund, when it
+    /// sends one.
+    pub amount: Option<Money>,
+    pub gateway_response: Option<String>,
+    pub payload: Value,
+}
+
+pub type GatewayFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AppError>> + Send + 'a>>;
+
+/// A payment provider. `payment_service` only talks to providers through
+/// this trait; [`PaystackGateway`] and [`FlutterwaveGateway`] are the live
+/// ones and [`MockGateway`] runs in-process for development and tests.
+pub trait PaymentGateway: Send + Sync {
+    /// Short provider name, e.g. `paystack`.
+    fn name(&self) -> &'static str;
+
+    fn initialize<'a>(&'a self, charge: &'a ChargeRequest) -> GatewayFuture<'a, InitializedCharge>;
+
+    fn verify<'a>(&'a self, reference: &'a str) -> GatewayFuture<'a, ChargeVerification>;
+
+    fn refund<'a>(&'a self, refund: &'a RefundRequest) -> GatewayFuture<'a, RefundOutcome>;
+
+    /// Checks a webhook delivery's signature.
+    fn verify_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), AppError>;
+
+    /// Reads the event out of a delivery whose signature has been checked,
+    /// when it arrived or, for a replay, when it was stored.
+    fn parse_webhook(&self, payload: Value) -> Result<WebhookEvent, AppError>;
+
+    /// Checks a parsed event with the provider before it is applied. The
+    /// default trusts the signature; providers whose signature does not
+    /// cover the body look the charge up again.
+    fn confirm_webhook<'a>(&'a self, event: WebhookEvent) -> GatewayFuture<'a, WebhookEvent> {
+        Box::pin(async move { Ok(event) })
+    }
+}
+
+/// Shared handle to the enabled gateways, passed to handlers as an
+/// extension. Charges go to the payer's company gateway when it has one,
+/// then to the gateway routed for the currency, then to the default.
+#[derive(Clone)]
+pub struct PaymentProvider {
+    inner: Arc<Gateways>,
+}
+
+struct Gateways {
+    gateways: HashMap<&'static str, Arc<dyn PaymentGateway>>,
+    default: &'static str,
+    /// Currency code to gateway name.
+    currency_routes: HashMap<String, &'static str>,
+}
+
+impl PaymentProvider {
+    /// Enables `PAYMENT_GATEWAY` as the default (`paystack`, `flutterwave` or
+    /// `mock`) and routes currencies per `PAYMENT_GATEWAY_BY_CURRENCY`, e.g.
+    /// `GHS=flutterwave,KES=flutterwave`. Paystack and Flutterwave are always
+    /// enabled; the mock gateway only when it is the default.
+    pub fn from_env() -> Self {
+        let mut gateways: HashMap<&'static str, Arc<dyn PaymentGateway>> = HashMap::new();
+        gateways.insert("paystack", Arc::new(PaystackGateway::from_env()));
+        gateways.insert("flutterwave", Arc::new(FlutterwaveGateway::from_env()));
+
+        let default = match std::env::var("PAYMENT_GATEWAY").as_deref() {
+            Ok("mock") => {
+                gateways.insert("mock", Arc::new(MockGateway::from_env()));
+                "mock"
+            }
+            Ok("flutterwave") => "flutterwave",
+            Ok("paystack") | Err(_) => "paystack",
+            Ok(other) => {
+                warn!(gateway = %other, "Unknown PAYMENT_GATEWAY, using paystack");
+                "paystack"
+            }
+        };
+
+        let mut currency_routes = HashMap::new();
+        let routes = std::env::var("PAYMENT_GATEWAY_BY_CURRENCY").unwrap_or_default();
+        for route in routes.split(',').filter(|route| !route.trim().is_empty()) {
+            let Some((currency, name)) = route.split_once('=') else {
+                warn!(route = %route, "Ignoring malformed PAYMENT_GATEWAY_BY_CURRENCY entry");
+                continue;
+            };
+            match gateways.get_key_value(name.trim()) {
+                Some((name, _)) => {
+                    currency_routes.insert(currency.trim().to_ascii_uppercase(), *name);
+                }
+                None => {
+                    warn!(gateway = %name, "Ignoring currency route to a gateway that is not enabled")
+                }
+            }
+        }
+
+        info!(default, routes = ?currency_routes, "Payment gateways configured");
+        PaymentProvider {
+            inner: Arc::new(Gateways {
+                gateways,
+                default,
+                currency_routes,
+            }),
+        }
+    }
+
+    pub fn default_gateway(&self) -> &dyn PaymentGateway {
+        self.inner.gateways[self.inner.default].as_ref()
+    }
+
+    /// Looks up an enabled gateway by name, e.g. the one stored on a payment.
+    pub fn gateway(&self, name: &str) -> Result<&dyn PaymentGateway, AppError> {
+        self.inner
+            .gateways
+            .get(name)
+            .map(|gateway| gateway.as_ref())
+            .ok_or_else(|| AppError::Validation(format!("Payment gateway {} is not enabled", name)))
+    }
+
+    /// Picks the gateway for a new charge.
+    pub fn select(
+        &self,
+        company_gateway: Option<&str>,
+        currency: &str,
+    ) -> Result<&dyn PaymentGateway, AppError> {
+        if let Some(name) = company_gateway {
+            return self.gateway(name);
+        }
+        match self
+            .inner
+            .currency_routes
+            .get(&currency.to_ascii_uppercase())
+        {
+            Some(name) => self.gateway(name),
+            None => Ok(self.default_gateway()),
+        }
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Reads a notification shaped like Paystack's, e.g.
+/// `{ "event": "charge.success", "data": { "reference": ..., "status": ... } }`.
+/// Refund events name the charge in `data.transaction_reference`.
+fn paystack_webhook_event(payload: Value) -> Result<WebhookEvent, AppError> {
+    let event = payload["event"].as_str().unwrap_or_default().to_string();
+    let data = &payload["data"];
+    let status = data["status"].as_str().unwrap_or("pending");
+
+    let (kind, reference, refund_id) = match event.as_str() {
+        "charge.success" => (
+            WebhookKind::Charge(ChargeStatus::Success),
+            &data["reference"],
+            None,
+        ),
+        "charge.failed" => (
+            WebhookKind::Charge(ChargeStatus::Failed),
+            &data["reference"],
+            None,
+        ),
+        event if event.starts_with("charge.") => (
+            WebhookKind::Charge(ChargeStatus::from_provider(status)),
+            &data["reference"],
+            None,
+        ),
+        event if event.starts_with("refund.") => (
+            WebhookKind::Refund(RefundStatus::from_provider(
+                event.trim_start_matches("refund."),
+            )),
+            &data["transaction_reference"],
+            id_value(&data["id"]),
+        ),
+        _ => (WebhookKind::Other, &data["reference"], None),
+    };
+    let reference = reference.as_str().unwrap_or_default().to_string();
+    if reference.is_empty() {
+        return Err(AppError::Validation(
+            "Webhook payload missing reference".to_string(),
+        ));
+    }
+
+    let gateway_response = data["gateway_response"]
+        .as_str()
+        .map(|value| value.to_string());
+    // Refund notifications send the amount as a string
+    let amount = match (
+        data["amount"]
+            .as_i64()
+            .or_else(|| data["amount"].as_str().and_then(|value| value.parse().ok())),
+        data["currency"].as_str(),
+    ) {
+        (Some(amount), Some(currency)) => Currency::parse(currency)
+            .ok()
+            .map(|currency| Money::new(amount, currency)),
+        _ => None,
+    };
+
+    Ok(WebhookEvent {
+        event_id: event_id(&event, data, &payload),
+        event,
+        kind,
+        reference,
+        refund_id,
+        amount,
+        gateway_response,
+        payload,
+    })
+}
+
+fn event_id(event: &str, data: &Value, payload: &Value) -> String {
+    match id_value(&data["id"]) {
+        Some(id) => format!("{}:{}", event, id),
+        None => hex::encode(Sha256::digest(payload.to_string().as_bytes())),
+    }
+}
+
+/// Provider ids are numbers on some endpoints and strings on others.
+fn id_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn signature_header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, AppError> {
+    headers
+        .get(name)
+        .ok_or_else(|| AppError::Unauthorized(format!("Missing {} header", name)))?
+        .to_str()
+        .map_err(|_| AppError::Unauthorized(format!("Invalid {} header", name)))
+}
+
+pub fn parse_payload(body: &[u8]) -> Result<Value, AppError> {
+    serde_json::from_slice(body)
+        .map_err(|e| AppError::Validation(format!("Invalid webhook payload: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payments_only_move_forward() {
+        assert!(!ChargeStatus::Failed.previous_states().contains(&"success"));
+        assert!(
+            !ChargeStatus::Abandoned
+                .previous_states()
+                .contains(&"failed")
+        );
+        assert!(!ChargeStatus::Pending.previous_states().contains(&"success"));
+        assert!(
+            !ChargeStatus::Success
+                .previous_states()
+                .contains(&"reversed")
+        );
+    }
+
+    #[test]
+    fn a_failed_or_abandoned_checkout_can_still_succeed() {
+        let previous = ChargeStatus::Success.previous_states();
+        assert!(previous.contains(&"pending"));
+        assert!(previous.contains(&"failed"));
+        assert!(previous.contains(&"abandoned"));
+    }
+
+    #[test]
+    fn only_successful_payments_are_reversed() {
+        assert_eq!(
+            ChargeStatus::Reversed.previous_states(),
+            &["success", "reversed"]
+        );
+    }
+
+    #[test]
+    fn repeating_the_current_status_is_allowed() {
+        for status in [
+            ChargeStatus::Pending,
+            ChargeStatus::Success,
+            ChargeStatus::Failed,
+            ChargeStatus::Abandoned,
+            ChargeStatus::Reversed,
+        ] {
+            assert!(status.previous_states().contains(&status.as_str()));
+        }
+    }
+
+    #[test]
+    fn reads_provider_statuses() {
+        assert_eq!(
+            ChargeStatus::from_provider("successful"),
+            ChargeStatus::Success
+        );
+        assert_eq!(ChargeStatus::from_provider("FAILED"), ChargeStatus::Failed);
+        assert_eq!(
+            ChargeStatus::from_provider("ongoing"),
+            ChargeStatus::Pending
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{Value, json};
+use sha2::Sha512;
+use tracing::{debug, error};
+
+use super::{
+    ChargeRequest, ChargeStatus, ChargeVerification, GatewayFuture, InitializedCharge,
+    PaymentGateway, ProviderReply, RefundOutcome, RefundRequest, RefundResult, WebhookEvent,
+    id_value, paystack_webhook_event, signature_header,
+};
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::money_model::{Currency, Money};
+use crate::models::refund_model::RefundStatus;
+
+type HmacSha512 = Hmac<Sha512>;
+
+#[derive(Debug, Clone)]
+pub struct PaystackConfig {
+    /// Secret key. Paystack also signs webhooks with it.
+    pub secret_key: Option<String>,
+    pub base_url: String,
+    pub initialize_url: String,
+    /// Prefix the transaction reference is appended to.
+    pub verify_url: String,
+}
+
+impl PaystackConfig {
+    pub fn from_env() -> Self {
+        let base_url = env_or("PAYSTACK_BASE_URL", "https://api.paystack.co".to_string());
+        PaystackConfig {
+            secret_key: std::env::var("PAYSTACK_API_KEY").ok(),
+            initialize_url: env_or(
+                "PAYSTACK_INITIALIZE_URL",
+                format!("{}/transaction/initialize", base_url),
+            ),
+            verify_url: env_or(
+                "PAYSTACK_TRANS_VERIFY_URL",
+                format!("{}/transaction/verify/", base_url),
+            ),
+            base_url,
+        }
+    }
+}
+
+/// Paystack's REST API.
+pub struct PaystackGateway {
+    config: PaystackConfig,
+    client: Client,
+}
+
+impl PaystackGateway {
+    pub fn new(config: PaystackConfig) -> Self {
+        PaystackGateway {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        PaystackGateway::new(PaystackConfig::from_env())
+    }
+
+    fn secret_key(&self) -> Result<&str, AppError> {
+        self.config.secret_key.as_deref().ok_or_else(|| {
+            AppError::Internal("PAYSTACK_API_KEY not set in environment".to_string())
+        })
+    }
+
+    /// Sends an authenticated request and returns the body of a successful
+    /// response.
+    async fn send(&self, request: RequestBuilder, action: &str) -> Result<Value, AppError> {
+        match self.call(request, action).await? {
+            ProviderReply::Accepted(body) => Ok(body),
+            ProviderReply::Refused(message) => Err(AppError::PaymentGateway(format!(
+                "Paystack {} failed: {}",
+                action, message
+            ))),
+        }
+    }
+
+    /// Sends an authenticated request. Paystack answers a 4xx, or
+    /// `{ "status": false, "message": ... }`, when it refuses a request; an
+    /// `Err` means the outcome is unknown.
+    async fn call(&self, request: RequestBuilder, action: &str) -> Result<ProviderReply, AppError> {
+        let response = request
+            .bearer_auth(self.secret_key()?)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(action, error = %e, "Paystack request failed");
+                AppError::PaymentGateway(format!("Paystack {} request failed: {}", action, e))
+            })?;
+
+        let http_status = response.status();
+        let body: Value = match response.json().await {
+            Ok(body) => body,
+            Err(_) if http_status.is_client_error() => Value::Null,
+            Err(e) => {
+                error!(action, error = %e, "Failed to parse Paystack response");
+                return Err(AppError::PaymentGateway(format!(
+                    "Failed to parse Paystack {} response: {}",
+                    action, e
+                )));
+            }
+        };
+
+        if http_status.is_server_error() {
+            error!(action, status = %http_status, "Paystack failed to answer");
+            return Err(AppError::PaymentGateway(format!(
+                "Paystack {} failed with status {}",
+                action, http_status
+            )));
+        }
+        if !http_status.is_success() || body["status"].as_bool() != Some(true) {
+            let message = body["message"].as_str().unwrap_or("request refused");
+            error!(action, status = %http_status, message, "Paystack refused the request");
+            return Ok(ProviderReply::Refused(message.to_string()));
+        }
+
+        Ok(ProviderReply::Accepted(body))
+    }
+}
+
+impl PaymentGateway for PaystackGateway {
+    fn name(&self) -> &'static str {
+        "paystack"
+    }
+
+    fn initialize<'a>(&'a self, charge: &'a ChargeRequest) -> GatewayFuture<'a, InitializedCharge> {
+        Box::pin(async move {
+            debug!(email = %charge.email, amount = %charge.amount, "Initializing Paystack transaction");
+            let request = self.client.post(&self.config.initialize_url).json(&json!({
+                "email": charge.email,
+                "amount": charge.amount.minor_units.to_string(),
+                "currency": charge.amount.currency.as_str(),
+            }));
+            let body = self.send(request, "initialization").await?;
+
+            Ok(InitializedCharge {
+                reference: string_field(&body, "reference")?,
+                authorization_url: string_field(&body, "authorization_url")?,
+                access_code: Some(string_field(&body, "access_code")?),
+            })
+        })
+    }
+
+    fn verify<'a>(&'a self, reference: &'a str) -> GatewayFuture<'a, ChargeVerification> {
+        Box::pin(async move {
+            let url = format!("{}{}", self.config.verify_url, reference);
+            let body = self.send(self.client.get(&url), "verification").await?;
+
+            Ok(ChargeVerification {
+                reference: string_field(&body, "reference")?,
+                status: ChargeStatus::from_provider(&string_field(&body, "status")?),
+                amount: Money::new(
+                    body["data"]["amount"].as_i64().unwrap_or_default(),
+                    Currency::parse(&string_field(&body, "currency")?)
+                        .map_err(|e| AppError::PaymentGateway(e.to_string()))?,
+                ),
+                gateway_response: body["data"]["gateway_response"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                raw: body,
+            })
+        })
+    }
+
+    fn refund<'a>(&'a self, refund: &'a RefundRequest) -> GatewayFuture<'a, RefundOutcome> {
+        Box::pin(async move {
+            let mut payload = json!({ "transaction": refund.reference });
+            if let Some(amount) = refund.amount {
+                payload["amount"] = json!(amount.minor_units);
+            }
+            if let Some(reason) = &refund.reason {
+                payload["merchant_note"] = json!(reason);
+            }
+            let request = self
+                .client
+                .post(format!("{}/refund", self.config.base_url))
+                .json(&payload);
+            if self.secret_key().is_err() {
+                return Ok(RefundOutcome::Rejected(
+                    "Paystack is not configured".to_string(),
+                ));
+            }
+            let body = match self.call(request, "refund").await? {
+                ProviderReply::Accepted(body) => body,
+                ProviderReply::Refused(message) => {
+                    return Ok(RefundOutcome::Rejected(format!(
+                        "Paystack refund failed: {}",
+                        message
+                    )));
+                }
+            };
+
+            Ok(RefundOutcome::Accepted(RefundResult {
+                refund_id: id_field(&body, "id")?,
+                status: RefundStatus::from_provider(&string_field(&body, "status")?),
+                raw: body,
+            }))
+        })
+    }
+
+    fn verify_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), AppError> {
+        let signature = signature_header(headers, "x-paystack-signature")?;
+        let signature = hex::decode(signature)
+            .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))?;
+
+        let mut mac = HmacSha512::new_from_slice(self.secret_key()?.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| AppError::Unauthorized("Invalid webhook signature".to_string()))
+    }
+
+    fn parse_webhook(&self, payload: Value) -> Result<WebhookEvent, AppError> {
+        paystack_webhook_event(payload)
+    }
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+fn string_field(body: &Value, field: &str) -> Result<String, AppError> {
+    body["data"][field]
+        .as_str()
+        .map(|value| value.to_string())
+        .ok_or_else(|| {
+            AppError::PaymentGateway(format!("Paystack response is missing data.{}", field))
+        })
+}
+
+fn id_field(body: &Value, field: &str) -> Result<String, AppError> {
+    id_value(&body["data"][field]).ok_or_else(|| {
+        AppError::PaymentGateway(format!("Paystack response is missing data.{}", field))
+    })
+}
@@ -1,138 +1,122 @@
+use crate::error::AppError;
+use crate::models::money_model::{Currency, Money};
 use crate::models::payment_model::{
     InitializeDataResponse, InitializeRequest, InitializeResponse, PaymentResponse, VerifyResponse,
     VerifyResponseData,
 };
+use crate::models::ride_model::{RideResponse, RideStatus};
+use crate::models::webhook_event_model::WebhookEventStatus;
+use crate::services::payment_gateway::{
+    ChargeRequest, ChargeStatus, InitializedCharge, PaymentProvider, WebhookEvent, WebhookKind,
+};
+use crate::services::refund_service::apply_refund_event;
+use crate::services::ride_service::get_ride_service;
 use chrono::Utc;
-use hex::encode;
-use hmac::{Hmac, Mac};
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
-use serde::Serialize;
 use serde_json::Value;
-use sha2::Sha512;
 use sqlx::{PgPool, Row, types::Json};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-type HmacSha512 = Hmac<Sha512>;
-
-#[derive(Serialize)]
-struct PaystackInitializePayload {
-    email: String,
-    amount: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    currency: Option<String>,
-}
-
 // ============================================================================
 // Payment Initialization Services
 // ============================================================================
 
 pub async fn initialize_payment_service(
     pool: &PgPool,
+    provider: &PaymentProvider,
     payload: InitializeRequest,
-) -> Result<InitializeResponse, String> {
-    debug!(email = %payload.email, amount = %payload.amount, "Initializing payment");
-
-    let api_key = std::env::var("PAYSTACK_API_KEY")
-        .map_err(|_| "PAYSTACK_API_KEY not set in environment".to_string())?;
-
-    let url = std::env::var("PAYSTACK_INITIALIZE_URL")
-        .map_err(|_| "PAYSTACK_INITIALIZE_URL not set in environment".to_string())?;
-
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(&format!("Bearer {}", api_key))
-            .map_err(|e| format!("Invalid authorization header: {}", e))?,
-    );
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-    let client = reqwest::Client::new();
-    let paystack_payload = PaystackInitializePayload {
-        email: payload.email.clone(),
-        amount: payload.amount.clone(),
-        currency: payload.currency.clone(),
-    };
-
-    let response = client
-        .post(&url)
-        .headers(headers)
-        .json(&paystack_payload)
-        .send()
+) -> Result<InitializeResponse, AppError> {
+    debug!(email = %payload.email, amount = ?payload.amount, ride_id = ?payload.ride_id, "Initializing payment");
+
+    let payer = resolve_payer(&payload)?;
+    let charge = resolve_charge(pool, &payload).await?;
+    let company_gateway = company_gateway(pool, &payload, &charge).await?;
+    let gateway = provider.select(company_gateway.as_deref(), charge.amount.currency.as_str())?;
+
+    // The row exists before the gateway is asked for a charge, so every
+    // charge the gateway hands out has a payment to land on.
+    let payment_id =
+        insert_pending_payment(pool, &payer, &payload.email, &charge, gateway.name()).await?;
+
+    let initialized = match gateway
+        .initialize(&ChargeRequest {
+            email: payload.email.clone(),
+            amount: charge.amount,
+        })
         .await
-        .map_err(|e| {
-            error!(error = %e, "Failed to send payment initialization request");
-            format!("Payment initialization request failed: {}", e)
-        })?;
-
-    let paystack_res: InitializeResponse = response.json().await.map_err(|e| {
-        error!(error = %e, "Failed to parse payment initialization response");
-        format!("Failed to parse response: {}", e)
-    })?;
-
-    info!(reference = %paystack_res.data.reference, "Payment initialized successfully");
+    {
+        Ok(initialized) => initialized,
+        Err(e) => {
+            mark_payment_failed(pool, &payer, &payment_id).await;
+            return Err(e);
+        }
+    };
 
-    store_initialized_payment(pool, &payload, &paystack_res).await?;
+    info!(reference = %initialized.reference, gateway = gateway.name(), "Payment initialized successfully");
+
+    if let Err(e) = attach_initialized_charge(pool, &payer, &payment_id, &initialized).await {
+        error!(
+            payment_id = %payment_id,
+            reference = %initialized.reference,
+            gateway = gateway.name(),
+            error = %e,
+            "Gateway charge created but not recorded on its payment"
+        );
+        return Err(e);
+    }
 
     Ok(InitializeResponse {
-        status: paystack_res.status,
-        message: paystack_res.message,
+        status: true,
+        message: "Authorization URL created".to_string(),
         data: InitializeDataResponse {
-            authorization_url: paystack_res.data.authorization_url,
-            access_code: paystack_res.data.access_code,
-            reference: paystack_res.data.reference,
+            authorization_url: initialized.authorization_url,
+            access_code: initialized.access_code,
+            reference: initialized.reference,
+            gateway: gateway.name().to_string(),
         },
     })
 }
 
+/// Starts the payment of whatever a ride owes on behalf of its rider.
+pub async fn initialize_ride_payment_service(
+    pool: &PgPool,
+    provider: &PaymentProvider,
+    ride: &RideResponse,
+) -> Result<InitializeResponse, AppError> {
+    let email: String = sqlx::query("SELECT email FROM users WHERE id = $1")
+        .bind(&ride.rider_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.get("email"))
+        .ok_or_else(|| AppError::NotFound("Rider not found".to_string()))?;
+
+    initialize_payment_service(
+        pool,
+        provider,
+        InitializeRequest {
+            email,
+            amount: None,
+            ride_id: Some(ride.id.clone()),
+            currency: ride.currency.clone(),
+            user_id: Some(ride.rider_id.clone()),
+            driver_id: None,
+        },
+    )
+    .await
+}
+
 pub async fn get_payment_redirect_url(
     pool: &PgPool,
+    provider: &PaymentProvider,
     payload: InitializeRequest,
-) -> Result<String, String> {
-    debug!(email = %payload.email, amount = %payload.amount, "Getting payment redirect URL");
-
-    let api_key = std::env::var("PAYSTACK_API_KEY")
-        .map_err(|_| "PAYSTACK_API_KEY not set in environment".to_string())?;
-
-    let url = std::env::var("PAYSTACK_INITIALIZE_URL")
-        .map_err(|_| "PAYSTACK_INITIALIZE_URL not set in environment".to_string())?;
-
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(&format!("Bearer {}", api_key))
-            .map_err(|e| format!("Invalid authorization header: {}", e))?,
-    );
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-    let client = reqwest::Client::new();
-    let paystack_payload = PaystackInitializePayload {
-        email: payload.email.clone(),
-        amount: payload.amount.clone(),
-        currency: payload.currency.clone(),
-    };
-
-    let response = client
-        .post(&url)
-        .headers(headers)
-        .json(&paystack_payload)
-        .send()
-        .await
-        .map_err(|e| {
-            error!(error = %e, "Failed to get redirect URL");
-            format!("Failed to get redirect URL: {}", e)
-        })?;
-
-    let paystack_res: InitializeResponse = response.json().await.map_err(|e| {
-        error!(error = %e, "Failed to parse redirect response");
-        format!("Failed to parse response: {}", e)
-    })?;
+) -> Result<String, AppError> {
+    debug!(email = %payload.email, amount = ?payload.amount, ride_id = ?payload.ride_id, "Getting payment redirect URL");
 
-    info!(reference = %paystack_res.data.reference, "Redirect URL generated");
+    let response = initialize_payment_service(pool, provider, payload).await?;
 
-    store_initialized_payment(pool, &payload, &paystack_res).await?;
+    info!(reference = %response.data.reference, "Redirect URL generated");
 
-    Ok(paystack_res.data.authorization_url)
+    Ok(response.data.authorization_url)
 }
 
 // ============================================================================
@@ -141,316 +125,508 @@ pub async fn get_payment_redirect_url(
 
 pub async fn verify_payment_service(
     pool: &PgPool,
+    provider: &PaymentProvider,
     reference: String,
-) -> Result<VerifyResponse, String> {
+) -> Result<VerifyResponse, AppError> {
     debug!(reference = %reference, "Verifying payment");
 
-    let api_key = std::env::var("PAYSTACK_API_KEY")
-        .map_err(|_| "PAYSTACK_API_KEY not set in environment".to_string())?;
-
-    let url_main = std::env::var("PAYSTACK_TRANS_VERIFY_URL")
-        .map_err(|_| "PAYSTACK_TRANS_VERIFY_URL not set in environment".to_string())?;
-
-    let url = format!("{}{}", url_main, reference);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .bearer_auth(api_key)
-        .send()
-        .await
-        .map_err(|e| {
-            error!(error = %e, "Failed to verify payment");
-            format!("Payment verification request failed: {}", e)
-        })?;
-
-    let paystack_res: VerifyResponse = response.json().await.map_err(|e| {
-        error!(error = %e, "Failed to parse verification response");
-        format!("Failed to parse response: {}", e)
-    })?;
+    let gateway = provider.gateway(&payment_gateway(pool, &reference).await?)?;
+    let verification = gateway.verify(&reference).await?;
 
     info!(
-        reference = %paystack_res.data.reference,
-        status = %paystack_res.data.status,
-        amount = %paystack_res.data.amount,
+        reference = %verification.reference,
+        status = verification.status.as_str(),
+        amount = %verification.amount,
         "Payment verified"
     );
 
-    let raw_payload = serde_json::to_value(&paystack_res)
-        .map_err(|e| format!("Failed to serialize verification response: {}", e))?;
-    update_payment_by_reference(
+    let amount_mismatch = match update_payment_by_reference(
         pool,
-        &paystack_res.data.reference,
-        &paystack_res.data.status,
-        Some(paystack_res.data.gateway_response.clone()),
-        Some(raw_payload),
+        gateway.name(),
+        &verification.reference,
+        verification.status,
+        Some(verification.gateway_response.clone()),
+        Some(verification.raw),
+        Some(verification.amount),
     )
-    .await?;
+    .await?
+    {
+        PaymentUpdate::Applied { amount_mismatch } => amount_mismatch,
+        PaymentUpdate::Stale {
+            amount_mismatch, ..
+        } => amount_mismatch,
+    };
 
     Ok(VerifyResponse {
-        status: paystack_res.status,
-        message: paystack_res.message,
+        status: true,
+        message: "Verification successful".to_string(),
         data: VerifyResponseData {
-            status: paystack_res.data.status,
-            amount: paystack_res.data.amount,
-            reference: paystack_res.data.reference,
-            gateway_response: paystack_res.data.gateway_response,
+            status: verification.status.as_str().to_string(),
+            amount: verification.amount,
+            reference: verification.reference,
+            gateway_response: verification.gateway_response,
+            amount_mismatch,
         },
     })
 }
 
+/// The rider or driver a payment is charged to.
+pub async fn payment_payer_service(pool: &PgPool, reference: &str) -> Result<String, AppError> {
+    sqlx::query(
+        "SELECT user_id AS payer_id FROM user_payments WHERE reference = $1 \
+        UNION ALL \
+        SELECT driver_id AS payer_id FROM driver_payments WHERE reference = $1",
+    )
+    .bind(reference)
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.get("payer_id"))
+    .ok_or_else(|| AppError::NotFound("No payment found for reference".to_string()))
+}
+
 // ============================================================================
-// Webhook Validation Service
+// Webhook Service
 // ============================================================================
 
-pub fn verify_webhook_signature(signature: &str, body: &[u8]) -> Result<bool, String> {
-    let api_key = std::env::var("PAYSTACK_API_KEY")
-        .map_err(|_| "PAYSTACK_API_KEY not set in environment".to_string())?;
+/// Applies a webhook event `gateway` has already authenticated. Only payments
+/// made through that gateway are touched. Events that would move a payment
+/// backwards, e.g. a `charge.failed` arriving after `charge.success`, are
+/// ignored.
+pub async fn process_webhook_event(
+    pool: &PgPool,
+    gateway: &str,
+    event: WebhookEvent,
+) -> Result<WebhookEventStatus, AppError> {
+    let status = match event.kind {
+        WebhookKind::Charge(status) => status,
+        WebhookKind::Refund(status) => {
+            return apply_refund_event(pool, gateway, &event, status).await;
+        }
+        WebhookKind::Other => {
+            info!(event = %event.event, reference = %event.reference, "Ignoring webhook event");
+            return Ok(WebhookEventStatus::Ignored);
+        }
+    };
 
-    let mut mac =
-        HmacSha512::new_from_slice(api_key.as_bytes()).expect("HMAC can take key of any size");
-    mac.update(body);
+    let update = update_payment_by_reference(
+        pool,
+        gateway,
+        &event.reference,
+        status,
+        event.gateway_response,
+        Some(event.payload),
+        event.amount,
+    )
+    .await?;
+
+    if let PaymentUpdate::Stale { current, .. } = update {
+        warn!(
+            reference = %event.reference,
+            event = %event.event,
+            current = %current,
+            "Ignoring webhook that would move the payment backwards"
+        );
+        return Ok(WebhookEventStatus::Ignored);
+    }
+    info!(reference = %event.reference, status = status.as_str(), "Webhook payment update applied");
+
+    Ok(WebhookEventStatus::Processed)
+}
+
+// ============================================================================
+// Database Helpers
+// ============================================================================
 
-    let result = mac.finalize();
-    let expected_signature = encode(result.into_bytes());
+/// What became of a status reported for a payment.
+enum PaymentUpdate {
+    Applied {
+        amount_mismatch: bool,
+    },
+    /// The payment has moved past the reported status; nothing was changed.
+    Stale {
+        current: String,
+        amount_mismatch: bool,
+    },
+}
 
-    Ok(expected_signature == signature)
+/// Amount actually sent to the gateway.
+struct Charge {
+    amount: Money,
+    ride_id: Option<String>,
 }
 
-pub async fn process_webhook_event(pool: &PgPool, payload: Value) -> Result<(), String> {
-    let event = payload["event"].as_str().unwrap_or_default();
-    let reference = payload["data"]["reference"]
-        .as_str()
-        .unwrap_or_default()
-        .to_string();
+/// Ride payments are charged the fare computed on completion, or the
+/// cancellation fee of a cancelled ride; anything else still uses the amount
+/// supplied in the request.
+async fn resolve_charge(pool: &PgPool, payload: &InitializeRequest) -> Result<Charge, AppError> {
+    let Some(ride_id) = payload.ride_id.as_ref() else {
+        let amount = payload
+            .amount
+            .ok_or_else(|| AppError::Validation("amount or ride_id is required".to_string()))?;
+        return Ok(Charge {
+            amount: Money::charge(amount, resolve_currency(payload)?)?,
+            ride_id: None,
+        });
+    };
+
+    if payload.amount.is_some() {
+        return Err(AppError::Validation(
+            "amount is computed from the ride; omit it when ride_id is set".to_string(),
+        ));
+    }
+
+    let ride = get_ride_service(pool, ride_id.clone()).await?;
 
-    if reference.is_empty() {
-        return Err("Webhook payload missing reference".to_string());
+    if payload.user_id.as_deref() != Some(ride.rider_id.as_str()) {
+        error!(ride_id = %ride_id, "Payment payer is not the ride's rider");
+        return Err(AppError::Forbidden(
+            "Only the rider can pay for a ride".to_string(),
+        ));
     }
 
-    let status = if event == "charge.success" {
-        "success".to_string()
-    } else if event == "charge.failed" {
-        "failed".to_string()
-    } else {
-        payload["data"]["status"]
-            .as_str()
-            .unwrap_or("pending")
-            .to_string()
+    let amount_due = match (ride.status, ride.final_fare, ride.cancellation_fee) {
+        (RideStatus::Completed, Some(final_fare), _) => final_fare,
+        (RideStatus::Cancelled, _, Some(fee)) if fee > 0 => fee,
+        _ => {
+            error!(ride_id = %ride_id, "Ride has nothing to charge");
+            return Err(AppError::Validation(
+                "Ride has nothing to charge yet".to_string(),
+            ));
+        }
     };
 
-    let gateway_response = payload["data"]["gateway_response"]
-        .as_str()
-        .map(|value| value.to_string());
+    let already_paid = sqlx::query(
+        "SELECT 1 FROM user_payments \
+        WHERE ride_id = $1 AND status = 'success' AND NOT amount_mismatch LIMIT 1",
+    )
+    .bind(ride_id)
+    .fetch_optional(pool)
+    .await?;
 
-    update_payment_by_reference(pool, &reference, &status, gateway_response, Some(payload)).await?;
+    if already_paid.is_some() {
+        error!(ride_id = %ride_id, "Ride has already been paid for");
+        return Err(AppError::conflict("Ride has already been paid for"));
+    }
 
-    info!(reference = %reference, status = %status, "Webhook payment update applied");
+    let currency = match ride.currency.as_deref() {
+        Some(currency) => Currency::parse(currency)?,
+        None => resolve_currency(payload)?,
+    };
 
-    Ok(())
+    Ok(Charge {
+        amount: Money::charge(amount_due, currency)?,
+        ride_id: Some(ride_id.clone()),
+    })
 }
 
-// ============================================================================
-// Database Helpers
-// ============================================================================
-
 enum Payer {
     User(String),
     Driver(String),
 }
 
-fn resolve_payer(payload: &InitializeRequest) -> Result<Payer, String> {
+fn resolve_payer(payload: &InitializeRequest) -> Result<Payer, AppError> {
     match (payload.user_id.as_ref(), payload.driver_id.as_ref()) {
         (Some(user_id), None) => Ok(Payer::User(user_id.clone())),
         (None, Some(driver_id)) => Ok(Payer::Driver(driver_id.clone())),
-        (Some(_), Some(_)) => Err("Provide only one of user_id or driver_id".to_string()),
-        (None, None) => Err("user_id or driver_id is required".to_string()),
+        (Some(_), Some(_)) => Err(AppError::Validation(
+            "Provide only one of user_id or driver_id".to_string(),
+        )),
+        (None, None) => Err(AppError::Validation(
+            "user_id or driver_id is required".to_string(),
+        )),
+    }
+}
+
+fn resolve_currency(payload: &InitializeRequest) -> Result<Currency, AppError> {
+    match payload.currency.as_deref() {
+        Some(currency) => Currency::parse(currency),
+        None => Ok(Currency::NGN),
     }
 }
 
-fn resolve_currency(payload: &InitializeRequest) -> String {
-    payload
-        .currency
-        .clone()
-        .unwrap_or_else(|| "NGN".to_string())
+impl Payer {
+    fn table(&self) -> &'static str {
+        match self {
+            Payer::User(_) => "user_payments",
+            Payer::Driver(_) => "driver_payments",
+        }
+    }
 }
 
-async fn store_initialized_payment(
+/// Records a payment before its charge is created at the gateway. The
+/// reference is a placeholder until `attach_initialized_charge` fills it in.
+async fn insert_pending_payment(
     pool: &PgPool,
-    payload: &InitializeRequest,
-    paystack_res: &InitializeResponse,
-) -> Result<(), String> {
-    let payer = resolve_payer(payload)?;
-    let currency = resolve_currency(payload);
+    payer: &Payer,
+    email: &str,
+    charge: &Charge,
+    gateway: &str,
+) -> Result<String, AppError> {
     let now = Utc::now();
     let payment_id = Uuid::new_v4().to_string();
+    let placeholder = format!("pending_{}", payment_id);
 
     match payer {
         Payer::User(user_id) => {
             sqlx::query(
                 "INSERT INTO user_payments \
-                (id, user_id, email, amount, currency, status, reference, authorization_url, access_code, created_at, updated_at) \
+                (id, user_id, email, amount, currency, status, reference, created_at, updated_at, ride_id, gateway) \
                 VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)",
             )
             .bind(&payment_id)
-            .bind(&user_id)
-            .bind(&payload.email)
-            .bind(&payload.amount)
-            .bind(&currency)
-            .bind("pending")
-            .bind(&paystack_res.data.reference)
-            .bind(&paystack_res.data.authorization_url)
-            .bind(&paystack_res.data.access_code)
+            .bind(user_id)
+            .bind(email)
+            .bind(charge.amount.minor_units)
+            .bind(charge.amount.currency.as_str())
+            .bind(ChargeStatus::Pending.as_str())
+            .bind(&placeholder)
             .bind(now)
             .bind(now)
+            .bind(&charge.ride_id)
+            .bind(gateway)
             .execute(pool)
-            .await
-            .map_err(|e| format!("Failed to store user payment: {}", e))?;
+            .await?;
         }
         Payer::Driver(driver_id) => {
             sqlx::query(
                 "INSERT INTO driver_payments \
-                (id, driver_id, email, amount, currency, status, reference, authorization_url, access_code, created_at, updated_at) \
-                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)",
+                (id, driver_id, email, amount, currency, status, reference, created_at, updated_at, gateway) \
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)",
             )
             .bind(&payment_id)
-            .bind(&driver_id)
-            .bind(&payload.email)
-            .bind(&payload.amount)
-            .bind(&currency)
-            .bind("pending")
-            .bind(&paystack_res.data.reference)
-            .bind(&paystack_res.data.authorization_url)
-            .bind(&paystack_res.data.access_code)
+            .bind(driver_id)
+            .bind(email)
+            .bind(charge.amount.minor_units)
+            .bind(charge.amount.currency.as_str())
+            .bind(ChargeStatus::Pending.as_str())
+            .bind(&placeholder)
             .bind(now)
             .bind(now)
+            .bind(gateway)
             .execute(pool)
-            .await
-            .map_err(|e| format!("Failed to store driver payment: {}", e))?;
+            .await?;
         }
     }
 
+    Ok(payment_id)
+}
+
+/// Stores the gateway reference and checkout details on a pending payment.
+async fn attach_initialized_charge(
+    pool: &PgPool,
+    payer: &Payer,
+    payment_id: &str,
+    initialized: &InitializedCharge,
+) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "UPDATE {} SET reference = $1, authorization_url = $2, access_code = $3, updated_at = $4 \
+        WHERE id = $5",
+        payer.table()
+    ))
+    .bind(&initialized.reference)
+    .bind(&initialized.authorization_url)
+    .bind(&initialized.access_code)
+    .bind(Utc::now())
+    .bind(payment_id)
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
 
+/// Closes a pending payment whose charge the gateway refused to create.
+async fn mark_payment_failed(pool: &PgPool, payer: &Payer, payment_id: &str) {
+    let result = sqlx::query(&format!(
+        "UPDATE {} SET status = $1, updated_at = $2 WHERE id = $3",
+        payer.table()
+    ))
+    .bind(ChargeStatus::Failed.as_str())
+    .bind(Utc::now())
+    .bind(payment_id)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        error!(payment_id = %payment_id, error = %e, "Failed to mark payment as failed");
+    }
+}
+
+/// The gateway override of the company behind a payment: the company of the
+/// ride's driver, or of the paying driver.
+async fn company_gateway(
+    pool: &PgPool,
+    payload: &InitializeRequest,
+    charge: &Charge,
+) -> Result<Option<String>, AppError> {
+    let row = if let Some(ride_id) = charge.ride_id.as_ref() {
+        sqlx::query(
+            "SELECT c.payment_gateway FROM rides r \
+            JOIN transport_company_drivers d ON d.id = r.driver_id \
+            JOIN transport_companies c ON c.id = d.transport_company_id \
+            WHERE r.id = $1",
+        )
+        .bind(ride_id)
+        .fetch_optional(pool)
+        .await?
+    } else if let Some(driver_id) = payload.driver_id.as_ref() {
+        sqlx::query(
+            "SELECT c.payment_gateway FROM transport_company_drivers d \
+            JOIN transport_companies c ON c.id = d.transport_company_id \
+            WHERE d.id = $1",
+        )
+        .bind(driver_id)
+        .fetch_optional(pool)
+        .await?
+    } else {
+        None
+    };
+
+    Ok(row.and_then(|row| row.get("payment_gateway")))
+}
+
+/// The gateway a payment was made through.
+async fn payment_gateway(pool: &PgPool, reference: &str) -> Result<String, AppError> {
+    sqlx::query(
+        "SELECT gateway FROM user_payments WHERE reference = $1 \
+        UNION ALL \
+        SELECT gateway FROM driver_payments WHERE reference = $1",
+    )
+    .bind(reference)
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.get("gateway"))
+    .ok_or_else(|| AppError::NotFound("No payment found for reference".to_string()))
+}
+
+/// Records what the gateway reported for a payment, unless the payment has
+/// moved past that status. For a successful charge the confirmed amount is
+/// kept and compared with what was initialized.
 async fn update_payment_by_reference(
     pool: &PgPool,
+    gateway: &str,
     reference: &str,
-    status: &str,
+    status: ChargeStatus,
     gateway_response: Option<String>,
     raw_payload: Option<Value>,
-) -> Result<(), String> {
+    confirmed: Option<Money>,
+) -> Result<PaymentUpdate, AppError> {
     let now = Utc::now();
     let raw_payload = raw_payload.map(Json);
+    let confirmed = confirmed.filter(|_| status == ChargeStatus::Success);
+
+    for table in ["user_payments", "driver_payments"] {
+        let row = sqlx::query(&format!(
+            "UPDATE {} \
+            SET status = $1, gateway_response = $2, raw_payload = $3, updated_at = $4, \
+            confirmed_amount = COALESCE($7, confirmed_amount), \
+            confirmed_currency = COALESCE($8, confirmed_currency), \
+            amount_mismatch = CASE WHEN $7::BIGINT IS NULL THEN amount_mismatch \
+                ELSE ($7 <> amount OR $8 <> currency) END \
+            WHERE reference = $5 AND gateway = $6 AND status = ANY($9) \
+            RETURNING amount, currency, amount_mismatch",
+            table
+        ))
+        .bind(status.as_str())
+        .bind(gateway_response.clone())
+        .bind(raw_payload.clone())
+        .bind(now)
+        .bind(reference)
+        .bind(gateway)
+        .bind(confirmed.map(|money| money.minor_units))
+        .bind(confirmed.map(|money| money.currency.as_str().to_string()))
+        .bind(status.previous_states())
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(row) = row {
+            let amount_mismatch: bool = row.get("amount_mismatch");
+            if amount_mismatch {
+                let expected = Money::stored(row.get("amount"), &row.get::<String, _>("currency"))?;
+                error!(
+                    reference = %reference,
+                    expected = %expected,
+                    confirmed = ?confirmed.map(|money| money.to_string()),
+                    "Gateway confirmed a different amount than was initialized"
+                );
+            }
+            return Ok(PaymentUpdate::Applied { amount_mismatch });
+        }
 
-    let user_result = sqlx::query(
-        "UPDATE user_payments \
-        SET status = $1, gateway_response = $2, raw_payload = $3, updated_at = $4 \
-        WHERE reference = $5",
-    )
-    .bind(status)
-    .bind(gateway_response.clone())
-    .bind(raw_payload.clone())
-    .bind(now)
-    .bind(reference)
-    .execute(pool)
-    .await
-    .map_err(|e| format!("Failed to update user payment: {}", e))?;
-
-    let driver_result = sqlx::query(
-        "UPDATE driver_payments \
-        SET status = $1, gateway_response = $2, raw_payload = $3, updated_at = $4 \
-        WHERE reference = $5",
-    )
-    .bind(status)
-    .bind(gateway_response)
-    .bind(raw_payload)
-    .bind(now)
-    .bind(reference)
-    .execute(pool)
-    .await
-    .map_err(|e| format!("Failed to update driver payment: {}", e))?;
-
-    if user_result.rows_affected() == 0 && driver_result.rows_affected() == 0 {
-        return Err("No payment found for reference".to_string());
+        let current = sqlx::query(&format!(
+            "SELECT status, amount_mismatch FROM {} WHERE reference = $1 AND gateway = $2",
+            table
+        ))
+        .bind(reference)
+        .bind(gateway)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(row) = current {
+            return Ok(PaymentUpdate::Stale {
+                current: row.get("status"),
+                amount_mismatch: row.get("amount_mismatch"),
+            });
+        }
     }
 
-    Ok(())
+    Err(AppError::NotFound(
+        "No payment found for reference".to_string(),
+    ))
 }
 
 // ============================================================================
 // Payment List Services
 // ============================================================================
 
-pub async fn list_all_payments_service(pool: &PgPool) -> Result<Vec<PaymentResponse>, String> {
+/// Minor units refunded so far, for a payment row aliased `p`.
+const REFUNDED_AMOUNT: &str = "COALESCE((SELECT SUM(r.amount) FROM refunds r \
+    WHERE r.payment_reference = p.reference AND r.gateway = p.gateway \
+    AND r.status = 'processed'), 0)::BIGINT AS refunded_amount";
+
+pub async fn list_all_payments_service(pool: &PgPool) -> Result<Vec<PaymentResponse>, AppError> {
     debug!("Fetching all payments from database");
 
     let mut payments = Vec::new();
 
     // Fetch user payments
-    let user_rows = sqlx::query(
+    let user_rows = sqlx::query(&format!(
         "SELECT id, user_id, email, amount, currency, status, reference, \
-        authorization_url, access_code, gateway_response, created_at, updated_at \
-        FROM user_payments ORDER BY created_at DESC",
-    )
+        authorization_url, access_code, gateway_response, gateway, \
+        confirmed_amount, confirmed_currency, amount_mismatch, created_at, updated_at, \
+        {} FROM user_payments p ORDER BY created_at DESC",
+        REFUNDED_AMOUNT
+    ))
     .fetch_all(pool)
     .await
     .map_err(|e| {
         error!(error = %e, "Failed to fetch user payments");
-        format!("Database fetch failed: {}", e)
+        AppError::from(e)
     })?;
 
     for row in user_rows {
-        payments.push(PaymentResponse {
-            id: row.get("id"),
-            payer_type: "user".to_string(),
-            payer_id: row.get("user_id"),
-            email: row.get("email"),
-            amount: row.get("amount"),
-            currency: row.get("currency"),
-            status: row.get("status"),
-            reference: row.get("reference"),
-            authorization_url: row.get("authorization_url"),
-            access_code: row.get("access_code"),
-            gateway_response: row.get("gateway_response"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        });
+        payments.push(row_to_payment_response(&row, "user")?);
     }
 
     // Fetch driver payments
-    let driver_rows = sqlx::query(
+    let driver_rows = sqlx::query(&format!(
         "SELECT id, driver_id, email, amount, currency, status, reference, \
-        authorization_url, access_code, gateway_response, created_at, updated_at \
-        FROM driver_payments ORDER BY created_at DESC",
-    )
+        authorization_url, access_code, gateway_response, gateway, \
+        confirmed_amount, confirmed_currency, amount_mismatch, created_at, updated_at, \
+        {} FROM driver_payments p ORDER BY created_at DESC",
+        REFUNDED_AMOUNT
+    ))
     .fetch_all(pool)
     .await
     .map_err(|e| {
         error!(error = %e, "Failed to fetch driver payments");
-        format!("Database fetch failed: {}", e)
+        AppError::from(e)
     })?;
 
     for row in driver_rows {
-        payments.push(PaymentResponse {
-            id: row.get("id"),
-            payer_type: "driver".to_string(),
-            payer_id: row.get("driver_id"),
-            email: row.get("email"),
-            amount: row.get("amount"),
-            currency: row.get("currency"),
-            status: row.get("status"),
-            reference: row.get("reference"),
-            authorization_url: row.get("authorization_url"),
-            access_code: row.get("access_code"),
-            gateway_response: row.get("gateway_response"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        });
+        payments.push(row_to_payment_response(&row, "driver")?);
     }
 
     // Sort by created_at descending
-    payments.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    payments.sort_by_key(|payment| std::cmp::Reverse(payment.created_at));
 
     info!(count = payments.len(), "Payments fetched successfully");
 
@@ -460,40 +636,28 @@ pub async fn list_all_payments_service(pool: &PgPool) -> Result<Vec<PaymentRespo
 pub async fn list_user_payments_service(
     pool: &PgPool,
     user_id: String,
-) -> Result<Vec<PaymentResponse>, String> {
+) -> Result<Vec<PaymentResponse>, AppError> {
     debug!(user_id = %user_id, "Fetching user payments from database");
 
-    let rows = sqlx::query(
+    let rows = sqlx::query(&format!(
         "SELECT id, user_id, email, amount, currency, status, reference, \
-        authorization_url, access_code, gateway_response, created_at, updated_at \
-        FROM user_payments WHERE user_id = $1 ORDER BY created_at DESC",
-    )
+        authorization_url, access_code, gateway_response, gateway, \
+        confirmed_amount, confirmed_currency, amount_mismatch, created_at, updated_at, \
+        {} FROM user_payments p WHERE user_id = $1 ORDER BY created_at DESC",
+        REFUNDED_AMOUNT
+    ))
     .bind(&user_id)
     .fetch_all(pool)
     .await
     .map_err(|e| {
         error!(error = %e, "Failed to fetch user payments");
-        format!("Database fetch failed: {}", e)
+        AppError::from(e)
     })?;
 
-    let payments: Vec<PaymentResponse> = rows
-        .into_iter()
-        .map(|row| PaymentResponse {
-            id: row.get("id"),
-            payer_type: "user".to_string(),
-            payer_id: row.get("user_id"),
-            email: row.get("email"),
-            amount: row.get("amount"),
-            currency: row.get("currency"),
-            status: row.get("status"),
-            reference: row.get("reference"),
-            authorization_url: row.get("authorization_url"),
-            access_code: row.get("access_code"),
-            gateway_response: row.get("gateway_response"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-        .collect();
+    let payments = rows
+        .iter()
+        .map(|row| row_to_payment_response(row, "user"))
+        .collect::<Result<Vec<_>, _>>()?;
 
     info!(count = payments.len(), user_id = %user_id, "User payments fetched successfully");
 
@@ -503,42 +667,70 @@ pub async fn list_user_payments_service(
 pub async fn list_driver_payments_service(
     pool: &PgPool,
     driver_id: String,
-) -> Result<Vec<PaymentResponse>, String> {
+) -> Result<Vec<PaymentResponse>, AppError> {
     debug!(driver_id = %driver_id, "Fetching driver payments from database");
 
-    let rows = sqlx::query(
+    let rows = sqlx::query(&format!(
         "SELECT id, driver_id, email, amount, currency, status, reference, \
-        authorization_url, access_code, gateway_response, created_at, updated_at \
-        FROM driver_payments WHERE driver_id = $1 ORDER BY created_at DESC",
-    )
+        authorization_url, access_code, gateway_response, gateway, \
+        confirmed_amount, confirmed_currency, amount_mismatch, created_at, updated_at, \
+        {} FROM driver_payments p WHERE driver_id = $1 ORDER BY created_at DESC",
+        REFUNDED_AMOUNT
+    ))
     .bind(&driver_id)
     .fetch_all(pool)
     .await
     .map_err(|e| {
         error!(error = %e, "Failed to fetch driver payments");
-        format!("Database fetch failed: {}", e)
+        AppError::from(e)
     })?;
 
-    let payments: Vec<PaymentResponse> = rows
-        .into_iter()
-        .map(|row| PaymentResponse {
-            id: row.get("id"),
-            payer_type: "driver".to_string(),
-            payer_id: row.get("driver_id"),
-            email: row.get("email"),
-            amount: row.get("amount"),
-            currency: row.get("currency"),
-            status: row.get("status"),
-            reference: row.get("reference"),
-            authorization_url: row.get("authorization_url"),
-            access_code: row.get("access_code"),
-            gateway_response: row.get("gateway_response"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-        .collect();
+    let payments = rows
+        .iter()
+        .map(|row| row_to_payment_response(row, "driver"))
+        .collect::<Result<Vec<_>, _>>()?;
 
     info!(count = payments.len(), driver_id = %driver_id, "Driver payments fetched successfully");
 
     Ok(payments)
 }
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// `payer_type` is `user` or `driver`, matching the table the row came from.
+fn row_to_payment_response(
+    row: &sqlx::postgres::PgRow,
+    payer_type: &str,
+) -> Result<PaymentResponse, AppError> {
+    let confirmed_amount = match (
+        row.get::<Option<i64>, _>("confirmed_amount"),
+        row.get::<Option<String>, _>("confirmed_currency"),
+    ) {
+        (Some(amount), Some(currency)) => Some(Money::stored(amount, &currency)?),
+        _ => None,
+    };
+    let amount = Money::stored(row.get("amount"), &row.get::<String, _>("currency"))?;
+    // Refunds are made in the currency the gateway confirmed
+    let paid = confirmed_amount.unwrap_or(amount);
+
+    Ok(PaymentResponse {
+        id: row.get("id"),
+        payer_type: payer_type.to_string(),
+        payer_id: row.get(format!("{}_id", payer_type).as_str()),
+        email: row.get("email"),
+        amount,
+        status: row.get("status"),
+        reference: row.get("reference"),
+        authorization_url: row.get("authorization_url"),
+        access_code: row.get("access_code"),
+        gateway_response: row.get("gateway_response"),
+        gateway: row.get("gateway"),
+        confirmed_amount,
+        amount_mismatch: row.get("amount_mismatch"),
+        refunded_amount: Money::new(row.get("refunded_amount"), paid.currency),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
@@ -0,0 +1,510 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::money_model::Currency;
+use crate::models::pricing_model::{
+    CreatePricingRuleRequest, FareBreakdown, FareEstimateRequest, PricingRule,
+    UpdatePricingRuleRequest,
+};
+use crate::models::ride_model::RideResponse;
+use crate::services::matching_service::{estimate_travel_seconds, haversine_km};
+use crate::services::ride_service::validate_coordinates;
+use crate::services::surge_service::current_surge;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+const PRICING_RULE_COLUMNS: &str = "id, transport_company_id, vehicle_type, currency, base_fare, \
+    per_km, per_minute, minimum_fare, booking_fee, is_active, created_at, updated_at";
+
+/// Rates applied to a trip: either a stored pricing rule or the defaults.
+#[derive(Debug, Clone)]
+pub struct Tariff {
+    pub pricing_rule_id: Option<String>,
+    pub currency: String,
+    pub base_fare: i64,
+    pub per_km: i64,
+    pub per_minute: i64,
+    pub minimum_fare: i64,
+    pub booking_fee: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricingConfig {
+    /// Used when no pricing rule matches the trip.
+    pub default_tariff: Tariff,
+    /// Multiplier from straight-line to expected road distance.
+    pub route_factor: f64,
+}
+
+impl PricingConfig {
+    pub fn from_env() -> Self {
+        PricingConfig {
+            default_tariff: Tariff {
+                pricing_rule_id: None,
+                currency: env_or("PRICING_DEFAULT_CURRENCY", "NGN".to_string()),
+                base_fare: env_or("PRICING_DEFAULT_BASE_FARE", 50_000),
+                per_km: env_or("PRICING_DEFAULT_PER_KM", 15_000),
+                per_minute: env_or("PRICING_DEFAULT_PER_MINUTE", 2_000),
+                minimum_fare: env_or("PRICING_DEFAULT_MINIMUM_FARE", 100_000),
+                booking_fee: env_or("PRICING_DEFAULT_BOOKING_FEE", 10_000),
+            },
+            route_factor: env_or("PRICING_ROUTE_FACTOR", 1.3),
+        }
+    }
+}
+
+// ============================================================================
+// Fare Engine
+// ============================================================================
+
+/// Prices a trip of the given length and duration. Every fare in the system,
+/// upfront or final, goes through this function. Surge scales the metered
+/// fare only; the booking fee is never surged.
+pub fn calculate_fare(
+    tariff: &Tariff,
+    distance_km: f64,
+    duration_seconds: i64,
+    surge_multiplier: f64,
+) -> FareBreakdown {
+    let distance_km = distance_km.max(0.0);
+    let duration_seconds = duration_seconds.max(0);
+    let surge_multiplier = surge_multiplier.max(1.0);
+
+    let distance_fare = (tariff.per_km as f64 * distance_km).round() as i64;
+    let time_fare = (tariff.per_minute as f64 * duration_seconds as f64 / 60.0).round() as i64;
+    let metered = tariff.base_fare + distance_fare + time_fare;
+    let surge_fare = (metered as f64 * (surge_multiplier - 1.0)).round() as i64;
+    let minimum_fare_adjustment = (tariff.minimum_fare - metered - surge_fare).max(0);
+
+    FareBreakdown {
+        pricing_rule_id: tariff.pricing_rule_id.clone(),
+        currency: tariff.currency.clone(),
+        distance_km,
+        duration_seconds,
+        base_fare: tariff.base_fare,
+        distance_fare,
+        time_fare,
+        booking_fee: tariff.booking_fee,
+        surge_multiplier,
+        surge_fare,
+        minimum_fare_adjustment,
+        total: metered + surge_fare + minimum_fare_adjustment + tariff.booking_fee,
+    }
+}
+
+/// Picks the most specific active rule for a company and vehicle type:
+/// company and type, then company only, then type only, then the catch-all
+/// rule, and finally the configured defaults.
+pub async fn resolve_tariff(
+    pool: &PgPool,
+    transport_company_id: Option<&str>,
+    vehicle_type: Option<&str>,
+) -> Result<Tariff, AppError> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM pricing_rules \
+        WHERE is_active = TRUE \
+        AND (transport_company_id IS NULL OR transport_company_id = $1) \
+        AND (vehicle_type IS NULL OR vehicle_type = $2) \
+        ORDER BY (transport_company_id IS NOT NULL) DESC, (vehicle_type IS NOT NULL) DESC \
+        LIMIT 1",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(transport_company_id)
+    .bind(vehicle_type)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some(row) => {
+            let rule = row_to_pricing_rule(row);
+            Tariff {
+                pricing_rule_id: Some(rule.id),
+                currency: rule.currency,
+                base_fare: rule.base_fare,
+                per_km: rule.per_km,
+                per_minute: rule.per_minute,
+                minimum_fare: rule.minimum_fare,
+                booking_fee: rule.booking_fee,
+            }
+        }
+        None => PricingConfig::from_env().default_tariff,
+    })
+}
+
+/// Expected road distance and driving time between two points.
+pub fn estimate_trip(
+    pickup_lat: f64,
+    pickup_lng: f64,
+    dropoff_lat: f64,
+    dropoff_lng: f64,
+) -> (f64, i64) {
+    let config = PricingConfig::from_env();
+    let distance_km =
+        haversine_km(pickup_lat, pickup_lng, dropoff_lat, dropoff_lng) * config.route_factor;
+    (distance_km, estimate_travel_seconds(distance_km))
+}
+
+// ============================================================================
+// Fare Services
+// ============================================================================
+
+pub async fn estimate_fare_service(
+    pool: &PgPool,
+    payload: FareEstimateRequest,
+) -> Result<FareBreakdown, AppError> {
+    debug!(vehicle_type = ?payload.vehicle_type, "Estimating fare");
+
+    validate_coordinates(payload.pickup_lat, payload.pickup_lng)?;
+    validate_coordinates(payload.dropoff_lat, payload.dropoff_lng)?;
+
+    let tariff = resolve_tariff(
+        pool,
+        payload.transport_company_id.as_deref(),
+        payload.vehicle_type.as_deref(),
+    )
+    .await?;
+    let (distance_km, duration_seconds) = estimate_trip(
+        payload.pickup_lat,
+        payload.pickup_lng,
+        payload.dropoff_lat,
+        payload.dropoff_lng,
+    );
+    let surge = current_surge(pool, payload.pickup_lat, payload.pickup_lng).await?;
+
+    Ok(calculate_fare(
+        &tariff,
+        distance_km,
+        duration_seconds,
+        surge.multiplier,
+    ))
+}
+
+/// Prices a finished trip from what actually happened: the GPS trail recorded
+/// after pickup and the time between start and completion, using the rules of
+/// the driver's company and vehicle type. The surge locked in when the ride
+/// was requested still applies.
+pub async fn final_fare_service(
+    pool: &PgPool,
+    ride: &RideResponse,
+    completed_at: DateTime<Utc>,
+) -> Result<FareBreakdown, AppError> {
+    debug!(ride_id = %ride.id, "Computing final fare");
+
+    let driver_id = ride.driver_id.as_deref().ok_or_else(|| {
+        error!(ride_id = %ride.id, "Cannot price a ride without a driver");
+        AppError::Validation("Ride has no driver".to_string())
+    })?;
+
+    let driver = sqlx::query(
+        "SELECT transport_company_id, vehicle_type FROM transport_company_drivers WHERE id = $1",
+    )
+    .bind(driver_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        error!(driver_id = %driver_id, "Driver not found");
+        AppError::NotFound("Driver not found".to_string())
+    })?;
+
+    let transport_company_id: String = driver.get("transport_company_id");
+    let vehicle_type: String = driver.get("vehicle_type");
+    let tariff = resolve_tariff(pool, Some(&transport_company_id), Some(&vehicle_type)).await?;
+
+    let (estimated_km, estimated_seconds) = estimate_trip(
+        ride.pickup_lat,
+        ride.pickup_lng,
+        ride.dropoff_lat,
+        ride.dropoff_lng,
+    );
+
+    let distance_km = match ride.started_at {
+        Some(_) => trail_distance_km(pool, &ride.id).await?,
+        None => None,
+    }
+    .unwrap_or(estimated_km);
+
+    let duration_seconds = ride
+        .started_at
+        .map(|started_at| (completed_at - started_at).num_seconds())
+        .unwrap_or(estimated_seconds);
+
+    let fare = calculate_fare(
+        &tariff,
+        distance_km,
+        duration_seconds,
+        ride.surge_multiplier,
+    );
+    info!(ride_id = %ride.id, total = fare.total, currency = %fare.currency, "Final fare computed");
+
+    Ok(fare)
+}
+
+// ============================================================================
+// Pricing Rule Services
+// ============================================================================
+
+pub async fn create_pricing_rule_service(
+    pool: &PgPool,
+    payload: CreatePricingRuleRequest,
+) -> Result<PricingRule, AppError> {
+    debug!(
+        transport_company_id = ?payload.transport_company_id,
+        vehicle_type = ?payload.vehicle_type,
+        "Creating pricing rule"
+    );
+
+    validate_amounts(&[
+        payload.base_fare,
+        payload.per_km,
+        payload.per_minute,
+        payload.minimum_fare,
+        payload.booking_fee,
+    ])?;
+    let currency = match payload.currency.as_deref() {
+        Some(currency) => Currency::parse(currency)?.to_string(),
+        None => PricingConfig::from_env().default_tariff.currency,
+    };
+
+    let now = Utc::now();
+    let row = sqlx::query(&format!(
+        "INSERT INTO pricing_rules \
+        (id, transport_company_id, vehicle_type, currency, base_fare, per_km, per_minute, minimum_fare, booking_fee, is_active, created_at, updated_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12) \
+        RETURNING {}",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(Uuid::new_v4().to_string())
+    .bind(&payload.transport_company_id)
+    .bind(&payload.vehicle_type)
+    .bind(currency)
+    .bind(payload.base_fare)
+    .bind(payload.per_km)
+    .bind(payload.per_minute)
+    .bind(payload.minimum_fare)
+    .bind(payload.booking_fee)
+    .bind(true)
+    .bind(now)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        error!(error = %e, "Failed to insert pricing rule");
+        AppError::from(e)
+    })?;
+
+    let rule = row_to_pricing_rule(row);
+    info!(pricing_rule_id = %rule.id, "Pricing rule created");
+
+    Ok(rule)
+}
+
+pub async fn list_pricing_rules_service(pool: &PgPool) -> Result<Vec<PricingRule>, AppError> {
+    debug!("Fetching pricing rules from database");
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM pricing_rules ORDER BY created_at DESC",
+        PRICING_RULE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(row_to_pricing_rule).collect())
+}
+
+pub async fn get_pricing_rule_service(pool: &PgPool, id: String) -> Result<PricingRule, AppError> {
+    debug!(pricing_rule_id = %id, "Fetching pricing rule from database");
+
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM pricing_rules WHERE id = $1",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(&id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        error!(pricing_rule_id = %id, "Pricing rule not found");
+        AppError::NotFound("Pricing rule not found".to_string())
+    })?;
+
+    Ok(row_to_pricing_rule(row))
+}
+
+pub async fn update_pricing_rule_service(
+    pool: &PgPool,
+    id: String,
+    payload: UpdatePricingRuleRequest,
+) -> Result<PricingRule, AppError> {
+    debug!(pricing_rule_id = %id, "Updating pricing rule");
+
+    let amounts: Vec<i64> = [
+        payload.base_fare,
+        payload.per_km,
+        payload.per_minute,
+        payload.minimum_fare,
+        payload.booking_fee,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    validate_amounts(&amounts)?;
+    let currency = payload
+        .currency
+        .as_deref()
+        .map(Currency::parse)
+        .transpose()?
+        .map(|currency| currency.to_string());
+
+    let row = sqlx::query(&format!(
+        "UPDATE pricing_rules SET \
+        currency = COALESCE($1, currency), \
+        base_fare = COALESCE($2, base_fare), \
+        per_km = COALESCE($3, per_km), \
+        per_minute = COALESCE($4, per_minute), \
+        minimum_fare = COALESCE($5, minimum_fare), \
+        booking_fee = COALESCE($6, booking_fee), \
+        is_active = COALESCE($7, is_active), \
+        updated_at = $8 \
+        WHERE id = $9 \
+        RETURNING {}",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(currency)
+    .bind(payload.base_fare)
+    .bind(payload.per_km)
+    .bind(payload.per_minute)
+    .bind(payload.minimum_fare)
+    .bind(payload.booking_fee)
+    .bind(payload.is_active)
+    .bind(Utc::now())
+    .bind(&id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        error!(pricing_rule_id = %id, "Pricing rule not found");
+        AppError::NotFound("Pricing rule not found".to_string())
+    })?;
+
+    info!(pricing_rule_id = %id, "Pricing rule updated");
+
+    Ok(row_to_pricing_rule(row))
+}
+
+pub async fn delete_pricing_rule_service(pool: &PgPool, id: String) -> Result<(), AppError> {
+    debug!(pricing_rule_id = %id, "Deleting pricing rule");
+
+    let result = sqlx::query("DELETE FROM pricing_rules WHERE id = $1")
+        .bind(&id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        error!(pricing_rule_id = %id, "Pricing rule not found");
+        return Err(AppError::NotFound("Pricing rule not found".to_string()));
+    }
+
+    info!(pricing_rule_id = %id, "Pricing rule deleted");
+
+    Ok(())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Distance driven since pickup, as counted from the GPS trail while the
+/// trip was in progress, if any of it was counted.
+async fn trail_distance_km(pool: &PgPool, ride_id: &str) -> Result<Option<f64>, AppError> {
+    let row = sqlx::query("SELECT trail_distance_km, trail_counted_until FROM rides WHERE id = $1")
+        .bind(ride_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row
+        .filter(|row| {
+            row.get::<Option<DateTime<Utc>>, _>("trail_counted_until")
+                .is_some()
+        })
+        .map(|row| row.get("trail_distance_km")))
+}
+
+fn validate_amounts(amounts: &[i64]) -> Result<(), AppError> {
+    if amounts.iter().any(|amount| *amount < 0) {
+        error!("Negative amount in pricing rule");
+        return Err(AppError::Validation(
+            "Pricing amounts cannot be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn row_to_pricing_rule(row: sqlx::postgres::PgRow) -> PricingRule {
+    PricingRule {
+        id: row.get("id"),
+        transport_company_id: row.get("transport_company_id"),
+        vehicle_type: row.get("vehicle_type"),
+        currency: row.get("currency"),
+        base_fare: row.get("base_fare"),
+        per_km: row.get("per_km"),
+        per_minute: row.get("per_minute"),
+        minimum_fare: row.get("minimum_fare"),
+        booking_fee: row.get("booking_fee"),
+        is_active: row.get("is_active"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tariff() -> Tariff {
+        Tariff {
+            pricing_rule_id: None,
+            currency: "NGN".to_string(),
+            base_fare: 50_000,
+            per_km: 15_000,
+            per_minute: 2_000,
+            minimum_fare: 100_000,
+            booking_fee: 10_000,
+        }
+    }
+
+    #[test]
+    fn meters_distance_and_time() {
+        let fare = calculate_fare(&tariff(), 10.0, 600, 1.0);
+
+        assert_eq!(fare.distance_fare, 150_000);
+        assert_eq!(fare.time_fare, 20_000);
+        assert_eq!(fare.surge_fare, 0);
+        assert_eq!(fare.minimum_fare_adjustment, 0);
+        assert_eq!(fare.total, 230_000);
+    }
+
+    #[test]
+    fn tops_short_trips_up_to_the_minimum_fare() {
+        let fare = calculate_fare(&tariff(), 0.5, 60, 1.0);
+
+        assert_eq!(fare.minimum_fare_adjustment, 100_000 - 59_500);
+        assert_eq!(fare.total, 100_000 + 10_000);
+    }
+
+    #[test]
+    fn surges_the_metered_fare_but_not_the_booking_fee() {
+        let fare = calculate_fare(&tariff(), 10.0, 600, 1.5);
+
+        assert_eq!(fare.surge_fare, 110_000);
+        assert_eq!(fare.total, 220_000 + 110_000 + 10_000);
+    }
+
+    #[test]
+    fn clamps_negative_inputs() {
+        let fare = calculate_fare(&tariff(), -3.0, -60, 0.5);
+
+        assert_eq!(fare.distance_km, 0.0);
+        assert_eq!(fare.duration_seconds, 0);
+        assert_eq!(fare.surge_multiplier, 1.0);
+        assert_eq!(fare.total, 110_000);
+    }
+}
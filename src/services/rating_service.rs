@@ -0,0 +1,237 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::rating_model::{CreateRatingRequest, RaterRole, RatingResponse};
+use crate::models::ride_model::RideStatus;
+use crate::services::ride_service::get_ride_service;
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+const RATING_COLUMNS: &str =
+    "id, ride_id, rater_role, rater_id, ratee_id, score, comment, tags, created_at";
+const MAX_COMMENT_LENGTH: usize = 1000;
+const MAX_TAGS: usize = 5;
+const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct RatingConfig {
+    /// How many of the most recent ratings make up a person's aggregate.
+    pub aggregate_window: i64,
+    /// How long after completion a trip can still be rated.
+    pub submission_window_days: i64,
+}
+
+impl RatingConfig {
+    pub fn from_env() -> Self {
+        RatingConfig {
+            aggregate_window: env_or("RATING_AGGREGATE_WINDOW", 100),
+            submission_window_days: env_or("RATING_SUBMISSION_WINDOW_DAYS", 7),
+        }
+    }
+}
+
+// ============================================================================
+// Rating Services
+// ============================================================================
+
+/// Records the caller's rating of the other party of a completed ride and
+/// refreshes the rated person's aggregate. Each side rates a ride once.
+pub async fn rate_ride_service(
+    pool: &PgPool,
+    ride_id: String,
+    actor_id: String,
+    payload: CreateRatingRequest,
+) -> Result<RatingResponse, AppError> {
+    debug!(ride_id = %ride_id, actor_id = %actor_id, score = payload.score, "Rating ride");
+
+    if !(1..=5).contains(&payload.score) {
+        return Err(AppError::Validation(
+            "Score must be between 1 and 5".to_string(),
+        ));
+    }
+
+    let comment = payload
+        .comment
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty());
+    if comment
+        .as_ref()
+        .is_some_and(|comment| comment.chars().count() > MAX_COMMENT_LENGTH)
+    {
+        return Err(AppError::Validation(format!(
+            "Comment must be at most {} characters",
+            MAX_COMMENT_LENGTH
+        )));
+    }
+    let tags = normalize_tags(payload.tags.unwrap_or_default())?;
+
+    let ride = get_ride_service(pool, ride_id.clone()).await?;
+
+    let (RideStatus::Completed, Some(driver_id), Some(completed_at)) =
+        (ride.status, ride.driver_id.clone(), ride.completed_at)
+    else {
+        error!(ride_id = %ride_id, status = %ride.status.as_str(), "Ride has not been completed");
+        return Err(AppError::Validation(
+            "Only completed rides can be rated".to_string(),
+        ));
+    };
+
+    let (rater_role, ratee_id) = if ride.rider_id == actor_id {
+        (RaterRole::Rider, driver_id)
+    } else if driver_id == actor_id {
+        (RaterRole::Driver, ride.rider_id.clone())
+    } else {
+        error!(ride_id = %ride_id, actor_id = %actor_id, "Actor not permitted to rate ride");
+        return Err(AppError::Forbidden(
+            "Not permitted to perform this action on the ride".to_string(),
+        ));
+    };
+
+    let config = RatingConfig::from_env();
+    if Utc::now() - completed_at > Duration::days(config.submission_window_days) {
+        return Err(AppError::Validation(format!(
+            "Rides can only be rated within {} days of completion",
+            config.submission_window_days
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(&format!(
+        "INSERT INTO ride_ratings \
+        (id, ride_id, rater_role, rater_id, ratee_id, score, comment, tags, created_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9) \
+        ON CONFLICT (ride_id, rater_role) DO NOTHING \
+        RETURNING {}",
+        RATING_COLUMNS
+    ))
+    .bind(Uuid::new_v4().to_string())
+    .bind(&ride_id)
+    .bind(rater_role.as_str())
+    .bind(&actor_id)
+    .bind(&ratee_id)
+    .bind(payload.score)
+    .bind(&comment)
+    .bind(&tags)
+    .bind(Utc::now())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        error!(ride_id = %ride_id, rater_role = %rater_role.as_str(), "Ride already rated");
+        AppError::conflict("You have already rated this ride")
+    })?;
+
+    refresh_aggregate(&mut tx, rater_role, &ratee_id, config.aggregate_window).await?;
+
+    tx.commit().await?;
+
+    info!(
+        ride_id = %ride_id,
+        rater_role = %rater_role.as_str(),
+        ratee_id = %ratee_id,
+        score = payload.score,
+        "Ride rated"
+    );
+
+    row_to_rating_response(row)
+}
+
+/// The rating the caller left on a ride, if any.
+pub async fn get_ride_rating_service(
+    pool: &PgPool,
+    ride_id: String,
+    actor_id: String,
+) -> Result<RatingResponse, AppError> {
+    debug!(ride_id = %ride_id, actor_id = %actor_id, "Fetching ride rating");
+
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM ride_ratings WHERE ride_id = $1 AND rater_id = $2",
+        RATING_COLUMNS
+    ))
+    .bind(&ride_id)
+    .bind(&actor_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Rating not found".to_string()))?;
+
+    row_to_rating_response(row)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Recomputes the average of the latest ratings a person received. Riders
+/// rate drivers and drivers rate riders, so the rater's role picks the table.
+async fn refresh_aggregate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    rater_role: RaterRole,
+    ratee_id: &str,
+    window: i64,
+) -> Result<(), AppError> {
+    let table = match rater_role {
+        RaterRole::Rider => "transport_company_drivers",
+        RaterRole::Driver => "users",
+    };
+
+    sqlx::query(&format!(
+        "UPDATE {} SET rating = recent.average, rating_count = recent.total, updated_at = $4 \
+        FROM ( \
+            SELECT AVG(score)::REAL AS average, COUNT(*)::INTEGER AS total FROM ( \
+                SELECT score FROM ride_ratings \
+                WHERE ratee_id = $1 AND rater_role = $2 \
+                ORDER BY created_at DESC LIMIT $3) latest \
+        ) recent \
+        WHERE id = $1",
+        table
+    ))
+    .bind(ratee_id)
+    .bind(rater_role.as_str())
+    .bind(window.max(1))
+    .bind(Utc::now())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Lowercases, trims and de-duplicates tags so aggregates group cleanly.
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase().replace(' ', "_");
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Tags must be at most {} characters",
+                MAX_TAG_LENGTH
+            )));
+        }
+        normalized.push(tag);
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::Validation(format!(
+            "At most {} tags are allowed",
+            MAX_TAGS
+        )));
+    }
+    Ok(normalized)
+}
+
+fn row_to_rating_response(row: sqlx::postgres::PgRow) -> Result<RatingResponse, AppError> {
+    Ok(RatingResponse {
+        id: row.get("id"),
+        ride_id: row.get("ride_id"),
+        rater_role: RaterRole::parse(row.get("rater_role"))?,
+        rater_id: row.get("rater_id"),
+        ratee_id: row.get("ratee_id"),
+        score: row.get("score"),
+        comment: row.get("comment"),
+        tags: row.get("tags"),
+        created_at: row.get("created_at"),
+    })
+}
@@ -0,0 +1,51 @@
+use crate::models::realtime_model::RealtimeEvent;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::debug;
+
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// Status changes of a single ride.
+    Ride(String),
+    /// Position updates of a single driver.
+    DriverLocation(String),
+    /// Ride offers addressed to a single driver.
+    DriverOffers(String),
+}
+
+/// In-process fan-out of live events to WebSocket sessions. Channels are
+/// created on first subscription and dropped once nobody listens.
+#[derive(Clone, Default)]
+pub struct RealtimeHub {
+    channels: Arc<Mutex<HashMap<Topic, broadcast::Sender<RealtimeEvent>>>>,
+}
+
+impl RealtimeHub {
+    pub fn new() -> Self {
+        RealtimeHub::default()
+    }
+
+    pub fn subscribe(&self, topic: Topic) -> broadcast::Receiver<RealtimeEvent> {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels
+            .entry(topic)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sends an event to everyone subscribed to `topic`; a no-op when nobody is.
+    pub fn publish(&self, topic: Topic, event: RealtimeEvent) {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = channels.get(&topic) else {
+            return;
+        };
+
+        if sender.send(event).is_err() {
+            debug!(topic = ?topic, "Dropping realtime channel without subscribers");
+            channels.remove(&topic);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::error::AppError;
+use crate::models::money_model::Money;
+use crate::models::refund_model::{CreateRefundRequest, RefundResponse, RefundStatus};
+use crate::models::webhook_event_model::WebhookEventStatus;
+use crate::services::payment_gateway::{
+    PaymentProvider, RefundOutcome, RefundRequest, WebhookEvent,
+};
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, Row, Transaction, types::Json};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+const REFUND_COLUMNS: &str = "id, payment_reference, gateway, gateway_refund_id, amount, \
+    currency, status, reason, failure_reason, requested_by, created_at, updated_at, processed_at";
+
+// ============================================================================
+// Refund Services
+// ============================================================================
+
+/// Refunds a successful payment in full or in part. The refund is recorded
+/// before the gateway is called, so concurrent requests cannot refund more
+/// than was paid; refunds the gateway refuses no longer count against the
+/// payment. When the gateway's answer is lost the refund stays `pending`
+/// until a `refund.*` webhook reports on it.
+pub async fn create_refund_service(
+    pool: &PgPool,
+    provider: &PaymentProvider,
+    reference: &str,
+    requested_by: &str,
+    payload: CreateRefundRequest,
+) -> Result<RefundResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let (gateway_name, paid) = lock_payment(&mut tx, reference).await?;
+    let gateway = provider.gateway(&gateway_name)?;
+
+    let refunded: i64 = sqlx::query(
+        "SELECT COALESCE(SUM(amount), 0)::BIGINT AS refunded FROM refunds \
+        WHERE payment_reference = $1 AND gateway = $2 AND status <> 'failed'",
+    )
+    .bind(reference)
+    .bind(&gateway_name)
+    .fetch_one(&mut *tx)
+    .await?
+    .get("refunded");
+
+    let remaining = paid.minor_units - refunded;
+    if remaining <= 0 {
+        return Err(AppError::conflict(
+            "Payment has already been refunded in full",
+        ));
+    }
+    let amount = payload.amount.unwrap_or(remaining);
+    if amount <= 0 || amount > remaining {
+        return Err(AppError::Validation(format!(
+            "amount must be between 1 and {} minor units",
+            remaining
+        )));
+    }
+    let amount = Money::new(amount, paid.currency);
+
+    let refund_id = Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO refunds (id, payment_reference, gateway, amount, currency, status, \
+        reason, requested_by) VALUES ($1, $2, $3, $4, $5, 'pending', $6, $7)",
+    )
+    .bind(&refund_id)
+    .bind(reference)
+    .bind(&gateway_name)
+    .bind(amount.minor_units)
+    .bind(amount.currency.as_str())
+    .bind(&payload.reason)
+    .bind(requested_by)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let request = RefundRequest {
+        reference: reference.to_string(),
+        amount: Some(amount),
+        reason: payload.reason,
+    };
+    let result = match gateway.refund(&request).await {
+        Ok(RefundOutcome::Accepted(result)) => result,
+        Ok(RefundOutcome::Rejected(reason)) => {
+            error!(reference = %reference, refund_id = %refund_id, reason = %reason, "Gateway refused the refund");
+            sqlx::query(
+                "UPDATE refunds SET status = 'failed', failure_reason = $2, updated_at = $3 \
+                WHERE id = $1",
+            )
+            .bind(&refund_id)
+            .bind(&reason)
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+            return Err(AppError::PaymentGateway(reason));
+        }
+        Err(e) => {
+            // The gateway may have accepted it; a `refund.*` webhook settles it.
+            error!(reference = %reference, refund_id = %refund_id, error = %e, "Refund outcome unknown");
+            return Err(AppError::PaymentGateway(format!(
+                "The gateway did not confirm refund {}; it stays pending until the gateway reports on it",
+                refund_id
+            )));
+        }
+    };
+
+    // A webhook may have moved the refund on already; never move it back.
+    let now = Utc::now();
+    let row = sqlx::query(&format!(
+        "UPDATE refunds SET gateway_refund_id = $2, raw_payload = $3, updated_at = $4, \
+        status = CASE WHEN status = ANY($6) THEN $5 ELSE status END, \
+        processed_at = CASE WHEN status = ANY($6) AND $5 = 'processed' THEN $4 \
+            ELSE processed_at END \
+        WHERE id = $1 RETURNING {}",
+        REFUND_COLUMNS
+    ))
+    .bind(&refund_id)
+    .bind(&result.refund_id)
+    .bind(Json(result.raw))
+    .bind(now)
+    .bind(result.status.as_str())
+    .bind(result.status.previous_states())
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        reference = %reference,
+        refund_id = %refund_id,
+        amount = %amount,
+        status = result.status.as_str(),
+        "Refund accepted by gateway"
+    );
+
+    row_to_refund_response(&row)
+}
+
+pub async fn list_refunds_service(
+    pool: &PgPool,
+    reference: &str,
+) -> Result<Vec<RefundResponse>, AppError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM refunds WHERE payment_reference = $1 ORDER BY created_at DESC",
+        REFUND_COLUMNS
+    ))
+    .bind(reference)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(row_to_refund_response).collect()
+}
+
+// ============================================================================
+// Webhook Service
+// ============================================================================
+
+/// Moves a refund forward from a `refund.*` webhook. The refund is found by
+/// the gateway's id, or else as the oldest open refund of the same amount on
+/// the payment, for notifications that arrive before the gateway answered.
+/// Repeated and out-of-order notifications are ignored.
+pub async fn apply_refund_event(
+    pool: &PgPool,
+    gateway: &str,
+    event: &WebhookEvent,
+    status: RefundStatus,
+) -> Result<WebhookEventStatus, AppError> {
+    let refund_id: Option<String> = sqlx::query(
+        "SELECT id FROM refunds WHERE gateway = $1 AND payment_reference = $2 \
+        AND (gateway_refund_id = $3 \
+            OR (($3::TEXT IS NULL OR gateway_refund_id IS NULL) \
+                AND status IN ('pending', 'processing') \
+                AND ($4::BIGINT IS NULL OR amount = $4))) \
+        ORDER BY (gateway_refund_id = $3) IS TRUE DESC, created_at LIMIT 1",
+    )
+    .bind(gateway)
+    .bind(&event.reference)
+    .bind(&event.refund_id)
+    .bind(event.amount.map(|money| money.minor_units))
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.get("id"));
+
+    let Some(refund_id) = refund_id else {
+        warn!(
+            reference = %event.reference,
+            gateway_refund_id = ?event.refund_id,
+            "Webhook names a refund we did not issue"
+        );
+        return Ok(WebhookEventStatus::Ignored);
+    };
+
+    let now = Utc::now();
+    let updated = sqlx::query(
+        "UPDATE refunds SET status = $2, \
+        gateway_refund_id = COALESCE(gateway_refund_id, $3), \
+        raw_payload = $4, updated_at = $5, \
+        processed_at = CASE WHEN $2 = 'processed' THEN $5 ELSE processed_at END, \
+        failure_reason = CASE WHEN $2 = 'failed' \
+            THEN COALESCE($6, failure_reason, 'The gateway reported the refund failed') \
+            ELSE failure_reason END \
+        WHERE id = $1 AND status = ANY($7)",
+    )
+    .bind(&refund_id)
+    .bind(status.as_str())
+    .bind(&event.refund_id)
+    .bind(Json(&event.payload))
+    .bind(now)
+    .bind(&event.gateway_response)
+    .bind(status.previous_states())
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        info!(refund_id = %refund_id, status = status.as_str(), "Refund already past this state");
+        return Ok(WebhookEventStatus::Ignored);
+    }
+    info!(refund_id = %refund_id, status = status.as_str(), "Webhook refund update applied");
+
+    Ok(WebhookEventStatus::Processed)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Locks a payment for refunding and returns its gateway and the amount the
+/// gateway confirmed, or the amount initialized when it confirmed none.
+async fn lock_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    reference: &str,
+) -> Result<(String, Money), AppError> {
+    for table in ["user_payments", "driver_payments"] {
+        let row = sqlx::query(&format!(
+            "SELECT gateway, status, COALESCE(confirmed_amount, amount) AS paid, \
+            COALESCE(confirmed_currency, currency) AS paid_currency \
+            FROM {} WHERE reference = $1 FOR UPDATE",
+            table
+        ))
+        .bind(reference)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        if let Some(row) = row {
+            if row.get::<String, _>("status") != "success" {
+                return Err(AppError::Validation(
+                    "Only successful payments can be refunded".to_string(),
+                ));
+            }
+            let paid = Money::stored(row.get("paid"), &row.get::<String, _>("paid_currency"))?;
+            return Ok((row.get("gateway"), paid));
+        }
+    }
+
+    Err(AppError::NotFound(
+        "No payment found for reference".to_string(),
+    ))
+}
+
+fn row_to_refund_response(row: &sqlx::postgres::PgRow) -> Result<RefundResponse, AppError> {
+    Ok(RefundResponse {
+        id: row.get("id"),
+        payment_reference: row.get("payment_reference"),
+        gateway: row.get("gateway"),
+        gateway_refund_id: row.get("gateway_refund_id"),
+        amount: Money::stored(row.get("amount"), &row.get::<String, _>("currency"))?,
+        status: RefundStatus::parse(&row.get::<String, _>("status"))?,
+        reason: row.get("reason"),
+        failure_reason: row.get("failure_reason"),
+        requested_by: row.get("requested_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        processed_at: row.get("processed_at"),
+    })
+}
@@ -0,0 +1,629 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
+use crate::models::matching_model::OfferStatus;
+use crate::models::pricing_model::FareBreakdown;
+use crate::models::ride_model::{
+    CancelRideRequest, CancellationReason, CancelledBy, CreateRideRequest, RideResponse, RideStatus,
+};
+use crate::services::pricing_service::{
+    calculate_fare, estimate_trip, final_fare_service, resolve_tariff,
+};
+use crate::services::schedule_service::{cancel_schedule, create_schedule, validate_pickup_time};
+use crate::services::surge_service::current_surge;
+use crate::services::verification_service::require_verified;
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Row, types::Json};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+const RIDE_COLUMNS: &str = "id, rider_id, driver_id, status, pickup_lat, pickup_lng, pickup_address, \
+    dropoff_lat, dropoff_lng, dropoff_address, vehicle_type, currency, estimated_fare, final_fare, \
+    fare_breakdown, surge_multiplier, surge_zone, surge_demand, surge_supply, scheduled_for, \
+    requested_at, accepted_at, arriving_at, started_at, completed_at, cancelled_at, cancelled_by, \
+    cancellation_reason, cancellation_note, cancellation_fee, created_at, updated_at";
+const MAX_CANCELLATION_NOTE_LENGTH: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct CancellationConfig {
+    /// How long after acceptance a rider may cancel for free.
+    pub grace_seconds: i64,
+    /// Fee for a rider cancelling after the grace window, in minor units.
+    pub fee: i64,
+}
+
+impl CancellationConfig {
+    pub fn from_env() -> Self {
+        CancellationConfig {
+            grace_seconds: env_or("CANCELLATION_GRACE_SECONDS", 120),
+            fee: env_or("CANCELLATION_FEE", 50_000),
+        }
+    }
+}
+
+// ============================================================================
+// Ride Request Services
+// ============================================================================
+
+pub async fn create_ride_service(
+    pool: &PgPool,
+    rider_id: String,
+    payload: CreateRideRequest,
+) -> Result<RideResponse, AppError> {
+    debug!(rider_id = %rider_id, "Starting ride request creation");
+
+    // Validate input
+    validate_coordinates(payload.pickup_lat, payload.pickup_lng)?;
+    validate_coordinates(payload.dropoff_lat, payload.dropoff_lng)?;
+    if let Some(pickup_at) = payload.scheduled_for {
+        validate_pickup_time(pickup_at)?;
+    }
+
+    let rider = sqlx::query("SELECT is_active FROM users WHERE id = $1")
+        .bind(&rider_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            error!(rider_id = %rider_id, "Rider not found");
+            AppError::NotFound("Rider not found".to_string())
+        })?;
+
+    if !rider.get::<bool, _>("is_active") {
+        error!(rider_id = %rider_id, "Inactive rider attempted to request a ride");
+        return Err(AppError::Forbidden(
+            "Rider account is not active".to_string(),
+        ));
+    }
+    require_verified(pool, &rider_id, &AccountType::Rider).await?;
+
+    // Bookings for later do not block requesting a ride now.
+    if payload.scheduled_for.is_none() {
+        let active_ride = sqlx::query(
+            "SELECT id FROM rides WHERE rider_id = $1 \
+            AND status NOT IN ('scheduled', 'completed', 'cancelled') LIMIT 1",
+        )
+        .bind(&rider_id)
+        .fetch_optional(pool)
+        .await?;
+
+        if active_ride.is_some() {
+            error!(rider_id = %rider_id, "Rider already has an active ride");
+            return Err(AppError::conflict("Rider already has an active ride"));
+        }
+    }
+
+    // Quote the fare upfront; the driver's company is not known yet.
+    let tariff = resolve_tariff(pool, None, payload.vehicle_type.as_deref()).await?;
+    let (distance_km, duration_seconds) = estimate_trip(
+        payload.pickup_lat,
+        payload.pickup_lng,
+        payload.dropoff_lat,
+        payload.dropoff_lng,
+    );
+    // Current demand says nothing about a future pickup, so bookings never surge.
+    let surge = match payload.scheduled_for {
+        None => Some(current_surge(pool, payload.pickup_lat, payload.pickup_lng).await?),
+        Some(_) => None,
+    };
+    let surge_multiplier = surge.as_ref().map_or(1.0, |surge| surge.multiplier);
+    let estimate = calculate_fare(&tariff, distance_km, duration_seconds, surge_multiplier);
+
+    let status = match payload.scheduled_for {
+        Some(_) => RideStatus::Scheduled,
+        None => RideStatus::Requested,
+    };
+    let ride_id = Uuid::new_v4().to_string();
+    let mut tx = pool.begin().await?;
+
+    let now = Utc::now();
+    let row = sqlx::query(&format!(
+        "INSERT INTO rides \
+        (id, rider_id, status, pickup_lat, pickup_lng, pickup_address, dropoff_lat, dropoff_lng, dropoff_address, \
+        vehicle_type, currency, estimated_fare, fare_breakdown, surge_multiplier, surge_zone, surge_demand, surge_supply, \
+        scheduled_for, requested_at, created_at, updated_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20,$21) \
+        RETURNING {}",
+        RIDE_COLUMNS
+    ))
+    .bind(&ride_id)
+    .bind(&rider_id)
+    .bind(status.as_str())
+    .bind(payload.pickup_lat)
+    .bind(payload.pickup_lng)
+    .bind(&payload.pickup_address)
+    .bind(payload.dropoff_lat)
+    .bind(payload.dropoff_lng)
+    .bind(&payload.dropoff_address)
+    .bind(&payload.vehicle_type)
+    .bind(&estimate.currency)
+    .bind(estimate.total)
+    .bind(Json(&estimate))
+    .bind(surge_multiplier)
+    .bind(surge.as_ref().map(|surge| surge.zone_id.clone()))
+    .bind(surge.as_ref().map(|surge| surge.demand))
+    .bind(surge.as_ref().map(|surge| surge.supply))
+    .bind(payload.scheduled_for)
+    .bind(now)
+    .bind(now)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if let Some(pickup_at) = payload.scheduled_for {
+        create_schedule(&mut tx, &ride_id, pickup_at).await?;
+        info!(ride_id = %ride_id, pickup_at = %pickup_at, "Ride scheduled");
+    }
+
+    tx.commit().await?;
+
+    row_to_ride_response(row)
+}
+
+pub async fn get_ride_service(pool: &PgPool, id: String) -> Result<RideResponse, AppError> {
+    debug!(ride_id = %id, "Fetching ride from database");
+
+    let row = sqlx::query(&format!("SELECT {} FROM rides WHERE id = $1", RIDE_COLUMNS))
+        .bind(&id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            error!(ride_id = %id, "Ride not found");
+            AppError::NotFound("Ride not found".to_string())
+        })?;
+
+    row_to_ride_response(row)
+}
+
+/// Lists every ride the given account took part in, either as rider or driver.
+pub async fn list_rides_service(
+    pool: &PgPool,
+    participant_id: String,
+) -> Result<Vec<RideResponse>, AppError> {
+    debug!(participant_id = %participant_id, "Fetching rides from database");
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM rides WHERE rider_id = $1 OR driver_id = $1 ORDER BY requested_at DESC",
+        RIDE_COLUMNS
+    ))
+    .bind(&participant_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!(error = %e, "Database fetch failed");
+        AppError::from(e)
+    })?;
+
+    rows.into_iter().map(row_to_ride_response).collect()
+}
+
+/// One page of the rides a user took as rider, or drove as driver, newest
+/// first, together with the total number of matching rides.
+pub async fn list_rides_page_service(
+    pool: &PgPool,
+    participant_id: &str,
+    as_driver: bool,
+    status: Option<RideStatus>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<RideResponse>, i64), AppError> {
+    let participant_column = if as_driver { "driver_id" } else { "rider_id" };
+    let status = status.map(|status| status.as_str());
+    debug!(participant_id = %participant_id, as_driver, status = ?status, limit, offset, "Fetching ride page");
+
+    let total: i64 = sqlx::query(&format!(
+        "SELECT COUNT(*) AS total FROM rides \
+        WHERE {} = $1 AND ($2::TEXT IS NULL OR status = $2)",
+        participant_column
+    ))
+    .bind(participant_id)
+    .bind(status)
+    .fetch_one(pool)
+    .await?
+    .get("total");
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM rides \
+        WHERE {} = $1 AND ($2::TEXT IS NULL OR status = $2) \
+        ORDER BY requested_at DESC, id LIMIT $3 OFFSET $4",
+        RIDE_COLUMNS, participant_column
+    ))
+    .bind(participant_id)
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let rides = rows
+        .into_iter()
+        .map(row_to_ride_response)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((rides, total))
+}
+
+// ============================================================================
+// Trip Lifecycle Services
+// ============================================================================
+
+pub async fn accept_ride_service(
+    pool: &PgPool,
+    id: String,
+    driver_id: String,
+) -> Result<RideResponse, AppError> {
+    debug!(ride_id = %id, driver_id = %driver_id, "Driver accepting ride");
+
+    let mut tx = pool.begin().await?;
+
+    let driver =
+        sqlx::query("SELECT is_available FROM transport_company_drivers WHERE id = $1 FOR UPDATE")
+            .bind(&driver_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| {
+                error!(driver_id = %driver_id, "Driver not found");
+                AppError::NotFound("Driver not found".to_string())
+            })?;
+
+    if !driver.get::<bool, _>("is_available") {
+        error!(driver_id = %driver_id, "Driver is not available");
+        return Err(AppError::conflict("Driver is not available"));
+    }
+
+    let now = Utc::now();
+
+    // Only the driver the ride is currently offered to may take it: a live
+    // pending offer, or one they have just accepted through the offer route.
+    let offer_id: String = sqlx::query(
+        "SELECT id FROM ride_offers \
+        WHERE ride_id = $1 AND driver_id = $2 \
+        AND ((status = $3 AND expires_at > $4) OR status = $5) \
+        ORDER BY offered_at DESC LIMIT 1 FOR UPDATE",
+    )
+    .bind(&id)
+    .bind(&driver_id)
+    .bind(OfferStatus::Pending.as_str())
+    .bind(now)
+    .bind(OfferStatus::Accepted.as_str())
+    .fetch_optional(&mut *tx)
+    .await?
+    .map(|row| row.get("id"))
+    .ok_or_else(|| {
+        error!(ride_id = %id, driver_id = %driver_id, "Driver holds no offer for the ride");
+        AppError::Forbidden("The ride has not been offered to you".to_string())
+    })?;
+
+    let row = sqlx::query(&format!(
+        "UPDATE rides SET status = $1, driver_id = $2, accepted_at = $3, updated_at = $3 \
+        WHERE id = $4 AND status = $5 \
+        RETURNING {}",
+        RIDE_COLUMNS
+    ))
+    .bind(RideStatus::Accepted.as_str())
+    .bind(&driver_id)
+    .bind(now)
+    .bind(&id)
+    .bind(RideStatus::Requested.as_str())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        error!(ride_id = %id, "Ride is no longer open for acceptance");
+        AppError::conflict("Ride not found or no longer open for acceptance")
+    })?;
+
+    set_driver_availability(&mut tx, &driver_id, false).await?;
+
+    sqlx::query(
+        "UPDATE ride_offers SET status = $1, responded_at = COALESCE(responded_at, $2) \
+        WHERE id = $3",
+    )
+    .bind(OfferStatus::Accepted.as_str())
+    .bind(now)
+    .bind(&offer_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE ride_offers SET status = $1, responded_at = $2 \
+        WHERE ride_id = $3 AND id <> $4 AND status = $5",
+    )
+    .bind(OfferStatus::Expired.as_str())
+    .bind(now)
+    .bind(&id)
+    .bind(&offer_id)
+    .bind(OfferStatus::Pending.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!(ride_id = %id, driver_id = %driver_id, "Ride accepted");
+
+    row_to_ride_response(row)
+}
+
+/// Moves a ride to `next` on behalf of `actor_id`, enforcing both the status
+/// graph and which participant is allowed to trigger the step.
+pub async fn transition_ride_service(
+    pool: &PgPool,
+    id: String,
+    actor_id: String,
+    next: RideStatus,
+) -> Result<RideResponse, AppError> {
+    debug!(ride_id = %id, actor_id = %actor_id, next = %next.as_str(), "Transitioning ride");
+
+    if next == RideStatus::Accepted {
+        return accept_ride_service(pool, id, actor_id).await;
+    }
+    // Cancellation carries a reason and fee policy; see `cancel_ride_service`.
+    if next == RideStatus::Cancelled {
+        return Err(AppError::Validation(
+            "Cancelling a ride requires a reason".to_string(),
+        ));
+    }
+
+    let ride = get_ride_service(pool, id.clone()).await?;
+
+    if ride.driver_id.as_deref() != Some(actor_id.as_str()) {
+        error!(ride_id = %id, actor_id = %actor_id, "Actor not permitted to transition ride");
+        return Err(AppError::Forbidden(
+            "Not permitted to perform this action on the ride".to_string(),
+        ));
+    }
+
+    if !ride.status.can_transition_to(next) {
+        error!(
+            ride_id = %id,
+            from = %ride.status.as_str(),
+            to = %next.as_str(),
+            "Invalid ride status transition"
+        );
+        return Err(AppError::conflict(format!(
+            "Cannot move ride from {} to {}",
+            ride.status.as_str(),
+            next.as_str()
+        )));
+    }
+
+    // Completed trips are priced by the same engine that quoted them.
+    let now = Utc::now();
+    let final_fare = match next {
+        RideStatus::Completed => Some(final_fare_service(pool, &ride, now).await?),
+        _ => None,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    // Guard on the current status so concurrent transitions cannot both win.
+    let row = sqlx::query(&format!(
+        "UPDATE rides SET status = $1, {} = $2, updated_at = $2, \
+        currency = COALESCE($5, currency), \
+        final_fare = COALESCE($6, final_fare), \
+        fare_breakdown = COALESCE($7, fare_breakdown) \
+        WHERE id = $3 AND status = $4 \
+        RETURNING {}",
+        next.timestamp_column(),
+        RIDE_COLUMNS
+    ))
+    .bind(next.as_str())
+    .bind(now)
+    .bind(&id)
+    .bind(ride.status.as_str())
+    .bind(final_fare.as_ref().map(|fare| fare.currency.clone()))
+    .bind(final_fare.as_ref().map(|fare| fare.total))
+    .bind(final_fare.as_ref().map(Json))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        error!(ride_id = %id, "Ride status changed concurrently");
+        AppError::conflict("Ride status changed, please retry")
+    })?;
+
+    if next.is_terminal()
+        && let Some(driver_id) = ride.driver_id.as_deref()
+    {
+        set_driver_availability(&mut tx, driver_id, true).await?;
+    }
+
+    tx.commit().await?;
+
+    info!(ride_id = %id, status = %next.as_str(), "Ride status updated");
+
+    row_to_ride_response(row)
+}
+
+/// Cancels a ride on behalf of its rider or driver. A rider who cancels
+/// after the driver has been en route longer than the grace window owes a
+/// cancellation fee; driver cancellations are counted against the driver.
+pub async fn cancel_ride_service(
+    pool: &PgPool,
+    id: String,
+    actor_id: String,
+    payload: CancelRideRequest,
+) -> Result<RideResponse, AppError> {
+    debug!(ride_id = %id, actor_id = %actor_id, reason = %payload.reason.as_str(), "Cancelling ride");
+
+    let note = payload
+        .note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_CANCELLATION_NOTE_LENGTH)
+    {
+        return Err(AppError::Validation(format!(
+            "Cancellation note must be at most {} characters",
+            MAX_CANCELLATION_NOTE_LENGTH
+        )));
+    }
+    if payload.reason == CancellationReason::NoDriverFound {
+        return Err(AppError::Validation(
+            "Reason 'no_driver_found' is reserved for the system".to_string(),
+        ));
+    }
+    if payload.reason == CancellationReason::Other && note.is_none() {
+        return Err(AppError::Validation(
+            "A note is required when the reason is 'other'".to_string(),
+        ));
+    }
+
+    let ride = get_ride_service(pool, id.clone()).await?;
+
+    let cancelled_by = if ride.rider_id == actor_id {
+        CancelledBy::Rider
+    } else if ride.driver_id.as_deref() == Some(actor_id.as_str()) {
+        CancelledBy::Driver
+    } else {
+        error!(ride_id = %id, actor_id = %actor_id, "Actor not permitted to cancel ride");
+        return Err(AppError::Forbidden(
+            "Not permitted to perform this action on the ride".to_string(),
+        ));
+    };
+
+    if !ride.status.can_transition_to(RideStatus::Cancelled) {
+        error!(ride_id = %id, status = %ride.status.as_str(), "Ride can no longer be cancelled");
+        return Err(AppError::conflict(format!(
+            "Cannot move ride from {} to {}",
+            ride.status.as_str(),
+            RideStatus::Cancelled.as_str()
+        )));
+    }
+
+    let config = CancellationConfig::from_env();
+    let now = Utc::now();
+    let fee = match (cancelled_by, ride.accepted_at) {
+        (CancelledBy::Rider, Some(accepted_at))
+            if now - accepted_at > Duration::seconds(config.grace_seconds) =>
+        {
+            Some(config.fee).filter(|fee| *fee > 0)
+        }
+        _ => None,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(&format!(
+        "UPDATE rides SET status = $1, cancelled_at = $2, updated_at = $2, \
+        cancelled_by = $3, cancellation_reason = $4, cancellation_note = $5, cancellation_fee = $6 \
+        WHERE id = $7 AND status = $8 \
+        RETURNING {}",
+        RIDE_COLUMNS
+    ))
+    .bind(RideStatus::Cancelled.as_str())
+    .bind(now)
+    .bind(cancelled_by.as_str())
+    .bind(payload.reason.as_str())
+    .bind(&note)
+    .bind(fee)
+    .bind(&id)
+    .bind(ride.status.as_str())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        error!(ride_id = %id, "Ride status changed concurrently");
+        AppError::conflict("Ride status changed, please retry")
+    })?;
+
+    if let Some(driver_id) = ride.driver_id.as_deref() {
+        set_driver_availability(&mut tx, driver_id, true).await?;
+    }
+
+    if cancelled_by == CancelledBy::Driver
+        && let Some(driver_id) = ride.driver_id.as_deref()
+    {
+        sqlx::query(
+            "UPDATE transport_company_drivers \
+            SET cancellation_count = cancellation_count + 1, updated_at = $1 WHERE id = $2",
+        )
+        .bind(now)
+        .bind(driver_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    cancel_schedule(&mut tx, &id).await?;
+
+    tx.commit().await?;
+
+    info!(
+        ride_id = %id,
+        cancelled_by = %cancelled_by.as_str(),
+        fee = ?fee,
+        "Ride cancelled"
+    );
+
+    row_to_ride_response(row)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+pub fn validate_coordinates(lat: f64, lng: f64) -> Result<(), AppError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        error!(lat, lng, "Invalid coordinates");
+        return Err(AppError::Validation("Invalid coordinates".to_string()));
+    }
+    Ok(())
+}
+
+async fn set_driver_availability(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    driver_id: &str,
+    is_available: bool,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE transport_company_drivers SET is_available = $1, updated_at = $2 WHERE id = $3",
+    )
+    .bind(is_available)
+    .bind(Utc::now())
+    .bind(driver_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+fn row_to_ride_response(row: sqlx::postgres::PgRow) -> Result<RideResponse, AppError> {
+    Ok(RideResponse {
+        id: row.get("id"),
+        rider_id: row.get("rider_id"),
+        driver_id: row.get("driver_id"),
+        status: RideStatus::parse(row.get("status"))?,
+        pickup_lat: row.get("pickup_lat"),
+        pickup_lng: row.get("pickup_lng"),
+        pickup_address: row.get("pickup_address"),
+        dropoff_lat: row.get("dropoff_lat"),
+        dropoff_lng: row.get("dropoff_lng"),
+        dropoff_address: row.get("dropoff_address"),
+        vehicle_type: row.get("vehicle_type"),
+        currency: row.get("currency"),
+        estimated_fare: row.get("estimated_fare"),
+        final_fare: row.get("final_fare"),
+        fare_breakdown: row
+            .get::<Option<Json<FareBreakdown>>, _>("fare_breakdown")
+            .map(|breakdown| breakdown.0),
+        surge_multiplier: row.get("surge_multiplier"),
+        surge_zone: row.get("surge_zone"),
+        surge_demand: row.get("surge_demand"),
+        surge_supply: row.get("surge_supply"),
+        scheduled_for: row.get("scheduled_for"),
+        cancelled_by: row
+            .get::<Option<&str>, _>("cancelled_by")
+            .map(CancelledBy::parse)
+            .transpose()?,
+        cancellation_reason: row
+            .get::<Option<&str>, _>("cancellation_reason")
+            .map(CancellationReason::parse)
+            .transpose()?,
+        cancellation_note: row.get("cancellation_note"),
+        cancellation_fee: row.get("cancellation_fee"),
+        requested_at: row.get("requested_at"),
+        accepted_at: row.get("accepted_at"),
+        arriving_at: row.get("arriving_at"),
+        started_at: row.get("started_at"),
+        completed_at: row.get("completed_at"),
+        cancelled_at: row.get("cancelled_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
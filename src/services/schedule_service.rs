@@ -0,0 +1,484 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::realtime_model::RealtimeEvent;
+use crate::models::ride_model::{CancellationReason, CancelledBy, RideStatus};
+use crate::models::schedule_model::{RideScheduleResponse, ScheduleStatus};
+use crate::services::matching_service::spawn_dispatch;
+use crate::services::notification_service::{Notification, NotificationChannel, Notifier};
+use crate::services::realtime_service::{RealtimeHub, Topic};
+use crate::services::ride_service::get_ride_service;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+const SCHEDULE_COLUMNS: &str = "id, ride_id, status, pickup_at, dispatch_at, remind_at, \
+    reminder_sent_at, dispatched_at, dispatch_attempts, created_at, updated_at";
+/// Upper bound on schedules handled per step of one scheduler tick.
+const SCHEDULER_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct ScheduleConfig {
+    pub poll_interval_seconds: u64,
+    /// How long before pickup a booking is handed to driver matching.
+    pub dispatch_lead_minutes: i64,
+    /// How long before pickup the rider is reminded.
+    pub reminder_lead_minutes: i64,
+    pub min_advance_minutes: i64,
+    pub max_advance_days: i64,
+    /// Pause between matching rounds while a booking has no driver.
+    pub redispatch_interval_seconds: i64,
+    /// A booking still without a driver this long after pickup is cancelled.
+    pub pickup_grace_minutes: i64,
+}
+
+impl ScheduleConfig {
+    pub fn from_env() -> Self {
+        ScheduleConfig {
+            poll_interval_seconds: env_or("SCHEDULER_POLL_INTERVAL_SECONDS", 30),
+            dispatch_lead_minutes: env_or("SCHEDULE_DISPATCH_LEAD_MINUTES", 15),
+            reminder_lead_minutes: env_or("SCHEDULE_REMINDER_LEAD_MINUTES", 60),
+            min_advance_minutes: env_or("SCHEDULE_MIN_ADVANCE_MINUTES", 30),
+            max_advance_days: env_or("SCHEDULE_MAX_ADVANCE_DAYS", 7),
+            redispatch_interval_seconds: env_or("SCHEDULE_REDISPATCH_INTERVAL_SECONDS", 60),
+            pickup_grace_minutes: env_or("SCHEDULE_PICKUP_GRACE_MINUTES", 10),
+        }
+    }
+}
+
+// ============================================================================
+// Booking Services
+// ============================================================================
+
+/// Rejects pickup times too close or too far in the future to schedule.
+pub fn validate_pickup_time(pickup_at: DateTime<Utc>) -> Result<(), AppError> {
+    let config = ScheduleConfig::from_env();
+    let now = Utc::now();
+
+    if pickup_at < now + Duration::minutes(config.min_advance_minutes) {
+        error!(pickup_at = %pickup_at, "Scheduled pickup too soon");
+        return Err(AppError::Validation(format!(
+            "Scheduled rides must be booked at least {} minutes ahead",
+            config.min_advance_minutes
+        )));
+    }
+    if pickup_at > now + Duration::days(config.max_advance_days) {
+        error!(pickup_at = %pickup_at, "Scheduled pickup too far ahead");
+        return Err(AppError::Validation(format!(
+            "Scheduled rides can be booked at most {} days ahead",
+            config.max_advance_days
+        )));
+    }
+    Ok(())
+}
+
+/// Stores the schedule for a newly booked ride inside the booking transaction.
+pub async fn create_schedule(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ride_id: &str,
+    pickup_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let config = ScheduleConfig::from_env();
+    let now = Utc::now();
+    let dispatch_at = (pickup_at - Duration::minutes(config.dispatch_lead_minutes)).max(now);
+    let remind_at = (pickup_at - Duration::minutes(config.reminder_lead_minutes)).max(now);
+
+    sqlx::query(
+        "INSERT INTO ride_schedules \
+        (id, ride_id, status, pickup_at, dispatch_at, remind_at, created_at, updated_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(ride_id)
+    .bind(ScheduleStatus::Pending.as_str())
+    .bind(pickup_at)
+    .bind(dispatch_at)
+    .bind(remind_at)
+    .bind(now)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+
+    debug!(ride_id = %ride_id, dispatch_at = %dispatch_at, "Ride schedule stored");
+
+    Ok(())
+}
+
+/// Closes the schedule of a ride the rider or driver cancelled.
+pub async fn cancel_schedule(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ride_id: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE ride_schedules SET status = $1, updated_at = $2 \
+        WHERE ride_id = $3 AND status IN ('pending', 'dispatched')",
+    )
+    .bind(ScheduleStatus::Cancelled.as_str())
+    .bind(Utc::now())
+    .bind(ride_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Upcoming bookings of a rider that have not found a driver yet.
+pub async fn list_ride_schedules_service(
+    pool: &PgPool,
+    rider_id: String,
+) -> Result<Vec<RideScheduleResponse>, AppError> {
+    debug!(rider_id = %rider_id, "Fetching scheduled rides");
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM ride_schedules \
+        WHERE status IN ('pending', 'dispatched') \
+        AND ride_id IN (SELECT id FROM rides WHERE rider_id = $1) \
+        ORDER BY pickup_at ASC",
+        SCHEDULE_COLUMNS
+    ))
+    .bind(&rider_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(row_to_schedule_response).collect()
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Background loop that reminds riders over the ride's WebSocket and by SMS
+/// or email, dispatches bookings when they fall due and gives up on bookings
+/// that found no driver. All state lives in `ride_schedules`, so pending
+/// bookings survive restarts, and rows are claimed with `SKIP LOCKED` so
+/// several instances can run side by side.
+pub fn spawn_ride_scheduler(pool: PgPool, hub: RealtimeHub, notifier: Notifier) {
+    let config = ScheduleConfig::from_env();
+    info!(
+        interval_seconds = config.poll_interval_seconds,
+        "Starting ride scheduler"
+    );
+
+    tokio::spawn(async move {
+        let mut ticker =
+            tokio::time::interval(std::time::Duration::from_secs(config.poll_interval_seconds));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_scheduler_tick(&pool, &hub, &notifier, &config).await {
+                error!(error = %e, "Ride scheduler tick failed");
+            }
+        }
+    });
+}
+
+async fn run_scheduler_tick(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    notifier: &Notifier,
+    config: &ScheduleConfig,
+) -> Result<(), AppError> {
+    send_due_reminders(pool, hub, notifier).await?;
+    dispatch_due_rides(pool, hub).await?;
+    follow_up_dispatched_rides(pool, hub, config).await
+}
+
+async fn send_due_reminders(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    notifier: &Notifier,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    let rows = sqlx::query(
+        "UPDATE ride_schedules SET reminder_sent_at = $1, updated_at = $1 \
+        WHERE id IN ( \
+            SELECT id FROM ride_schedules \
+            WHERE status = 'pending' AND reminder_sent_at IS NULL AND remind_at <= $1 \
+            ORDER BY remind_at LIMIT $2 FOR UPDATE SKIP LOCKED) \
+        RETURNING ride_id, pickup_at",
+    )
+    .bind(now)
+    .bind(SCHEDULER_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let ride_id: String = row.get("ride_id");
+        let pickup_at: DateTime<Utc> = row.get("pickup_at");
+        info!(ride_id = %ride_id, pickup_at = %pickup_at, "Sending scheduled ride reminder");
+        hub.publish(
+            Topic::Ride(ride_id.clone()),
+            RealtimeEvent::RideReminder {
+                ride_id: ride_id.clone(),
+                pickup_at,
+            },
+        );
+        // The rider may not have the app open; a failed send is logged only.
+        if let Err(e) = notify_rider_of_pickup(pool, notifier, &ride_id, pickup_at).await {
+            warn!(ride_id = %ride_id, error = %e, "Failed to send scheduled ride reminder");
+        }
+    }
+
+    Ok(())
+}
+
+/// Texts the rider about an upcoming pickup, or emails them when they have
+/// no phone number on file.
+async fn notify_rider_of_pickup(
+    pool: &PgPool,
+    notifier: &Notifier,
+    ride_id: &str,
+    pickup_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let Some(rider) = sqlx::query(
+        "SELECT u.email, u.phone, r.pickup_address FROM rides r \
+        JOIN users u ON u.id = r.rider_id WHERE r.id = $1",
+    )
+    .bind(ride_id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let pickup_address: Option<String> = rider.get("pickup_address");
+    let body = format!(
+        "Your RideNow ride is booked for pickup at {} UTC{}. We will start looking for a driver shortly before then.",
+        pickup_at.format("%H:%M on %d %b %Y"),
+        pickup_address
+            .map(|address| format!(" from {}", address))
+            .unwrap_or_default()
+    );
+    let (channel, recipient) = match rider.get::<Option<String>, _>("phone") {
+        Some(phone) => (NotificationChannel::Sms, phone),
+        None => (NotificationChannel::Email, rider.get("email")),
+    };
+
+    notifier
+        .send(Notification {
+            channel,
+            recipient,
+            subject: "Your RideNow pickup is coming up".to_string(),
+            body,
+        })
+        .await
+        .map_err(AppError::Internal)
+}
+
+/// Moves due bookings to `requested` and starts matching. The schedule and
+/// the ride are updated in one statement so a crash cannot split them.
+async fn dispatch_due_rides(pool: &PgPool, hub: &RealtimeHub) -> Result<(), AppError> {
+    let now = Utc::now();
+    let rows = sqlx::query(
+        "WITH due AS ( \
+            SELECT s.id, s.ride_id FROM ride_schedules s \
+            JOIN rides r ON r.id = s.ride_id AND r.status = 'scheduled' \
+            WHERE s.status = 'pending' AND s.dispatch_at <= $1 \
+            ORDER BY s.dispatch_at LIMIT $2 \
+            FOR UPDATE OF s SKIP LOCKED), \
+        claimed AS ( \
+            UPDATE ride_schedules s \
+            SET status = 'dispatched', dispatched_at = $1, last_dispatch_at = $1, \
+                dispatch_attempts = s.dispatch_attempts + 1, updated_at = $1 \
+            FROM due WHERE s.id = due.id \
+            RETURNING s.ride_id) \
+        UPDATE rides r SET status = 'requested', requested_at = $1, updated_at = $1 \
+        FROM claimed WHERE r.id = claimed.ride_id AND r.status = 'scheduled' \
+        RETURNING r.id",
+    )
+    .bind(now)
+    .bind(SCHEDULER_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let ride_id: String = row.get("id");
+        info!(ride_id = %ride_id, "Dispatching scheduled ride");
+        publish_ride_status(pool, hub, &ride_id).await;
+        spawn_dispatch(pool.clone(), hub.clone(), ride_id);
+    }
+
+    Ok(())
+}
+
+/// Settles dispatched bookings: records a match, retries matching while
+/// there is time, and cancels the ride once the pickup deadline passes.
+async fn follow_up_dispatched_rides(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    config: &ScheduleConfig,
+) -> Result<(), AppError> {
+    let rows = sqlx::query(
+        "SELECT s.id, s.ride_id, s.pickup_at, s.last_dispatch_at, r.status AS ride_status, \
+        EXISTS (SELECT 1 FROM ride_offers o WHERE o.ride_id = s.ride_id AND o.status = 'pending') \
+            AS has_pending_offer \
+        FROM ride_schedules s JOIN rides r ON r.id = s.ride_id \
+        WHERE s.status = 'dispatched' \
+        ORDER BY s.pickup_at LIMIT $1",
+    )
+    .bind(SCHEDULER_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let now = Utc::now();
+    for row in rows {
+        let schedule_id: String = row.get("id");
+        let ride_id: String = row.get("ride_id");
+        let pickup_at: DateTime<Utc> = row.get("pickup_at");
+        let last_dispatch_at: Option<DateTime<Utc>> = row.get("last_dispatch_at");
+        let has_pending_offer: bool = row.get("has_pending_offer");
+
+        match RideStatus::parse(row.get("ride_status"))? {
+            RideStatus::Requested => {
+                if now > pickup_at + Duration::minutes(config.pickup_grace_minutes) {
+                    expire_scheduled_ride(pool, hub, &schedule_id, &ride_id).await?;
+                } else if !has_pending_offer {
+                    let retry_before = now - Duration::seconds(config.redispatch_interval_seconds);
+                    if last_dispatch_at.is_none_or(|at| at <= retry_before) {
+                        redispatch(pool, hub, &schedule_id, &ride_id, retry_before).await?;
+                    }
+                }
+            }
+            RideStatus::Cancelled => {
+                set_schedule_status(pool, &schedule_id, ScheduleStatus::Cancelled).await?;
+            }
+            RideStatus::Scheduled => {}
+            _ => {
+                debug!(ride_id = %ride_id, "Scheduled ride matched");
+                set_schedule_status(pool, &schedule_id, ScheduleStatus::Matched).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn redispatch(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    schedule_id: &str,
+    ride_id: &str,
+    retry_before: DateTime<Utc>,
+) -> Result<(), AppError> {
+    // Conditional on the previous attempt so only one instance retries.
+    let now = Utc::now();
+    let claimed = sqlx::query(
+        "UPDATE ride_schedules \
+        SET last_dispatch_at = $1, dispatch_attempts = dispatch_attempts + 1, updated_at = $1 \
+        WHERE id = $2 AND status = 'dispatched' \
+        AND (last_dispatch_at IS NULL OR last_dispatch_at <= $3)",
+    )
+    .bind(now)
+    .bind(schedule_id)
+    .bind(retry_before)
+    .execute(pool)
+    .await?;
+
+    if claimed.rows_affected() > 0 {
+        debug!(ride_id = %ride_id, "Retrying matching for scheduled ride");
+        spawn_dispatch(pool.clone(), hub.clone(), ride_id.to_string());
+    }
+
+    Ok(())
+}
+
+async fn expire_scheduled_ride(
+    pool: &PgPool,
+    hub: &RealtimeHub,
+    schedule_id: &str,
+    ride_id: &str,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    let cancelled = sqlx::query(
+        "UPDATE rides SET status = $1, cancelled_at = $2, updated_at = $2, \
+        cancelled_by = $3, cancellation_reason = $4 \
+        WHERE id = $5 AND status = $6",
+    )
+    .bind(RideStatus::Cancelled.as_str())
+    .bind(now)
+    .bind(CancelledBy::System.as_str())
+    .bind(CancellationReason::NoDriverFound.as_str())
+    .bind(ride_id)
+    .bind(RideStatus::Requested.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    if cancelled.rows_affected() == 0 {
+        // A driver accepted in the meantime; the next tick records the match.
+        return Ok(());
+    }
+
+    sqlx::query(
+        "UPDATE ride_offers SET status = 'expired', responded_at = $1 \
+        WHERE ride_id = $2 AND status = 'pending'",
+    )
+    .bind(now)
+    .bind(ride_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE ride_schedules SET status = $1, updated_at = $2 WHERE id = $3")
+        .bind(ScheduleStatus::Expired.as_str())
+        .bind(now)
+        .bind(schedule_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    warn!(ride_id = %ride_id, "Scheduled ride cancelled: no driver found before pickup");
+    publish_ride_status(pool, hub, ride_id).await;
+
+    Ok(())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+async fn set_schedule_status(
+    pool: &PgPool,
+    schedule_id: &str,
+    status: ScheduleStatus,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE ride_schedules SET status = $1, updated_at = $2 \
+        WHERE id = $3 AND status = 'dispatched'",
+    )
+    .bind(status.as_str())
+    .bind(Utc::now())
+    .bind(schedule_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn publish_ride_status(pool: &PgPool, hub: &RealtimeHub, ride_id: &str) {
+    match get_ride_service(pool, ride_id.to_string()).await {
+        Ok(ride) => hub.publish(
+            Topic::Ride(ride_id.to_string()),
+            RealtimeEvent::RideStatus {
+                ride: Box::new(ride),
+            },
+        ),
+        Err(e) => error!(ride_id = %ride_id, error = %e, "Failed to load ride for realtime update"),
+    }
+}
+
+fn row_to_schedule_response(row: sqlx::postgres::PgRow) -> Result<RideScheduleResponse, AppError> {
+    Ok(RideScheduleResponse {
+        id: row.get("id"),
+        ride_id: row.get("ride_id"),
+        status: ScheduleStatus::parse(row.get("status"))?,
+        pickup_at: row.get("pickup_at"),
+        dispatch_at: row.get("dispatch_at"),
+        remind_at: row.get("remind_at"),
+        reminder_sent_at: row.get("reminder_sent_at"),
+        dispatched_at: row.get("dispatched_at"),
+        dispatch_attempts: row.get("dispatch_attempts"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
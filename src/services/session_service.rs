@@ -0,0 +1,163 @@
+use crate::auth::password_utils::Claims;
+use crate::error::AppError;
+use crate::models::auth_model::{DevicePlatform, SessionSummary};
+use crate::services::auth_service::revoke_account_sessions;
+use chrono::Utc;
+use sqlx::{PgPool, Row};
+use tracing::info;
+
+const MAX_PUSH_TOKEN_LEN: usize = 4096;
+
+// ============================================================================
+// Session Services
+// ============================================================================
+
+/// Open sessions of an account, most recently seen first. `current_session`
+/// marks the session of the caller.
+pub async fn list_sessions_service(
+    pool: &PgPool,
+    account_id: &str,
+    current_session: Option<&str>,
+) -> Result<Vec<SessionSummary>, AppError> {
+    let rows = sqlx::query(
+        "SELECT id, device_name, platform, ip_address, user_agent, created_at, last_used_at, \
+        expires_at, two_factor, push_token IS NOT NULL AS push_enabled \
+        FROM auth_sessions \
+        WHERE account_id = $1 AND revoked_at IS NULL AND expires_at > $2 \
+        ORDER BY last_used_at DESC",
+    )
+    .bind(account_id)
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let id: String = row.get("id");
+            SessionSummary {
+                current: current_session == Some(id.as_str()),
+                id,
+                device_name: row.get("device_name"),
+                platform: row
+                    .get::<Option<String>, _>("platform")
+                    .as_deref()
+                    .and_then(DevicePlatform::parse),
+                ip_address: row.get("ip_address"),
+                user_agent: row.get("user_agent"),
+                created_at: row.get("created_at"),
+                last_seen_at: row.get("last_used_at"),
+                expires_at: row.get("expires_at"),
+                two_factor: row.get("two_factor"),
+                push_enabled: row.get("push_enabled"),
+            }
+        })
+        .collect())
+}
+
+/// Signs out one session of an account, e.g. a lost phone. Sessions of
+/// other accounts are reported as not found.
+pub async fn revoke_account_session_service(
+    pool: &PgPool,
+    account_id: &str,
+    session_id: &str,
+    reason: &str,
+) -> Result<(), AppError> {
+    let result = sqlx::query(
+        "UPDATE auth_sessions SET revoked_at = $3, revoked_reason = $4, push_token = NULL \
+        WHERE id = $1 AND account_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(account_id)
+    .bind(Utc::now())
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    info!(account_id = %account_id, session_id = %session_id, reason, "Session revoked");
+
+    Ok(())
+}
+
+/// Signs out every session of an account on behalf of support.
+pub async fn revoke_all_account_sessions_service(
+    pool: &PgPool,
+    account_id: &str,
+) -> Result<u64, AppError> {
+    let revoked = revoke_account_sessions(pool, account_id, "support").await?;
+
+    info!(account_id = %account_id, revoked, "All sessions revoked by support");
+
+    Ok(revoked)
+}
+
+// ============================================================================
+// Push Token Services
+// ============================================================================
+
+/// Attaches a push token to the caller's session. A device that signs in
+/// again keeps its token, so the token moves off any older session.
+pub async fn register_push_token_service(
+    pool: &PgPool,
+    claims: &Claims,
+    push_token: &str,
+) -> Result<(), AppError> {
+    let push_token = push_token.trim();
+    if push_token.is_empty() || push_token.len() > MAX_PUSH_TOKEN_LEN {
+        return Err(AppError::Validation("Invalid push token".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE auth_sessions SET push_token = NULL, push_token_updated_at = $3 \
+        WHERE push_token = $1 AND id <> $2",
+    )
+    .bind(push_token)
+    .bind(&claims.sid)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query(
+        "UPDATE auth_sessions SET push_token = $2, push_token_updated_at = $3 \
+        WHERE id = $1 AND revoked_at IS NULL",
+    )
+    .bind(&claims.sid)
+    .bind(push_token)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    tx.commit().await?;
+
+    info!(account_id = %claims.sub, session_id = %claims.sid, "Push token registered");
+
+    Ok(())
+}
+
+pub async fn remove_push_token_service(pool: &PgPool, claims: &Claims) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE auth_sessions SET push_token = NULL, push_token_updated_at = $2 WHERE id = $1",
+    )
+    .bind(&claims.sid)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    info!(account_id = %claims.sub, session_id = %claims.sid, "Push token removed");
+
+    Ok(())
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
@@ -0,0 +1,200 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::pricing_model::SurgeSnapshot;
+use crate::services::matching_service::MatchingConfig;
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+
+const KM_PER_DEGREE_LAT: f64 = 111.32;
+
+#[derive(Debug, Clone)]
+pub struct SurgeConfig {
+    pub enabled: bool,
+    /// Edge length of the square grid cells used as surge zones.
+    pub zone_size_km: f64,
+    /// How far back open ride requests count towards demand.
+    pub window_seconds: i64,
+    /// Below this many open requests a zone never surges.
+    pub min_demand: i64,
+    /// Multiplier growth per unit of demand/supply ratio above 1.
+    pub sensitivity: f64,
+    pub max_multiplier: f64,
+    /// Multipliers are rounded to this step so prices do not flicker.
+    pub step: f64,
+}
+
+impl SurgeConfig {
+    pub fn from_env() -> Self {
+        SurgeConfig {
+            enabled: env_or("SURGE_ENABLED", true),
+            zone_size_km: env_or("SURGE_ZONE_SIZE_KM", 2.0),
+            window_seconds: env_or("SURGE_WINDOW_SECONDS", 600),
+            min_demand: env_or("SURGE_MIN_DEMAND", 3),
+            sensitivity: env_or("SURGE_SENSITIVITY", 0.5),
+            max_multiplier: env_or("SURGE_MAX_MULTIPLIER", 2.5),
+            step: env_or("SURGE_STEP", 0.1),
+        }
+    }
+}
+
+/// A square cell of the surge grid.
+#[derive(Debug, Clone)]
+pub struct Zone {
+    pub id: String,
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lng: f64,
+    pub max_lng: f64,
+}
+
+// ============================================================================
+// Surge Calculation
+// ============================================================================
+
+/// Grid cell containing a point. Cells are fixed in degrees so the same
+/// pickup always maps to the same zone id.
+pub fn zone_for(lat: f64, lng: f64, config: &SurgeConfig) -> Zone {
+    let size_deg = config.zone_size_km.max(0.1) / KM_PER_DEGREE_LAT;
+    let lat_index = (lat / size_deg).floor();
+    let lng_index = (lng / size_deg).floor();
+
+    Zone {
+        id: format!("{}:{}", lat_index as i64, lng_index as i64),
+        min_lat: lat_index * size_deg,
+        max_lat: (lat_index + 1.0) * size_deg,
+        min_lng: lng_index * size_deg,
+        max_lng: (lng_index + 1.0) * size_deg,
+    }
+}
+
+/// Multiplier for the given demand and supply, capped and rounded.
+pub fn surge_multiplier(demand: i64, supply: i64, config: &SurgeConfig) -> f64 {
+    if !config.enabled || demand < config.min_demand || demand <= supply {
+        return 1.0;
+    }
+
+    let ratio = demand as f64 / supply.max(1) as f64;
+    let raw = 1.0 + (ratio - 1.0) * config.sensitivity;
+    let step = config.step.max(0.01);
+    let rounded = (raw / step).round() * step;
+
+    rounded.clamp(1.0, config.max_multiplier.max(1.0))
+}
+
+/// Observes the pickup zone and returns the surge that applies to a ride
+/// requested there right now.
+pub async fn current_surge(pool: &PgPool, lat: f64, lng: f64) -> Result<SurgeSnapshot, AppError> {
+    let config = SurgeConfig::from_env();
+    let zone = zone_for(lat, lng, &config);
+
+    if !config.enabled {
+        return Ok(SurgeSnapshot {
+            zone_id: zone.id,
+            demand: 0,
+            supply: 0,
+            multiplier: 1.0,
+        });
+    }
+
+    let now = Utc::now();
+    let demand_since = now - Duration::seconds(config.window_seconds);
+    let fresh_after = now - Duration::seconds(MatchingConfig::from_env().location_max_age_seconds);
+
+    let row = sqlx::query(
+        "SELECT \
+        (SELECT COUNT(*) FROM rides \
+            WHERE status = 'requested' AND requested_at >= $1 \
+            AND pickup_lat >= $3 AND pickup_lat < $4 \
+            AND pickup_lng >= $5 AND pickup_lng < $6) AS demand, \
+        (SELECT COUNT(*) FROM transport_company_drivers d \
+            JOIN driver_locations l ON l.driver_id = d.id \
+            WHERE d.is_available = TRUE AND l.updated_at >= $2 \
+            AND l.lat >= $3 AND l.lat < $4 \
+            AND l.lng >= $5 AND l.lng < $6) AS supply",
+    )
+    .bind(demand_since)
+    .bind(fresh_after)
+    .bind(zone.min_lat)
+    .bind(zone.max_lat)
+    .bind(zone.min_lng)
+    .bind(zone.max_lng)
+    .fetch_one(pool)
+    .await?;
+
+    let demand: i64 = row.get("demand");
+    let supply: i64 = row.get("supply");
+    let multiplier = surge_multiplier(demand, supply, &config);
+
+    if multiplier > 1.0 {
+        info!(zone_id = %zone.id, demand, supply, multiplier, "Surge pricing active");
+    } else {
+        debug!(zone_id = %zone.id, demand, supply, "No surge in zone");
+    }
+
+    Ok(SurgeSnapshot {
+        zone_id: zone.id,
+        demand: demand as i32,
+        supply: supply as i32,
+        multiplier,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SurgeConfig {
+        SurgeConfig {
+            enabled: true,
+            zone_size_km: 2.0,
+            window_seconds: 600,
+            min_demand: 3,
+            sensitivity: 0.5,
+            max_multiplier: 2.5,
+            step: 0.1,
+        }
+    }
+
+    fn assert_multiplier(demand: i64, supply: i64, expected: f64) {
+        let multiplier = surge_multiplier(demand, supply, &config());
+        assert!(
+            (multiplier - expected).abs() < 1e-9,
+            "demand {demand}, supply {supply}: got {multiplier}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn does_not_surge_without_excess_demand() {
+        assert_multiplier(2, 0, 1.0);
+        assert_multiplier(5, 5, 1.0);
+        assert_multiplier(4, 9, 1.0);
+    }
+
+    #[test]
+    fn grows_with_the_demand_to_supply_ratio() {
+        assert_multiplier(6, 2, 2.0);
+        assert_multiplier(4, 2, 1.5);
+    }
+
+    #[test]
+    fn rounds_to_the_configured_step() {
+        // A ratio of 4/3 gives 1.1666…, shown as 1.2.
+        assert_multiplier(4, 3, 1.2);
+    }
+
+    #[test]
+    fn caps_at_the_maximum() {
+        assert_multiplier(100, 1, 2.5);
+        assert_multiplier(5, 0, 2.5);
+    }
+
+    #[test]
+    fn does_not_surge_when_disabled() {
+        let config = SurgeConfig {
+            enabled: false,
+            ..config()
+        };
+        assert_eq!(surge_multiplier(100, 1, &config), 1.0);
+    }
+}
@@ -1,9 +1,14 @@
 use crate::auth::password_utils::hash_password;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
 use crate::models::transport_company_model::{
-    CreateDriverRequest, CreateTransportCompanyRequest, CreateVehicleRequest, Driver,
-    DriverResponse, TransportCompany, TransportCompanyResponse, UpdateDriverRequest,
-    UpdateTransportCompanyRequest, UpdateVehicleRequest, Vehicle, VehicleResponse,
+    CompanyAdmin, CompanyAdminResponse, CreateCompanyAdminRequest, CreateDriverRequest,
+    CreateTransportCompanyRequest, CreateVehicleRequest, Driver, DriverResponse, TransportCompany,
+    TransportCompanyResponse, UpdateDriverRequest, UpdateTransportCompanyRequest,
+    UpdateVehicleRequest, Vehicle, VehicleResponse,
 };
+use crate::services::auth_service::revoke_account_sessions;
+use crate::services::verification_service::{normalize_optional_phone, require_verified};
 use chrono::Utc;
 use sqlx::{PgPool, Row};
 use tracing::{debug, error};
@@ -16,13 +21,15 @@ use uuid::Uuid;
 pub async fn create_transport_company_service(
     pool: &PgPool,
     payload: CreateTransportCompanyRequest,
-) -> Result<TransportCompanyResponse, String> {
+) -> Result<TransportCompanyResponse, AppError> {
     debug!(email = %payload.email, "Starting transport company creation");
 
     // Validate input
     if payload.name.is_empty() || payload.email.is_empty() {
         error!("Name and email are required");
-        return Err("Name and email are required".to_string());
+        return Err(AppError::Validation(
+            "Name and email are required".to_string(),
+        ));
     }
 
     // Create transport company object
@@ -35,15 +42,16 @@ pub async fn create_transport_company_service(
         vehicles: None,
         drivers: None,
         is_active: true,
+        payment_gateway: payload.payment_gateway,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
     let row = sqlx::query(
         "INSERT INTO transport_companies \
-        (id, name, email, phone, address, is_active, created_at, updated_at) \
-        VALUES ($1,$2,$3,$4,$5,$6,$7,$8) \
-        RETURNING id, name, email, phone, address, is_active, created_at, updated_at",
+        (id, name, email, phone, address, is_active, payment_gateway, created_at, updated_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9) \
+        RETURNING id, name, email, phone, address, is_active, payment_gateway, created_at, updated_at",
     )
     .bind(&company.id)
     .bind(&company.name)
@@ -51,25 +59,24 @@ pub async fn create_transport_company_service(
     .bind(&company.phone)
     .bind(&company.address)
     .bind(company.is_active)
+    .bind(&company.payment_gateway)
     .bind(company.created_at)
     .bind(company.updated_at)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_transport_company_response(row))
 }
 
 pub async fn list_transport_companies_service(
     pool: &PgPool,
-) -> Result<Vec<TransportCompanyResponse>, String> {
+) -> Result<Vec<TransportCompanyResponse>, AppError> {
     let rows = sqlx::query(
-        "SELECT id, name, email, phone, address, is_active, created_at, updated_at \
+        "SELECT id, name, email, phone, address, is_active, payment_gateway, created_at, updated_at \
         FROM transport_companies",
     )
     .fetch_all(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(rows
         .into_iter()
@@ -80,15 +87,14 @@ pub async fn list_transport_companies_service(
 pub async fn get_transport_company_service(
     pool: &PgPool,
     id: String,
-) -> Result<TransportCompanyResponse, String> {
+) -> Result<TransportCompanyResponse, AppError> {
     let row = sqlx::query(
-        "SELECT id, name, email, phone, address, is_active, created_at, updated_at \
+        "SELECT id, name, email, phone, address, is_active, payment_gateway, created_at, updated_at \
         FROM transport_companies WHERE id = $1",
     )
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_transport_company_response(row))
 }
@@ -97,7 +103,7 @@ pub async fn update_transport_company_service(
     pool: &PgPool,
     id: String,
     payload: UpdateTransportCompanyRequest,
-) -> Result<TransportCompanyResponse, String> {
+) -> Result<TransportCompanyResponse, AppError> {
     let row = sqlx::query(
         "UPDATE transport_companies SET \
         name = COALESCE($1, name), \
@@ -105,20 +111,21 @@ pub async fn update_transport_company_service(
         phone = COALESCE($3, phone), \
         address = COALESCE($4, address), \
         is_active = COALESCE($5, is_active), \
-        updated_at = $6 \
-        WHERE id = $7 \
-        RETURNING id, name, email, phone, address, is_active, created_at, updated_at",
+        payment_gateway = CASE WHEN $6::TEXT IS NULL THEN payment_gateway ELSE NULLIF($6, '') END, \
+        updated_at = $7 \
+        WHERE id = $8 \
+        RETURNING id, name, email, phone, address, is_active, payment_gateway, created_at, updated_at",
     )
     .bind(payload.name)
     .bind(payload.email)
     .bind(payload.phone)
     .bind(payload.address)
     .bind(payload.is_active)
+    .bind(payload.payment_gateway)
     .bind(Utc::now())
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_transport_company_response(row))
 }
@@ -127,17 +134,16 @@ pub async fn patch_transport_company_service(
     pool: &PgPool,
     id: String,
     payload: UpdateTransportCompanyRequest,
-) -> Result<TransportCompanyResponse, String> {
+) -> Result<TransportCompanyResponse, AppError> {
     // For simplicity, this is the same as update - in a real implementation, you might want to handle this differently
     update_transport_company_service(pool, id, payload).await
 }
 
-pub async fn delete_transport_company_service(pool: &PgPool, id: String) -> Result<(), String> {
+pub async fn delete_transport_company_service(pool: &PgPool, id: String) -> Result<(), AppError> {
     sqlx::query("DELETE FROM transport_companies WHERE id = $1")
         .bind(id)
         .execute(pool)
-        .await
-        .map_err(map_db_err)?;
+        .await?;
 
     Ok(())
 }
@@ -149,13 +155,15 @@ pub async fn delete_transport_company_service(pool: &PgPool, id: String) -> Resu
 pub async fn create_vehicle_service(
     pool: &PgPool,
     payload: CreateVehicleRequest,
-) -> Result<VehicleResponse, String> {
+) -> Result<VehicleResponse, AppError> {
     debug!(license_plate = %payload.license_plate, "Starting vehicle creation");
 
     // Validate input
     if payload.make.is_empty() || payload.model.is_empty() || payload.license_plate.is_empty() {
         error!("Make, model, and license plate are required");
-        return Err("Make, model, and license plate are required".to_string());
+        return Err(AppError::Validation(
+            "Make, model, and license plate are required".to_string(),
+        ));
     }
 
     // Create vehicle object
@@ -185,8 +193,7 @@ pub async fn create_vehicle_service(
     .bind(vehicle.created_at)
     .bind(vehicle.updated_at)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_vehicle_response(row))
 }
@@ -194,28 +201,26 @@ pub async fn create_vehicle_service(
 pub async fn list_vehicles_service(
     pool: &PgPool,
     transport_company_id: String,
-) -> Result<Vec<VehicleResponse>, String> {
+) -> Result<Vec<VehicleResponse>, AppError> {
     let rows = sqlx::query(
         "SELECT id, transport_company_id, make, model, year, license_plate, created_at, updated_at \
         FROM vehicles WHERE transport_company_id = $1",
     )
     .bind(transport_company_id)
     .fetch_all(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(rows.into_iter().map(row_to_vehicle_response).collect())
 }
 
-pub async fn get_vehicle_service(pool: &PgPool, id: String) -> Result<VehicleResponse, String> {
+pub async fn get_vehicle_service(pool: &PgPool, id: String) -> Result<VehicleResponse, AppError> {
     let row = sqlx::query(
         "SELECT id, transport_company_id, make, model, year, license_plate, created_at, updated_at \
         FROM vehicles WHERE id = $1",
     )
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_vehicle_response(row))
 }
@@ -224,7 +229,7 @@ pub async fn update_vehicle_service(
     pool: &PgPool,
     id: String,
     payload: UpdateVehicleRequest,
-) -> Result<VehicleResponse, String> {
+) -> Result<VehicleResponse, AppError> {
     let row = sqlx::query(
         "UPDATE vehicles SET \
         make = COALESCE($1, make), \
@@ -242,8 +247,7 @@ pub async fn update_vehicle_service(
     .bind(Utc::now())
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_vehicle_response(row))
 }
@@ -252,17 +256,16 @@ pub async fn patch_vehicle_service(
     pool: &PgPool,
     id: String,
     payload: UpdateVehicleRequest,
-) -> Result<VehicleResponse, String> {
+) -> Result<VehicleResponse, AppError> {
     // For simplicity, this is the same as update - in a real implementation, you might want to handle this differently
     update_vehicle_service(pool, id, payload).await
 }
 
-pub async fn delete_vehicle_service(pool: &PgPool, id: String) -> Result<(), String> {
+pub async fn delete_vehicle_service(pool: &PgPool, id: String) -> Result<(), AppError> {
     sqlx::query("DELETE FROM vehicles WHERE id = $1")
         .bind(id)
         .execute(pool)
-        .await
-        .map_err(map_db_err)?;
+        .await?;
 
     Ok(())
 }
@@ -275,19 +278,24 @@ pub async fn create_driver_service(
     pool: &PgPool,
     transport_company_id: String,
     payload: CreateDriverRequest,
-) -> Result<DriverResponse, String> {
+) -> Result<DriverResponse, AppError> {
     debug!(email = %payload.email, "Starting driver creation");
 
     // Validate input
     if payload.email.is_empty() || payload.username.is_empty() || payload.password.is_empty() {
         error!("Email, username, and password are required");
-        return Err("Email, username, and password are required".to_string());
+        return Err(AppError::Validation(
+            "Email, username, and password are required".to_string(),
+        ));
     }
 
+    let phone = normalize_optional_phone(Some(payload.phone))?
+        .ok_or_else(|| AppError::Validation("Phone number is required".to_string()))?;
+
     // Hash password
     let password_hash = hash_password(&payload.password).map_err(|e| {
         error!(error = %e, "Password hashing failed");
-        format!("Password hashing failed: {}", e)
+        AppError::Internal(format!("Password hashing failed: {}", e))
     })?;
 
     // Create driver object
@@ -299,12 +307,13 @@ pub async fn create_driver_service(
         username: payload.username,
         email: payload.email,
         password_hash,
-        role: crate::models::transport_company_model::Role::TCDriver,
-        phone: payload.phone,
+        role: crate::models::transport_company_model::Role::Driver,
+        phone,
         license_number: payload.license_number.unwrap_or_default(),
         vehicle_type: payload.vehicle_type.unwrap_or_default(),
-        rating: payload.rating,
-        is_available: true,
+        rating: None,
+        // Drivers go online once their email and phone are verified
+        is_available: false,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -313,7 +322,7 @@ pub async fn create_driver_service(
         "INSERT INTO transport_company_drivers \
         (id, transport_company_id, first_name, last_name, username, email, password_hash, role, phone, license_number, vehicle_type, rating, is_available, created_at, updated_at) \
         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15) \
-        RETURNING id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, is_available, created_at, updated_at",
+        RETURNING id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, rating_count, is_available, cancellation_count, created_at, updated_at",
     )
     .bind(&driver.id)
     .bind(&driver.transport_company_id)
@@ -322,7 +331,7 @@ pub async fn create_driver_service(
     .bind(&driver.username)
     .bind(&driver.email)
     .bind(&driver.password_hash)
-    .bind(format!("{:?}", driver.role))
+    .bind(driver.role.as_str())
     .bind(&driver.phone)
     .bind(&driver.license_number)
     .bind(&driver.vehicle_type)
@@ -331,8 +340,7 @@ pub async fn create_driver_service(
     .bind(driver.created_at)
     .bind(driver.updated_at)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_driver_response(row))
 }
@@ -340,15 +348,14 @@ pub async fn create_driver_service(
 pub async fn list_drivers_service(
     pool: &PgPool,
     transport_company_id: String,
-) -> Result<Vec<DriverResponse>, String> {
+) -> Result<Vec<DriverResponse>, AppError> {
     let rows = sqlx::query(
-        "SELECT id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, is_available, created_at, updated_at \
+        "SELECT id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, rating_count, is_available, cancellation_count, created_at, updated_at \
         FROM transport_company_drivers WHERE transport_company_id = $1",
     )
     .bind(transport_company_id)
     .fetch_all(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
     let mut drivers = Vec::new();
     for row in rows {
         drivers.push(row_to_driver_response(row));
@@ -356,15 +363,14 @@ pub async fn list_drivers_service(
     Ok(drivers)
 }
 
-pub async fn get_driver_service(pool: &PgPool, id: String) -> Result<DriverResponse, String> {
+pub async fn get_driver_service(pool: &PgPool, id: String) -> Result<DriverResponse, AppError> {
     let row = sqlx::query(
-        "SELECT id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, is_available, created_at, updated_at \
+        "SELECT id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, rating_count, is_available, cancellation_count, created_at, updated_at \
         FROM transport_company_drivers WHERE id = $1",
     )
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
 
     Ok(row_to_driver_response(row))
 }
@@ -373,38 +379,70 @@ pub async fn update_driver_service(
     pool: &PgPool,
     id: String,
     payload: UpdateDriverRequest,
-) -> Result<DriverResponse, String> {
+) -> Result<DriverResponse, AppError> {
+    // Going online is the driver's own call, made through
+    // `update_driver_availability_service` where verification is checked.
+    // The fleet can only take a driver offline.
+    if payload.is_available == Some(true) {
+        error!(driver_id = %id, "Fleet update tried to put a driver online");
+        return Err(AppError::Validation(
+            "Drivers go online themselves; is_available can only be set to false here".to_string(),
+        ));
+    }
+
     let row = sqlx::query(
         "UPDATE transport_company_drivers SET \
         first_name = COALESCE($1, first_name), \
         last_name = COALESCE($2, last_name), \
         username = COALESCE($3, username), \
+        email_verified_at = CASE WHEN $4::TEXT IS NOT NULL AND $4 IS DISTINCT FROM email THEN NULL ELSE email_verified_at END, \
         email = COALESCE($4, email), \
-        password_hash = COALESCE($5, password_hash), \
-        phone = COALESCE($6, phone), \
-        license_number = COALESCE($7, license_number), \
-        vehicle_type = COALESCE($8, vehicle_type), \
-        rating = COALESCE($9, rating), \
-        is_available = COALESCE($10, is_available), \
-        updated_at = $11 \
-        WHERE id = $12 \
-        RETURNING id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, is_available, created_at, updated_at",
+        phone_verified_at = CASE WHEN $5::TEXT IS NOT NULL AND $5 IS DISTINCT FROM phone THEN NULL ELSE phone_verified_at END, \
+        phone = COALESCE($5, phone), \
+        license_number = COALESCE($6, license_number), \
+        vehicle_type = COALESCE($7, vehicle_type), \
+        is_available = COALESCE($8, is_available), \
+        updated_at = $9 \
+        WHERE id = $10 \
+        RETURNING id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, rating_count, is_available, cancellation_count, created_at, updated_at",
     )
     .bind(payload.first_name)
     .bind(payload.last_name)
     .bind(payload.username)
     .bind(payload.email)
-    .bind(payload.password_hash)
-    .bind(payload.phone)
+    .bind(normalize_optional_phone(payload.phone)?)
     .bind(payload.license_number)
     .bind(payload.vehicle_type)
-    .bind(payload.rating)
     .bind(payload.is_available)
     .bind(Utc::now())
     .bind(id)
     .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+    .await?;
+
+    Ok(row_to_driver_response(row))
+}
+
+/// A driver going online or offline. Going online needs a verified email
+/// and phone.
+pub async fn update_driver_availability_service(
+    pool: &PgPool,
+    id: String,
+    is_available: bool,
+) -> Result<DriverResponse, AppError> {
+    if is_available {
+        require_verified(pool, &id, &AccountType::Driver).await?;
+    }
+
+    let row = sqlx::query(
+        "UPDATE transport_company_drivers SET is_available = $1, updated_at = $2 \
+        WHERE id = $3 \
+        RETURNING id, first_name, last_name, username, email, phone, license_number, vehicle_type, rating, rating_count, is_available, cancellation_count, created_at, updated_at",
+    )
+    .bind(is_available)
+    .bind(Utc::now())
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
 
     Ok(row_to_driver_response(row))
 }
@@ -413,26 +451,105 @@ pub async fn patch_driver_service(
     pool: &PgPool,
     id: String,
     payload: UpdateDriverRequest,
-) -> Result<DriverResponse, String> {
+) -> Result<DriverResponse, AppError> {
     // For simplicity, this is the same as update - in a real implementation, you might want to handle this differently
     update_driver_service(pool, id, payload).await
 }
 
-pub async fn delete_driver_service(pool: &PgPool, id: String) -> Result<(), String> {
+pub async fn delete_driver_service(pool: &PgPool, id: String) -> Result<(), AppError> {
     sqlx::query("DELETE FROM transport_company_drivers WHERE id = $1")
-        .bind(id)
+        .bind(&id)
         .execute(pool)
-        .await
-        .map_err(map_db_err)?;
+        .await?;
+    revoke_account_sessions(pool, &id, "account_deleted").await?;
 
     Ok(())
 }
 
+// =====================================================================================
+// Company Admin Services
+// =====================================================================================
+
+pub async fn create_company_admin_service(
+    pool: &PgPool,
+    transport_company_id: String,
+    payload: CreateCompanyAdminRequest,
+) -> Result<CompanyAdminResponse, AppError> {
+    debug!(email = %payload.email, company_id = %transport_company_id, "Starting company admin creation");
+
+    if payload.email.is_empty() || payload.username.is_empty() || payload.password.is_empty() {
+        error!("Email, username, and password are required");
+        return Err(AppError::Validation(
+            "Email, username, and password are required".to_string(),
+        ));
+    }
+
+    let password_hash = hash_password(&payload.password).map_err(|e| {
+        error!(error = %e, "Password hashing failed");
+        AppError::Internal(format!("Password hashing failed: {}", e))
+    })?;
+
+    let admin = CompanyAdmin {
+        id: Uuid::new_v4().to_string(),
+        transport_company_id,
+        first_name: payload.first_name,
+        last_name: payload.last_name,
+        username: payload.username,
+        email: payload.email,
+        password_hash,
+        role: crate::models::transport_company_model::Role::Admin,
+        phone: payload.phone,
+        is_active: true,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    let row = sqlx::query(
+        "INSERT INTO transport_company_admins \
+        (id, transport_company_id, first_name, last_name, username, email, password_hash, role, phone, is_active, created_at, updated_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12) \
+        RETURNING id, transport_company_id, first_name, last_name, username, email, phone, is_active, created_at, updated_at",
+    )
+    .bind(&admin.id)
+    .bind(&admin.transport_company_id)
+    .bind(&admin.first_name)
+    .bind(&admin.last_name)
+    .bind(&admin.username)
+    .bind(&admin.email)
+    .bind(&admin.password_hash)
+    .bind(admin.role.as_str())
+    .bind(&admin.phone)
+    .bind(admin.is_active)
+    .bind(admin.created_at)
+    .bind(admin.updated_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row_to_company_admin_response(row))
+}
+
+pub async fn list_company_admins_service(
+    pool: &PgPool,
+    transport_company_id: String,
+) -> Result<Vec<CompanyAdminResponse>, AppError> {
+    let rows = sqlx::query(
+        "SELECT id, transport_company_id, first_name, last_name, username, email, phone, is_active, created_at, updated_at \
+        FROM transport_company_admins WHERE transport_company_id = $1 ORDER BY created_at",
+    )
+    .bind(transport_company_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(row_to_company_admin_response)
+        .collect())
+}
+
 //=====================================================================================
 // Helper Functions
 //=====================================================================================
 
-
 fn row_to_transport_company_response(row: sqlx::postgres::PgRow) -> TransportCompanyResponse {
     TransportCompanyResponse {
         id: row.get("id"),
@@ -441,6 +558,7 @@ fn row_to_transport_company_response(row: sqlx::postgres::PgRow) -> TransportCom
         phone: row.get("phone"),
         address: row.get("address"),
         is_active: row.get("is_active"),
+        payment_gateway: row.get("payment_gateway"),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
         vehicles: None,
@@ -473,12 +591,25 @@ fn row_to_driver_response(row: sqlx::postgres::PgRow) -> DriverResponse {
         license_number: row.get("license_number"),
         vehicle_type: row.get("vehicle_type"),
         rating: row.get("rating"),
+        rating_count: row.get("rating_count"),
         is_available: row.get("is_available"),
+        cancellation_count: row.get("cancellation_count"),
         created_at: row.get("created_at"),
         updated_at: row.get("updated_at"),
     }
 }
 
-fn map_db_err(err: sqlx::Error) -> String {
-    err.to_string()
+fn row_to_company_admin_response(row: sqlx::postgres::PgRow) -> CompanyAdminResponse {
+    CompanyAdminResponse {
+        id: row.get("id"),
+        transport_company_id: row.get("transport_company_id"),
+        first_name: row.get("first_name"),
+        last_name: row.get("last_name"),
+        username: row.get("username"),
+        email: row.get("email"),
+        phone: row.get("phone"),
+        is_active: row.get("is_active"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
 }
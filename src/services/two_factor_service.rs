@@ -0,0 +1,463 @@
+use crate::auth::password_utils::Claims;
+use crate::auth::permissions::Role;
+use crate::auth::totp::{TOTP_STEP_SECONDS, generate_secret, provisioning_uri, verify_code};
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
+use crate::models::two_factor_model::{
+    RecoveryCodes, TwoFactorChallenge, TwoFactorEnrollment, TwoFactorStatus,
+};
+use crate::services::auth_service::{SessionAccount, generate_token, hash_token};
+use crate::services::lockout_service::{
+    LoginAttemptKey, LoginFailure, check_login_allowed, record_login_failure,
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct TwoFactorConfig {
+    /// Name shown in authenticator apps.
+    pub issuer: String,
+    /// Token roles (e.g. `PlatformAdmin`, `TCAdmin`) whose protected routes
+    /// are refused until the session passed a second factor.
+    pub required_roles: Vec<String>,
+    pub challenge_ttl_seconds: i64,
+    pub challenge_max_attempts: i32,
+    pub recovery_code_count: usize,
+}
+
+impl TwoFactorConfig {
+    pub fn from_env() -> Self {
+        TwoFactorConfig {
+            issuer: env_or("TOTP_ISSUER", "RideNow".to_string()),
+            required_roles: std::env::var("TWO_FACTOR_REQUIRED_ROLES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|role| role.trim().to_string())
+                .filter(|role| !role.is_empty())
+                .collect(),
+            challenge_ttl_seconds: env_or("LOGIN_CHALLENGE_TTL_SECONDS", 300),
+            challenge_max_attempts: env_or("LOGIN_CHALLENGE_MAX_ATTEMPTS", 5),
+            recovery_code_count: env_or("TWO_FACTOR_RECOVERY_CODES", 10),
+        }
+    }
+
+    pub fn is_required_for(&self, role: &str) -> bool {
+        self.required_roles.iter().any(|required| required == role)
+    }
+}
+
+/// An account that passed the password step and the second factor.
+pub struct VerifiedLogin {
+    pub account: SessionAccount,
+    pub username: String,
+    pub email: String,
+}
+
+// ============================================================================
+// Enrollment Services
+// ============================================================================
+
+pub async fn two_factor_status_service(
+    pool: &PgPool,
+    claims: &Claims,
+) -> Result<TwoFactorStatus, AppError> {
+    let row = sqlx::query(
+        "SELECT c.enabled_at, \
+        (SELECT COUNT(*) FROM two_factor_recovery_codes r WHERE r.account_id = c.account_id AND r.used_at IS NULL) AS remaining \
+        FROM two_factor_credentials c WHERE c.account_id = $1",
+    )
+    .bind(&claims.sub)
+    .fetch_optional(pool)
+    .await?;
+
+    let (enabled, remaining) = match row {
+        Some(row) => (
+            row.get::<Option<DateTime<Utc>>, _>("enabled_at").is_some(),
+            row.get::<i64, _>("remaining"),
+        ),
+        None => (false, 0),
+    };
+
+    Ok(TwoFactorStatus {
+        enabled,
+        required: TwoFactorConfig::from_env().is_required_for(&claims.role),
+        recovery_codes_remaining: remaining,
+    })
+}
+
+/// Creates a new secret for the caller. It only takes effect once a code
+/// from it is confirmed with [`activate_two_factor_service`].
+pub async fn enroll_two_factor_service(
+    pool: &PgPool,
+    claims: &Claims,
+) -> Result<TwoFactorEnrollment, AppError> {
+    let account_type = two_factor_account_type(claims)?;
+    let config = TwoFactorConfig::from_env();
+    let secret = generate_secret();
+
+    let table = match account_type {
+        AccountType::CompanyAdmin => "transport_company_admins",
+        _ => "users",
+    };
+    let query = format!("SELECT username FROM {} WHERE id = $1", table);
+    let username: String = sqlx::query(&query)
+        .bind(&claims.sub)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Account not found".to_string()))?
+        .get("username");
+
+    let result = sqlx::query(
+        "INSERT INTO two_factor_credentials (account_id, account_type, secret, created_at) \
+        VALUES ($1,$2,$3,$4) \
+        ON CONFLICT (account_id) DO UPDATE SET secret = EXCLUDED.secret, created_at = EXCLUDED.created_at \
+        WHERE two_factor_credentials.enabled_at IS NULL",
+    )
+    .bind(&claims.sub)
+    .bind(account_type.as_str())
+    .bind(&secret)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::conflict(
+            "Two-factor authentication is already enabled",
+        ));
+    }
+
+    info!(account_id = %claims.sub, "Two-factor enrollment started");
+
+    Ok(TwoFactorEnrollment {
+        otpauth_uri: provisioning_uri(&secret, &config.issuer, &username),
+        secret,
+    })
+}
+
+/// Confirms enrollment with a first code and hands out recovery codes.
+pub async fn activate_two_factor_service(
+    pool: &PgPool,
+    claims: &Claims,
+    code: &str,
+) -> Result<RecoveryCodes, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        "SELECT secret FROM two_factor_credentials \
+        WHERE account_id = $1 AND enabled_at IS NULL FOR UPDATE",
+    )
+    .bind(&claims.sub)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Validation("Start enrollment first".to_string()))?;
+
+    let now = Utc::now();
+    let step = verify_code(row.get("secret"), code, now.timestamp())
+        .ok_or_else(|| AppError::Validation("Invalid code".to_string()))?;
+
+    sqlx::query(
+        "UPDATE two_factor_credentials SET enabled_at = $2, last_used_step = $3 WHERE account_id = $1",
+    )
+    .bind(&claims.sub)
+    .bind(now)
+    .bind(step)
+    .execute(&mut *tx)
+    .await?;
+
+    let codes = replace_recovery_codes(&mut tx, &claims.sub).await?;
+
+    tx.commit().await?;
+
+    info!(account_id = %claims.sub, "Two-factor authentication enabled");
+
+    Ok(codes)
+}
+
+pub async fn disable_two_factor_service(
+    pool: &PgPool,
+    claims: &Claims,
+    code: &str,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    verify_second_factor(&mut tx, &claims.sub, code).await?;
+
+    sqlx::query("DELETE FROM two_factor_credentials WHERE account_id = $1")
+        .bind(&claims.sub)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    warn!(account_id = %claims.sub, "Two-factor authentication disabled");
+
+    Ok(())
+}
+
+/// Replaces every recovery code of the caller.
+pub async fn regenerate_recovery_codes_service(
+    pool: &PgPool,
+    claims: &Claims,
+    code: &str,
+) -> Result<RecoveryCodes, AppError> {
+    let mut tx = pool.begin().await?;
+
+    verify_second_factor(&mut tx, &claims.sub, code).await?;
+    let codes = replace_recovery_codes(&mut tx, &claims.sub).await?;
+
+    tx.commit().await?;
+
+    info!(account_id = %claims.sub, "Recovery codes regenerated");
+
+    Ok(codes)
+}
+
+// ============================================================================
+// Login Challenge Services
+// ============================================================================
+
+pub async fn is_two_factor_enabled(pool: &PgPool, account_id: &str) -> Result<bool, AppError> {
+    let row = sqlx::query(
+        "SELECT 1 FROM two_factor_credentials WHERE account_id = $1 AND enabled_at IS NOT NULL",
+    )
+    .bind(account_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+/// Parks a password-verified login until the second factor is given.
+pub async fn create_login_challenge(
+    pool: &PgPool,
+    account: &SessionAccount,
+    username: &str,
+    email: &str,
+) -> Result<TwoFactorChallenge, AppError> {
+    let config = TwoFactorConfig::from_env();
+    let token = generate_token();
+    let now = Utc::now();
+    let expires_at = now + Duration::seconds(config.challenge_ttl_seconds);
+
+    sqlx::query(
+        "INSERT INTO login_challenges \
+        (id, token_hash, account_id, account_type, role, transport_company_id, username, email, created_at, expires_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(hash_token(&token))
+    .bind(&account.account_id)
+    .bind(account.account_type.as_str())
+    .bind(&account.role)
+    .bind(&account.transport_company_id)
+    .bind(username)
+    .bind(email)
+    .bind(now)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(TwoFactorChallenge {
+        two_factor_required: true,
+        challenge_token: token,
+        expires_at,
+    })
+}
+
+/// Checks the second factor for a login challenge. Each challenge allows a
+/// limited number of wrong codes, and wrong codes count towards the
+/// account's login lockout.
+pub async fn complete_login_challenge(
+    pool: &PgPool,
+    challenge_token: &str,
+    code: &str,
+    ip_address: Option<&str>,
+) -> Result<VerifiedLogin, AppError> {
+    let config = TwoFactorConfig::from_env();
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    let challenge = sqlx::query(
+        "SELECT id, account_id, account_type, role, transport_company_id, username, email, attempts \
+        FROM login_challenges \
+        WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > $2 FOR UPDATE",
+    )
+    .bind(hash_token(challenge_token))
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Login has expired, sign in again".to_string()))?;
+
+    let challenge_id: String = challenge.get("id");
+    let account_id: String = challenge.get("account_id");
+    let account_type = AccountType::parse(challenge.get("account_type"))?;
+    let username: String = challenge.get("username");
+    if challenge.get::<i32, _>("attempts") >= config.challenge_max_attempts {
+        return Err(AppError::Unauthorized(
+            "Too many wrong codes, sign in again".to_string(),
+        ));
+    }
+
+    let attempt = LoginAttemptKey {
+        account_type,
+        username: &username,
+        ip_address,
+    };
+    check_login_allowed(pool, &attempt).await?;
+
+    if let Err(e) = verify_second_factor(&mut tx, &account_id, code).await {
+        drop(tx);
+        sqlx::query("UPDATE login_challenges SET attempts = attempts + 1 WHERE id = $1")
+            .bind(&challenge_id)
+            .execute(pool)
+            .await?;
+        record_login_failure(
+            pool,
+            &attempt,
+            Some(&account_id),
+            LoginFailure::BadSecondFactor,
+        )
+        .await?;
+        warn!(account_id = %account_id, "Wrong second factor at login");
+        return Err(match e {
+            AppError::Validation(message) => AppError::Unauthorized(message),
+            other => other,
+        });
+    }
+
+    sqlx::query("UPDATE login_challenges SET consumed_at = $2 WHERE id = $1")
+        .bind(&challenge_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(VerifiedLogin {
+        account: SessionAccount {
+            account_id,
+            account_type,
+            role: challenge.get("role"),
+            transport_company_id: challenge.get("transport_company_id"),
+            two_factor: true,
+        },
+        username,
+        email: challenge.get("email"),
+    })
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Accepts a current TOTP code (each time step only once) or an unused
+/// recovery code, which is then spent.
+async fn verify_second_factor(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: &str,
+    code: &str,
+) -> Result<(), AppError> {
+    let credential = sqlx::query(
+        "SELECT secret, last_used_step FROM two_factor_credentials \
+        WHERE account_id = $1 AND enabled_at IS NOT NULL FOR UPDATE",
+    )
+    .bind(account_id)
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| AppError::Validation("Two-factor authentication is not enabled".to_string()))?;
+
+    let now = Utc::now().timestamp();
+    if let Some(step) = verify_code(credential.get("secret"), code, now) {
+        let last_used_step: Option<i64> = credential.get("last_used_step");
+        if last_used_step.is_some_and(|last| step <= last) {
+            return Err(AppError::Validation(format!(
+                "Code was already used, wait up to {} seconds for the next one",
+                TOTP_STEP_SECONDS
+            )));
+        }
+        sqlx::query("UPDATE two_factor_credentials SET last_used_step = $2 WHERE account_id = $1")
+            .bind(account_id)
+            .bind(step)
+            .execute(&mut **tx)
+            .await?;
+        return Ok(());
+    }
+
+    let spent = sqlx::query(
+        "UPDATE two_factor_recovery_codes SET used_at = $3 \
+        WHERE account_id = $1 AND code_hash = $2 AND used_at IS NULL RETURNING id",
+    )
+    .bind(account_id)
+    .bind(hash_token(&normalize_recovery_code(code)))
+    .bind(Utc::now())
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match spent {
+        Some(_) => {
+            warn!(account_id = %account_id, "Recovery code used");
+            Ok(())
+        }
+        None => Err(AppError::Validation("Invalid code".to_string())),
+    }
+}
+
+async fn replace_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: &str,
+) -> Result<RecoveryCodes, AppError> {
+    let config = TwoFactorConfig::from_env();
+    let now = Utc::now();
+
+    sqlx::query("DELETE FROM two_factor_recovery_codes WHERE account_id = $1")
+        .bind(account_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let mut recovery_codes = Vec::with_capacity(config.recovery_code_count);
+    for _ in 0..config.recovery_code_count {
+        let mut bytes = [0u8; 5];
+        OsRng.fill_bytes(&mut bytes);
+        let raw = hex::encode(bytes);
+        let code = format!("{}-{}", &raw[..5], &raw[5..]);
+
+        sqlx::query(
+            "INSERT INTO two_factor_recovery_codes (id, account_id, code_hash, created_at) \
+            VALUES ($1,$2,$3,$4)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(account_id)
+        .bind(hash_token(&normalize_recovery_code(&code)))
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        recovery_codes.push(code);
+    }
+
+    Ok(RecoveryCodes { recovery_codes })
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Only platform admins and company admins can enroll.
+fn two_factor_account_type(claims: &Claims) -> Result<AccountType, AppError> {
+    match Role::from_claim(&claims.role) {
+        Some(role @ (Role::PlatformAdmin | Role::CompanyAdmin)) => Ok(role.account_type()),
+        _ => {
+            error!(user_id = %claims.sub, role = %claims.role, "Two-factor enrollment refused");
+            Err(AppError::Forbidden(
+                "Two-factor authentication is available to admin accounts".to_string(),
+            ))
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::AppError;
 use crate::models::user_model::{CreateUserRequest, UpdateUserRequest, User, UserResponse};
 use chrono::Utc;
 use sqlx::{PgPool, Row};
@@ -5,23 +6,31 @@ use tracing::{debug, error};
 use uuid::Uuid;
 
 use crate::auth::password_utils::hash_password;
+use crate::services::auth_service::revoke_account_sessions;
+use crate::services::verification_service::normalize_optional_phone;
+
+const USER_COLUMNS: &str = "id, email, username, first_name, last_name, phone, profile_picture, is_active, rating, rating_count, created_at";
 
 pub async fn create_user_service(
     pool: &PgPool,
     payload: CreateUserRequest,
-) -> Result<UserResponse, String> {
+) -> Result<UserResponse, AppError> {
     debug!(email = %payload.email, "Starting user creation");
 
     // Validate input
     if payload.email.is_empty() || payload.username.is_empty() || payload.password.is_empty() {
         error!("Email, username, and password are required");
-        return Err("Email, username, and password are required".to_string());
+        return Err(AppError::Validation(
+            "Email, username, and password are required".to_string(),
+        ));
     }
 
+    let phone = normalize_optional_phone(payload.phone)?;
+
     // Hash password (you'll need to implement or use a crate like `bcrypt`)
     let password_hash = hash_password(&payload.password).map_err(|e| {
         error!(error = %e, "Password hashing failed");
-        format!("Password hashing failed: {}", e)
+        AppError::Internal(format!("Password hashing failed: {}", e))
     })?;
 
     // Create user object
@@ -33,78 +42,75 @@ pub async fn create_user_service(
         role: crate::models::user_model::Role::User,
         first_name: payload.first_name,
         last_name: payload.last_name,
-        phone: payload.phone,
+        phone,
         profile_picture: None,
         is_active: true,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
-    let row = sqlx::query(
+    let query = format!(
         "INSERT INTO users \
         (id, email, username, password_hash, role, first_name, last_name, phone, profile_picture, is_active, created_at, updated_at) \
         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12) \
-        RETURNING id, email, username, first_name, last_name, phone, profile_picture, is_active, created_at",
-    )
-    .bind(&user.id)
-    .bind(&user.email)
-    .bind(&user.username)
-    .bind(&user.password_hash)
-    .bind(format!("{:?}", user.role))
-    .bind(&user.first_name)
-    .bind(&user.last_name)
-    .bind(&user.phone)
-    .bind(&user.profile_picture)
-    .bind(user.is_active)
-    .bind(user.created_at)
-    .bind(user.updated_at)
-    .fetch_one(pool)
-    .await
-    .map_err(map_db_err)?;
+        RETURNING {}",
+        USER_COLUMNS
+    );
+    let row = sqlx::query(&query)
+        .bind(&user.id)
+        .bind(&user.email)
+        .bind(&user.username)
+        .bind(&user.password_hash)
+        .bind(format!("{:?}", user.role))
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.phone)
+        .bind(&user.profile_picture)
+        .bind(user.is_active)
+        .bind(user.created_at)
+        .bind(user.updated_at)
+        .fetch_one(pool)
+        .await?;
 
     Ok(row_to_user_response(row))
 }
 
-pub async fn list_users_service(pool: &PgPool) -> Result<Vec<UserResponse>, String> {
+pub async fn list_users_service(pool: &PgPool) -> Result<Vec<UserResponse>, AppError> {
     debug!("Fetching all users from database");
-    let rows = sqlx::query(
-        "SELECT * \
-        FROM users ORDER BY created_at DESC",
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| {
+    let query = format!(
+        "SELECT {} FROM users ORDER BY created_at DESC",
+        USER_COLUMNS
+    );
+    let rows = sqlx::query(&query).fetch_all(pool).await.map_err(|e| {
         error!(error = %e, "Database fetch failed");
-        map_db_err(e)
+        AppError::from(e)
     })?;
 
     Ok(rows.into_iter().map(row_to_user_response).collect())
 }
 
-pub async fn get_user_service(pool: &PgPool, id: String) -> Result<UserResponse, String> {
+pub async fn get_user_service(pool: &PgPool, id: String) -> Result<UserResponse, AppError> {
     debug!(user_id = %id, "Fetching user from database");
 
     // Validate input
     if id.is_empty() {
         error!("User ID is required");
-        return Err("User ID is required".to_string());
+        return Err(AppError::Validation("User ID is required".to_string()));
     }
 
-    let row = sqlx::query(
-        "SELECT id, email, username, first_name, last_name, phone, profile_picture, is_active, created_at \
-        FROM users WHERE id = $1",
-    )
-    .bind(&id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| {
-        error!(error = %e, "Database fetch failed");
-        map_db_err(e)
-    })?
-    .ok_or_else(|| {
-        error!(user_id = %id, "User not found");
-        "User not found".to_string()
-    })?;
+    let query = format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS);
+    let row = sqlx::query(&query)
+        .bind(&id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Database fetch failed");
+            AppError::from(e)
+        })?
+        .ok_or_else(|| {
+            error!(user_id = %id, "User not found");
+            AppError::NotFound("User not found".to_string())
+        })?;
 
     Ok(row_to_user_response(row))
 }
@@ -113,58 +119,65 @@ pub async fn update_user_service(
     pool: &PgPool,
     id: String,
     payload: UpdateUserRequest,
-) -> Result<UserResponse, String> {
+) -> Result<UserResponse, AppError> {
     debug!(user_id = %id, "Starting user update");
 
     // Validate input
     if id.is_empty() {
         error!("User ID is required");
-        return Err("User ID is required".to_string());
+        return Err(AppError::Validation("User ID is required".to_string()));
     }
 
-    let row = sqlx::query(
+    let deactivated = payload.is_active == Some(false);
+    let query = format!(
         "UPDATE users SET \
             first_name = COALESCE($1, first_name), \
             last_name = COALESCE($2, last_name), \
+            phone_verified_at = CASE WHEN $3::TEXT IS NOT NULL AND $3 IS DISTINCT FROM phone THEN NULL ELSE phone_verified_at END, \
             phone = COALESCE($3, phone), \
             profile_picture = COALESCE($4, profile_picture), \
             is_active = COALESCE($5, is_active), \
-            password_hash = COALESCE($6, password_hash), \
-            updated_at = $7 \
-        WHERE id = $8 \
-        RETURNING id, email, username, first_name, last_name, phone, profile_picture, is_active, created_at",
-    )
-    .bind(payload.first_name)
-    .bind(payload.last_name)
-    .bind(payload.phone)
-    .bind(payload.profile_picture)
-    .bind(payload.is_active)
-    .bind(payload.password_hash)
-    .bind(Utc::now())
-    .bind(id)
-    .fetch_optional(pool)
-    .await
-    .map_err(map_db_err)?
-    .ok_or_else(|| "User not found".to_string())?;
+            updated_at = $6 \
+        WHERE id = $7 \
+        RETURNING {}",
+        USER_COLUMNS
+    );
+    let row = sqlx::query(&query)
+        .bind(payload.first_name)
+        .bind(payload.last_name)
+        .bind(normalize_optional_phone(payload.phone)?)
+        .bind(payload.profile_picture)
+        .bind(payload.is_active)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let user = row_to_user_response(row);
+    if deactivated {
+        let revoked = revoke_account_sessions(pool, &user.id, "account_disabled").await?;
+        debug!(user_id = %user.id, revoked, "Sessions of disabled user revoked");
+    }
 
-    Ok(row_to_user_response(row))
+    Ok(user)
 }
 
 pub async fn patch_user_service(
     pool: &PgPool,
     id: String,
     payload: UpdateUserRequest,
-) -> Result<UserResponse, String> {
+) -> Result<UserResponse, AppError> {
     update_user_service(pool, id, payload).await
 }
 
-pub async fn delete_user_service(pool: &PgPool, id: String) -> Result<String, String> {
+pub async fn delete_user_service(pool: &PgPool, id: String) -> Result<String, AppError> {
     debug!(user_id = %id, "Starting user deletion");
 
     // Validate input
     if id.is_empty() {
         error!("User ID is required");
-        return Err("User ID is required".to_string());
+        return Err(AppError::Validation("User ID is required".to_string()));
     }
 
     let result = sqlx::query("DELETE FROM users WHERE id = $1")
@@ -173,15 +186,16 @@ pub async fn delete_user_service(pool: &PgPool, id: String) -> Result<String, St
         .await
         .map_err(|e| {
             error!(error = %e, "Database delete failed");
-            map_db_err(e)
+            AppError::from(e)
         })?;
 
     if result.rows_affected() == 0 {
         error!(user_id = %id, "User not found");
-        return Err("User not found".to_string());
+        return Err(AppError::NotFound("User not found".to_string()));
     }
 
-    debug!(user_id = %id, "User deleted from database");
+    let revoked = revoke_account_sessions(pool, &id, "account_deleted").await?;
+    debug!(user_id = %id, revoked, "User deleted from database");
     Ok(format!("User {} deleted successfully", id))
 }
 
@@ -190,16 +204,13 @@ fn row_to_user_response(row: sqlx::postgres::PgRow) -> UserResponse {
         id: row.get("id"),
         email: row.get("email"),
         username: row.get("username"),
-        password_hash: row.get("password_hash"),
         first_name: row.get("first_name"),
         last_name: row.get("last_name"),
         phone: row.get("phone"),
         profile_picture: row.get("profile_picture"),
         is_active: row.get("is_active"),
+        rating: row.get("rating"),
+        rating_count: row.get("rating_count"),
         created_at: row.get("created_at"),
     }
 }
-
-fn map_db_err(err: sqlx::Error) -> String {
-    err.to_string()
-}
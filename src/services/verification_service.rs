@@ -0,0 +1,489 @@
+use crate::config::env_or;
+use crate::error::AppError;
+use crate::models::login_model::AccountType;
+use crate::models::verification_model::{
+    VerificationChannel, VerificationSent, VerificationStatus,
+};
+use crate::services::auth_service::{generate_token, hash_token};
+use crate::services::notification_service::{Notification, NotificationChannel, Notifier};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct VerificationConfig {
+    pub email_link_ttl_hours: i64,
+    /// Page of the client app that takes the token. Without it the link
+    /// points at `GET /auth/verification/email/confirm` directly.
+    pub email_confirm_url: Option<String>,
+    pub public_base_url: String,
+    pub phone_code_ttl_minutes: i64,
+    /// Wrong guesses allowed per SMS code before it stops working.
+    pub phone_code_max_attempts: i32,
+    pub resend_interval_seconds: i64,
+    pub max_sends_per_hour: i64,
+    /// Country calling code assumed for numbers written in national format.
+    pub default_country_code: String,
+}
+
+impl VerificationConfig {
+    pub fn from_env() -> Self {
+        VerificationConfig {
+            email_link_ttl_hours: env_or("EMAIL_VERIFICATION_TTL_HOURS", 24),
+            email_confirm_url: std::env::var("EMAIL_VERIFICATION_URL").ok(),
+            public_base_url: env_or("PUBLIC_BASE_URL", "http://127.0.0.1:3000".to_string()),
+            phone_code_ttl_minutes: env_or("PHONE_OTP_TTL_MINUTES", 10),
+            phone_code_max_attempts: env_or("PHONE_OTP_MAX_ATTEMPTS", 5),
+            resend_interval_seconds: env_or("VERIFICATION_RESEND_INTERVAL_SECONDS", 60),
+            max_sends_per_hour: env_or("VERIFICATION_MAX_SENDS_PER_HOUR", 5),
+            default_country_code: env_or("DEFAULT_PHONE_COUNTRY_CODE", "234".to_string()),
+        }
+    }
+}
+
+/// Contact details and verification state of an account.
+struct VerificationAccount {
+    email: String,
+    phone: Option<String>,
+    email_verified_at: Option<DateTime<Utc>>,
+    phone_verified_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// Verification Services
+// ============================================================================
+
+pub async fn verification_status_service(
+    pool: &PgPool,
+    account_id: &str,
+    account_type: &AccountType,
+) -> Result<VerificationStatus, AppError> {
+    let account = fetch_account(pool, account_id, account_type).await?;
+    let email_verified = account.email_verified_at.is_some();
+    let phone_verified = account.phone_verified_at.is_some();
+
+    Ok(VerificationStatus {
+        email: account.email,
+        email_verified,
+        phone: account.phone,
+        phone_verified,
+        complete: email_verified && phone_verified,
+    })
+}
+
+/// Sends a confirmation link to the account's email address.
+pub async fn send_email_verification_service(
+    pool: &PgPool,
+    notifier: &Notifier,
+    account_id: &str,
+    account_type: &AccountType,
+) -> Result<VerificationSent, AppError> {
+    let config = VerificationConfig::from_env();
+    let account = fetch_account(pool, account_id, account_type).await?;
+    if account.email_verified_at.is_some() {
+        return Err(AppError::conflict("Email is already verified"));
+    }
+
+    let token = generate_token();
+    let sent = issue_code(
+        pool,
+        &config,
+        account_id,
+        account_type,
+        VerificationChannel::Email,
+        &account.email,
+        &token,
+        Duration::hours(config.email_link_ttl_hours),
+    )
+    .await?;
+
+    let link = match &config.email_confirm_url {
+        Some(url) => format!("{}?token={}", url, token),
+        None => format!(
+            "{}/auth/verification/email/confirm?token={}",
+            config.public_base_url.trim_end_matches('/'),
+            token
+        ),
+    };
+    notifier
+        .send(Notification {
+            channel: NotificationChannel::Email,
+            recipient: account.email,
+            subject: "Confirm your RideNow email".to_string(),
+            body: format!(
+                "Confirm your email address by opening this link:\n\n{}\n\nThe link expires in {} hours.",
+                link, config.email_link_ttl_hours
+            ),
+        })
+        .await
+        .map_err(AppError::Internal)?;
+
+    info!(account_id = %account_id, "Email verification sent");
+
+    Ok(sent)
+}
+
+/// Confirms an email address from the token in a verification link.
+pub async fn confirm_email_service(pool: &PgPool, token: &str) -> Result<(), AppError> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    let code = sqlx::query(
+        "UPDATE verification_codes SET consumed_at = $2 \
+        WHERE code_hash = $1 AND channel = 'email' AND consumed_at IS NULL AND expires_at > $2 \
+        RETURNING account_id, account_type, destination",
+    )
+    .bind(hash_token(token))
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Validation("Invalid or expired verification link".to_string()))?;
+
+    let account_id: String = code.get("account_id");
+    let account_type = AccountType::parse(code.get("account_type"))?;
+    let destination: String = code.get("destination");
+
+    // The link only confirms the address it was sent to
+    let query = format!(
+        "UPDATE {} SET email_verified_at = $1, updated_at = $1 WHERE id = $2 AND email = $3",
+        account_table(&account_type)?
+    );
+    let updated = sqlx::query(&query)
+        .bind(now)
+        .bind(&account_id)
+        .bind(&destination)
+        .execute(&mut *tx)
+        .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Validation(
+            "Email address has changed since the link was sent".to_string(),
+        ));
+    }
+
+    tx.commit().await?;
+
+    info!(account_id = %account_id, "Email verified");
+
+    Ok(())
+}
+
+/// Texts a one-time code to the account's phone number.
+pub async fn send_phone_verification_service(
+    pool: &PgPool,
+    notifier: &Notifier,
+    account_id: &str,
+    account_type: &AccountType,
+) -> Result<VerificationSent, AppError> {
+    let config = VerificationConfig::from_env();
+    let account = fetch_account(pool, account_id, account_type).await?;
+    if account.phone_verified_at.is_some() {
+        return Err(AppError::conflict("Phone number is already verified"));
+    }
+    let phone = account.phone.ok_or_else(|| {
+        AppError::Validation("Add a phone number before verifying it".to_string())
+    })?;
+    let phone = normalize_phone(&phone, &config.default_country_code)?;
+
+    let code = format!("{:06}", OsRng.next_u32() % 1_000_000);
+    let sent = issue_code(
+        pool,
+        &config,
+        account_id,
+        account_type,
+        VerificationChannel::Phone,
+        &phone,
+        &code,
+        Duration::minutes(config.phone_code_ttl_minutes),
+    )
+    .await?;
+
+    notifier
+        .send(Notification {
+            channel: NotificationChannel::Sms,
+            recipient: phone,
+            subject: "RideNow verification code".to_string(),
+            body: format!(
+                "Your RideNow code is {}. It expires in {} minutes.",
+                code, config.phone_code_ttl_minutes
+            ),
+        })
+        .await
+        .map_err(AppError::Internal)?;
+
+    info!(account_id = %account_id, "Phone verification code sent");
+
+    Ok(sent)
+}
+
+/// Checks an SMS code against the latest one sent to the account. Each code
+/// allows a limited number of wrong guesses.
+pub async fn confirm_phone_service(
+    pool: &PgPool,
+    account_id: &str,
+    account_type: &AccountType,
+    code: &str,
+) -> Result<(), AppError> {
+    let config = VerificationConfig::from_env();
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query(
+        "SELECT id, destination, code_hash, attempts, expires_at FROM verification_codes \
+        WHERE account_id = $1 AND channel = 'phone' AND consumed_at IS NULL \
+        ORDER BY created_at DESC LIMIT 1 FOR UPDATE",
+    )
+    .bind(account_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Validation("No verification code has been sent".to_string()))?;
+
+    let code_id: String = row.get("id");
+    let attempts: i32 = row.get("attempts");
+    if row.get::<DateTime<Utc>, _>("expires_at") <= now {
+        return Err(AppError::Validation(
+            "Verification code has expired, request a new one".to_string(),
+        ));
+    }
+    if attempts >= config.phone_code_max_attempts {
+        return Err(AppError::Validation(
+            "Too many wrong attempts, request a new code".to_string(),
+        ));
+    }
+
+    if hash_token(&format!("{}:{}", code_id, code.trim())) != row.get::<String, _>("code_hash") {
+        sqlx::query("UPDATE verification_codes SET attempts = attempts + 1 WHERE id = $1")
+            .bind(&code_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let remaining = config.phone_code_max_attempts - attempts - 1;
+        warn!(account_id = %account_id, remaining, "Wrong phone verification code");
+        return Err(AppError::Validation(format!(
+            "Incorrect code, {} attempt(s) left",
+            remaining.max(0)
+        )));
+    }
+
+    sqlx::query("UPDATE verification_codes SET consumed_at = $2 WHERE id = $1")
+        .bind(&code_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+    let destination: String = row.get("destination");
+    let query = format!(
+        "UPDATE {} SET phone = $1, phone_verified_at = $2, updated_at = $2 WHERE id = $3",
+        account_table(account_type)?
+    );
+    sqlx::query(&query)
+        .bind(&destination)
+        .bind(now)
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    info!(account_id = %account_id, "Phone verified");
+
+    Ok(())
+}
+
+/// Errors unless both the email and the phone number of the account are
+/// verified. Gates ride booking and drivers going online.
+pub async fn require_verified(
+    pool: &PgPool,
+    account_id: &str,
+    account_type: &AccountType,
+) -> Result<(), AppError> {
+    let account = fetch_account(pool, account_id, account_type).await?;
+    match (account.email_verified_at, account.phone_verified_at) {
+        (Some(_), Some(_)) => Ok(()),
+        (None, _) => Err(AppError::Forbidden(
+            "Verify your email address first".to_string(),
+        )),
+        (_, None) => Err(AppError::Forbidden(
+            "Verify your phone number first".to_string(),
+        )),
+    }
+}
+
+/// Brings a phone number to E.164 (`+2348012345678`). Numbers in national
+/// format (`08012345678`) get `country_code`.
+pub fn normalize_phone(raw: &str, country_code: &str) -> Result<String, AppError> {
+    let trimmed = raw.trim();
+    let digits: String = trimmed
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.'))
+        .collect();
+
+    let international = if let Some(rest) = digits.strip_prefix('+') {
+        rest.to_string()
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else if let Some(rest) = digits.strip_prefix('0') {
+        format!("{}{}", country_code, rest)
+    } else {
+        digits
+    };
+
+    if !international.chars().all(|c| c.is_ascii_digit())
+        || !(8..=15).contains(&international.len())
+        || international.starts_with('0')
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid phone number: {}",
+            trimmed
+        )));
+    }
+
+    Ok(format!("+{}", international))
+}
+
+/// Validates and normalizes an optional phone number from a request.
+pub fn normalize_optional_phone(phone: Option<String>) -> Result<Option<String>, AppError> {
+    let country_code = VerificationConfig::from_env().default_country_code;
+    phone
+        .filter(|phone| !phone.trim().is_empty())
+        .map(|phone| normalize_phone(&phone, &country_code))
+        .transpose()
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Records a new code after applying resend throttling. Earlier codes of the
+/// same channel stop working.
+#[allow(clippy::too_many_arguments)]
+async fn issue_code(
+    pool: &PgPool,
+    config: &VerificationConfig,
+    account_id: &str,
+    account_type: &AccountType,
+    channel: VerificationChannel,
+    destination: &str,
+    secret: &str,
+    ttl: Duration,
+) -> Result<VerificationSent, AppError> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    let recent = sqlx::query(
+        "SELECT COUNT(*) AS sent, MIN(created_at) AS first_sent_at, MAX(created_at) AS last_sent_at \
+        FROM verification_codes \
+        WHERE account_id = $1 AND channel = $2 AND created_at > $3",
+    )
+    .bind(account_id)
+    .bind(channel.as_str())
+    .bind(now - Duration::hours(1))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let sent: i64 = recent.get("sent");
+    if sent >= config.max_sends_per_hour {
+        debug!(account_id = %account_id, channel = channel.as_str(), "Hourly verification limit reached");
+        let window_ends = recent
+            .get::<Option<DateTime<Utc>>, _>("first_sent_at")
+            .map(|first_sent_at| first_sent_at + Duration::hours(1))
+            .unwrap_or(now);
+        return Err(AppError::rate_limited(
+            "Too many verification requests, try again later",
+            (window_ends - now).num_seconds() + 1,
+        ));
+    }
+    if let Some(last_sent_at) = recent.get::<Option<DateTime<Utc>>, _>("last_sent_at") {
+        let wait = last_sent_at + Duration::seconds(config.resend_interval_seconds) - now;
+        if wait > Duration::zero() {
+            return Err(AppError::rate_limited(
+                format!(
+                    "Please wait {} seconds before requesting another code",
+                    wait.num_seconds() + 1
+                ),
+                wait.num_seconds() + 1,
+            ));
+        }
+    }
+
+    sqlx::query(
+        "UPDATE verification_codes SET consumed_at = $3 \
+        WHERE account_id = $1 AND channel = $2 AND consumed_at IS NULL",
+    )
+    .bind(account_id)
+    .bind(channel.as_str())
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    // SMS codes are short, so their digest is salted with the row id
+    let id = Uuid::new_v4().to_string();
+    let code_hash = match channel {
+        VerificationChannel::Email => hash_token(secret),
+        VerificationChannel::Phone => hash_token(&format!("{}:{}", id, secret)),
+    };
+    let expires_at = now + ttl;
+
+    sqlx::query(
+        "INSERT INTO verification_codes \
+        (id, account_id, account_type, channel, destination, code_hash, created_at, expires_at) \
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8)",
+    )
+    .bind(&id)
+    .bind(account_id)
+    .bind(account_type.as_str())
+    .bind(channel.as_str())
+    .bind(destination)
+    .bind(code_hash)
+    .bind(now)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(VerificationSent {
+        channel,
+        destination: destination.to_string(),
+        expires_at,
+        resend_after: now + Duration::seconds(config.resend_interval_seconds),
+    })
+}
+
+async fn fetch_account(
+    pool: &PgPool,
+    account_id: &str,
+    account_type: &AccountType,
+) -> Result<VerificationAccount, AppError> {
+    let query = format!(
+        "SELECT email, phone, email_verified_at, phone_verified_at FROM {} WHERE id = $1",
+        account_table(account_type)?
+    );
+    let row = sqlx::query(&query)
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            error!(account_id = %account_id, "Account not found");
+            AppError::NotFound("Account not found".to_string())
+        })?;
+
+    Ok(VerificationAccount {
+        email: row.get("email"),
+        phone: row.get("phone"),
+        email_verified_at: row.get("email_verified_at"),
+        phone_verified_at: row.get("phone_verified_at"),
+    })
+}
+
+fn account_table(account_type: &AccountType) -> Result<&'static str, AppError> {
+    match account_type {
+        AccountType::Rider => Ok("users"),
+        AccountType::Driver => Ok("transport_company_drivers"),
+        AccountType::CompanyAdmin => Err(AppError::Validation(
+            "Verification is only required for riders and drivers".to_string(),
+        )),
+    }
+}
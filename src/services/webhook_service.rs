@@ -0,0 +1,287 @@
+use crate::error::AppError;
+use crate::models::webhook_event_model::{
+    WebhookEventQuery, WebhookEventResponse, WebhookEventStatus,
+};
+use crate::services::payment_gateway::{
+    PaymentGateway, PaymentProvider, WebhookEvent, parse_payload,
+};
+use crate::services::payment_service::process_webhook_event;
+use axum::http::HeaderMap;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use sqlx::{PgPool, Row, types::Json};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+const MAX_EVENT_LIMIT: i64 = 500;
+/// An event still `received` this long after it was claimed was abandoned,
+/// e.g. by a crash while applying it, and a redelivery may claim it again.
+const STALE_CLAIM_SECONDS: i64 = 300;
+
+const EVENT_COLUMNS: &str = "id, gateway, event_id, event, reference, signature_valid, status, \
+    error, attempts, payload, received_at, processed_at";
+
+// ============================================================================
+// Webhook Intake Service
+// ============================================================================
+
+/// Stores a webhook delivery and applies it. Deliveries that fail the
+/// signature check or cannot be read are stored and their error returned.
+/// An event seen before is applied again only if applying it failed or was
+/// abandoned part way, so gateway retries are safe; otherwise its earlier
+/// status is returned.
+pub async fn receive_webhook_service(
+    pool: &PgPool,
+    gateway: &dyn PaymentGateway,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<WebhookEventStatus, AppError> {
+    let payload = parse_payload(body);
+
+    // 1. Authenticate and read the delivery
+    if let Err(e) = gateway.verify_webhook(headers, body) {
+        store_unreadable(pool, gateway.name(), payload.ok(), false, &e).await?;
+        return Err(e);
+    }
+    let payload = match payload {
+        Ok(payload) => payload,
+        Err(e) => {
+            store_unreadable(pool, gateway.name(), None, true, &e).await?;
+            return Err(e);
+        }
+    };
+    let event = match gateway.parse_webhook(payload.clone()) {
+        Ok(event) => event,
+        Err(e) => {
+            store_unreadable(pool, gateway.name(), Some(payload), true, &e).await?;
+            return Err(e);
+        }
+    };
+
+    // 2. Store it, or claim a stored copy whose processing failed or stalled
+    let now = Utc::now();
+    let id = sqlx::query(
+        "INSERT INTO webhook_events \
+        (id, gateway, event_id, event, reference, signature_valid, status, attempts, payload, \
+        claimed_at) \
+        VALUES ($1, $2, $3, $4, $5, TRUE, 'received', 1, $6, $7) \
+        ON CONFLICT (gateway, event_id) WHERE event_id IS NOT NULL DO UPDATE \
+        SET status = 'received', attempts = webhook_events.attempts + 1, error = NULL, \
+            claimed_at = $7 \
+        WHERE webhook_events.status = 'failed' \
+            OR (webhook_events.status = 'received' \
+                AND COALESCE(webhook_events.claimed_at, webhook_events.received_at) < $8) \
+        RETURNING id",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(gateway.name())
+    .bind(&event.event_id)
+    .bind(&event.event)
+    .bind(&event.reference)
+    .bind(Json(&payload))
+    .bind(now)
+    .bind(now - Duration::seconds(STALE_CLAIM_SECONDS))
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.get::<String, _>("id"));
+
+    let Some(id) = id else {
+        let status: String =
+            sqlx::query("SELECT status FROM webhook_events WHERE gateway = $1 AND event_id = $2")
+                .bind(gateway.name())
+                .bind(&event.event_id)
+                .fetch_one(pool)
+                .await?
+                .get("status");
+        info!(
+            gateway = gateway.name(),
+            event_id = %event.event_id,
+            status = %status,
+            "Skipping duplicate webhook delivery"
+        );
+        return WebhookEventStatus::parse(&status);
+    };
+
+    // 3. Apply it
+    apply_stored_event(pool, gateway, &id, event).await
+}
+
+// ============================================================================
+// Webhook Event Admin Services
+// ============================================================================
+
+pub async fn list_webhook_events_service(
+    pool: &PgPool,
+    query: WebhookEventQuery,
+) -> Result<Vec<WebhookEventResponse>, AppError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM webhook_events \
+        WHERE ($1::TEXT IS NULL OR gateway = $1) \
+        AND ($2::TEXT IS NULL OR status = $2) \
+        AND ($3::TEXT IS NULL OR reference = $3) \
+        ORDER BY received_at DESC LIMIT $4",
+        EVENT_COLUMNS
+    ))
+    .bind(query.gateway)
+    .bind(query.status.map(|status| status.as_str()))
+    .bind(query.reference)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(row_to_webhook_event_response).collect()
+}
+
+/// Applies a stored event again, e.g. after fixing what made it fail. The
+/// payment and refund state machines still apply, so replaying an event that
+/// was processed changes nothing. Deliveries with a bad signature are never
+/// replayed.
+pub async fn replay_webhook_event_service(
+    pool: &PgPool,
+    provider: &PaymentProvider,
+    id: &str,
+) -> Result<WebhookEventResponse, AppError> {
+    let row =
+        sqlx::query("SELECT gateway, signature_valid, payload FROM webhook_events WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Webhook event not found".to_string()))?;
+
+    if !row.get::<bool, _>("signature_valid") {
+        return Err(AppError::Validation(
+            "Webhook events with an invalid signature cannot be replayed".to_string(),
+        ));
+    }
+    let payload: Value = row
+        .get::<Option<Json<Value>>, _>("payload")
+        .map(|payload| payload.0)
+        .ok_or_else(|| {
+            AppError::Validation("Webhook event has no payload to replay".to_string())
+        })?;
+    let gateway = provider.gateway(&row.get::<String, _>("gateway"))?;
+    let event = gateway.parse_webhook(payload)?;
+
+    sqlx::query(
+        "UPDATE webhook_events SET status = 'received', attempts = attempts + 1, error = NULL, \
+        claimed_at = $2 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    info!(id = %id, gateway = gateway.name(), event = %event.event, "Replaying webhook event");
+
+    apply_stored_event(pool, gateway, id, event).await?;
+
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM webhook_events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    row_to_webhook_event_response(&row)
+}
+
+//=====================================================================================
+// Helper Functions
+//=====================================================================================
+
+/// Confirms a stored event with its gateway, applies it and records the
+/// outcome on it.
+async fn apply_stored_event(
+    pool: &PgPool,
+    gateway: &dyn PaymentGateway,
+    id: &str,
+    event: WebhookEvent,
+) -> Result<WebhookEventStatus, AppError> {
+    let result = match gateway.confirm_webhook(event).await {
+        Ok(event) => process_webhook_event(pool, gateway.name(), event).await,
+        Err(e) => Err(e),
+    };
+    let (status, error) = match &result {
+        Ok(status) => (*status, None),
+        Err(e) => (WebhookEventStatus::Failed, Some(e.to_string())),
+    };
+
+    sqlx::query(
+        "UPDATE webhook_events SET status = $2, error = $3, \
+        processed_at = CASE WHEN $2 = 'failed' THEN processed_at ELSE $4 END \
+        WHERE id = $1",
+    )
+    .bind(id)
+    .bind(status.as_str())
+    .bind(error)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    result
+}
+
+/// Keeps a delivery that was never applied: `rejected` when its signature
+/// failed, `failed` when it was authentic but could not be read.
+async fn store_unreadable(
+    pool: &PgPool,
+    gateway: &str,
+    payload: Option<Value>,
+    signature_valid: bool,
+    e: &AppError,
+) -> Result<(), AppError> {
+    let status = if signature_valid {
+        WebhookEventStatus::Failed
+    } else {
+        WebhookEventStatus::Rejected
+    };
+    warn!(gateway, status = status.as_str(), error = %e, "Storing unusable webhook delivery");
+
+    sqlx::query(
+        "INSERT INTO webhook_events (id, gateway, event, signature_valid, status, error, payload) \
+        VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(gateway)
+    .bind(
+        payload
+            .as_ref()
+            .and_then(|payload| payload["event"].as_str())
+            .map(|event| event.to_string()),
+    )
+    .bind(signature_valid)
+    .bind(status.as_str())
+    .bind(e.to_string())
+    .bind(payload.map(Json))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn row_to_webhook_event_response(
+    row: &sqlx::postgres::PgRow,
+) -> Result<WebhookEventResponse, AppError> {
+    Ok(WebhookEventResponse {
+        id: row.get("id"),
+        gateway: row.get("gateway"),
+        event_id: row.get("event_id"),
+        event: row.get("event"),
+        reference: row.get("reference"),
+        signature_valid: row.get("signature_valid"),
+        status: WebhookEventStatus::parse(&row.get::<String, _>("status"))?,
+        error: row.get("error"),
+        attempts: row.get("attempts"),
+        payload: row
+            .get::<Option<Json<Value>>, _>("payload")
+            .map(|payload| payload.0),
+        received_at: row.get("received_at"),
+        processed_at: row.get("processed_at"),
+    })
+}